@@ -0,0 +1,5 @@
+pub const NES_SCREEN_WIDTH: u16 = 256;
+pub const NES_SCREEN_HEIGHT: u16 = 240;
+pub const STACK_ADDRESS: u16 = 0x0100;
+pub const CPU_CLOCK_RATE: u32 = 1_789_773;
+pub const AUDIO_SAMPLE_RATE: u32 = 44_100;
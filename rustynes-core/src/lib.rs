@@ -0,0 +1,18 @@
+//! NES emulation core, with no front end or platform dependency.
+//!
+//! Load a cartridge into a [`Nes`], give the [`InputDevice`]s in its ports their inputs, call [`Nes::run_frame`]
+//! and read the picture from [`Nes::get_screen`] and the sound from [`Nes::take_audio_samples`].
+
+#![allow(clippy::cast_lossless, clippy::similar_names, clippy::module_name_repetitions, clippy::new_without_default)]
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+mod tests;
+pub mod constants;
+mod nes;
+
+pub use nes::{Nes, CpuInfo};
+pub use nes::{ComponentCartridge, CartridgeInfo, CartridgeError, LoadOptions, ConsoleType, HeaderFormat, Mirror, Timing, Mapper};
+pub use nes::{InputDevice, InputPorts, Controller, FourScore, Zapper, ArkanoidPaddle};
+pub use nes::{Movie, MovieError, MovieFrame, Rewind};
+pub use nes::{Pixel, Channel};
+pub use nes::{StateError, StateReader, StateValue, StateWriter};
@@ -0,0 +1,641 @@
+mod cartridge;
+mod mappers;
+mod cpu;
+mod ppu;
+mod apu;
+mod bus;
+mod state;
+mod rewind;
+mod movie;
+mod input;
+
+pub use cartridge::{ComponentCartridge, Mirror, CartridgeInfo, CartridgeError, LoadOptions};
+pub use cartridge::{ConsoleType, HeaderFormat, Timing};
+#[cfg(test)]
+pub(crate) use cartridge::{GameDatabase, HeaderCartridge};
+pub use mappers::Mapper;
+pub(crate) use cpu::{Component6502, Flags, ADDRESSING_MODES};
+pub use ppu::Pixel;
+pub(crate) use ppu::{Component2C02, ScreenData};
+pub use apu::Channel;
+pub(crate) use apu::Component2A03;
+pub(crate) use bus::{Bus, IrqSource};
+pub use state::{StateError, StateReader, StateValue, StateWriter};
+pub(crate) use state::SaveState;
+pub use rewind::Rewind;
+pub use movie::{Movie, MovieError, MovieFrame};
+pub use input::{ArkanoidPaddle, Controller, FourScore, InputDevice, InputPorts, Zapper};
+
+use crate::constants::STACK_ADDRESS;
+use state::{STATE_HEADER_SIZE, STATE_MAGIC, STATE_VERSION};
+
+#[cfg(feature = "nestest")]
+pub struct Snapshot {
+    cpu: Component6502,
+    ppu: Component2C02,
+    bus: bus::Bus,
+}
+
+#[derive(Debug)]
+pub struct Nes {
+    pub input_ports: InputPorts,
+    cartridge: ComponentCartridge,
+    cpu: Component6502,
+    ppu: Component2C02,
+    apu: Component2A03,
+    bus: bus::Bus,
+
+    screen: ScreenData,
+    pub pause: bool,
+    current_palette: u8,
+    pub total_clock_ticks: u128,
+    /// nestest log line of the instruction started by the last tick, if it started one
+    #[cfg(feature = "nestest")]
+    pub nestest_log: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct CpuInfo {
+    pub program_counter: u16,
+    pub reg_a: u8,
+    pub reg_x: u8,
+    pub reg_y: u8,
+    pub stack_pointer: u8,
+    pub cycles: u8,
+}
+
+impl Nes {
+    pub fn new() -> Self {
+        Self {
+            input_ports: [Box::new(Controller::new()), Box::new(Controller::new())],
+            cartridge: ComponentCartridge::new(),
+            cpu: Component6502::new(),
+            ppu: Component2C02::new(),
+            apu: Component2A03::new(),
+            bus: bus::Bus::new(),
+
+            screen: ScreenData::new(),
+            pause: true,
+            current_palette: 0,
+            total_clock_ticks: 0,
+            #[cfg(feature = "nestest")]
+            nestest_log: None,
+        }
+    }
+    
+    #[allow(dead_code)]
+    pub fn cpu_read(&mut self, addr: u16) -> u8 {
+        self.cpu.read(addr, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus)
+    }
+
+    #[allow(dead_code)]
+    pub fn cpu_write(&mut self, addr: u16, data: u8) {
+        self.cpu.write(addr, data, &mut self.input_ports, &mut self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+    }
+
+    /// The current cartridge is kept if the new one can't be loaded
+    pub fn load_cartridge(&mut self, path: &str, options: &LoadOptions) -> Result<(), CartridgeError> {
+        self.insert_cartridge(ComponentCartridge::from_path_with_options(path, options)?);
+
+        Ok(())
+    }
+
+    /// For cartridges built with `ComponentCartridge::from_bytes` or `from_reader`
+    pub fn insert_cartridge(&mut self, cartridge: ComponentCartridge) {
+        self.cartridge = cartridge;
+    }
+
+    /// Persists battery-backed PRG-RAM next to the ROM
+    pub fn save_battery(&self) -> std::io::Result<()> {
+        self.cartridge.save_battery()
+    }
+
+    pub const fn get_cartridge_info(&self) -> &CartridgeInfo {
+        self.cartridge.info()
+    }
+
+    /// Famicom Disk System only, 0 for cartridges
+    pub fn get_disk_sides_count(&self) -> usize {
+        self.cartridge.disk_sides_count()
+    }
+
+    pub fn get_disk_side(&self) -> Option<usize> {
+        self.cartridge.disk_side()
+    }
+
+    /// Buttons of controllers 1 to 4, 3 and 4 being on a Four Score, 0 for what isn't plugged
+    pub fn get_pad_states(&self) -> [u8; 4] {
+        std::array::from_fn(|pad| self.input_ports[pad % 2].buttons(pad / 2))
+    }
+
+    pub fn set_pad_states(&mut self, states: [u8; 4]) {
+        for (pad, state) in states.into_iter().enumerate() {
+            self.input_ports[pad % 2].set_buttons(pad / 2, state);
+        }
+    }
+
+    /// Snapshot of the whole machine, tied to the current cartridge
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut state = StateWriter::new();
+
+        state.write(&STATE_MAGIC);
+        state.write(&STATE_VERSION);
+        state.write(&self.cartridge.info().crc32);
+
+        self.cpu.save_state(&mut state);
+        self.ppu.save_state(&mut state);
+        self.apu.save_state(&mut state);
+        self.bus.save_state(&mut state);
+        for device in &self.input_ports {
+            device.save_state(&mut state);
+        }
+        self.cartridge.save_state(&mut state);
+        state.write(&self.total_clock_ticks);
+
+        state.into_bytes()
+    }
+
+    /// The machine is left untouched if the state can't be loaded
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), StateError> {
+        let mut state = StateReader::new(data);
+
+        if state.read::<[u8; 4]>().ok() != Some(STATE_MAGIC) {
+            return Err(StateError::BadMagic);
+        }
+        let version = state.read::<u16>()?;
+        if version != STATE_VERSION {
+            return Err(StateError::UnsupportedVersion(version));
+        }
+        let crc32 = state.read::<u32>()?;
+        if crc32 != self.cartridge.info().crc32 {
+            return Err(StateError::WrongCartridge { expected: self.cartridge.info().crc32, found: crc32 });
+        }
+
+        let backup = self.save_state();
+        if let Err(error) = self.load_components(&mut state) {
+            self.load_components(&mut StateReader::new(&backup[STATE_HEADER_SIZE..]))
+                .expect("a state saved by this machine loads back");
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    fn load_components(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.cpu.load_state(state)?;
+        self.ppu.load_state(state)?;
+        self.apu.load_state(state)?;
+        self.bus.load_state(state)?;
+        for device in &mut self.input_ports {
+            device.load_state(state)?;
+        }
+        self.cartridge.load_state(state)?;
+        self.total_clock_ticks = state.read()?;
+
+        if !state.is_empty() {
+            return Err(StateError::TrailingData);
+        }
+
+        Ok(())
+    }
+
+    /// Ejects the disk and inserts the next side, going back to the first one after the last
+    pub fn switch_disk_side(&mut self) {
+        let count = self.cartridge.disk_sides_count();
+        if count > 0 {
+            let side = self.cartridge.disk_side().map_or(0, |side| (side + 1) % count);
+            self.cartridge.insert_disk_side(Some(side));
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.cpu.reset(&mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+        self.cartridge.reset();
+        self.apu.reset();
+        self.total_clock_ticks = 0;
+    }
+
+    /// Like turning the console off and on, the cartridge keeps its PRG-RAM
+    pub fn power_on(&mut self) {
+        for device in &mut self.input_ports {
+            device.reset();
+        }
+        self.cpu = Component6502::new();
+        self.ppu = Component2C02::new();
+        self.bus = bus::Bus::new();
+        self.apu.power_on();
+        self.reset();
+    }
+
+    /// Gathers the state of every device able to request an interrupt
+    fn update_irq_line(&mut self) {
+        self.bus.set_irq(IrqSource::ApuFrameCounter, self.apu.is_frame_irq_asserted());
+        self.bus.set_irq(IrqSource::ApuDmc, self.apu.is_dmc_irq_asserted());
+        self.bus.set_irq(IrqSource::Mapper, self.cartridge.is_irq_asserted());
+    }
+
+    pub fn handle_dma(&mut self) {
+        if self.total_clock_ticks % 2 == 0 { // on even cycles
+            self.bus.dma_data = self.cpu.read((self.bus.dma_page as u16) << 8 | self.bus.dma_addr as u16, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+        } else { // on odd cycles
+            self.ppu.oam.write(self.bus.dma_addr, self.bus.dma_data);
+            self.bus.dma_addr = self.bus.dma_addr.wrapping_add(1);
+            if self.bus.dma_addr == 0 { // DMA transfer complete because it wraps around
+                self.bus.is_dma_active = false;
+                self.bus.dma_wait_for_sync = true;
+            }
+        }
+    }
+
+    pub fn tick(&mut self) {
+        #[cfg(feature = "nestest")]
+        let snapshot = Snapshot {
+            cpu: self.cpu,
+            bus: self.bus,
+            ppu: self.ppu,
+        };
+
+        #[cfg(feature = "nestest")]
+        let display_log = self.cpu.cycles == 0;
+
+        self.ppu.tick(&mut self.screen, &self.cartridge);
+        self.cartridge.ppu_tick(self.ppu.address_bus);
+        if self.ppu.get_cycle() == 0 {
+            for device in &mut self.input_ports {
+                device.ppu_scanline(self.ppu.get_scanline(), &self.screen.displayable_screen);
+            }
+        }
+        
+        if self.total_clock_ticks % 3 == 0 {
+            self.cartridge.cpu_tick();
+            self.apu.set_expansion_output(self.cartridge.audio_output());
+            self.apu.tick();
+            self.update_irq_line();
+
+            if self.bus.dmc_stall_cycles > 0 {
+                self.bus.dmc_stall_cycles -= 1;
+            } else if self.bus.is_dma_active {
+                if self.bus.dma_wait_for_sync {
+                    if self.total_clock_ticks % 2 == 1 {
+                        self.bus.dma_wait_for_sync = false;
+                    }
+                } else {
+                    self.handle_dma()
+                }
+            } else {
+                // Interrupts are only polled between instructions
+                if self.cpu.cycles == 0 && self.bus.is_irq_asserted() && !self.cpu.irq_disable_polled {
+                    self.cpu.irq(&mut self.input_ports, &mut self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+                }
+                self.cpu.tick(&mut self.input_ports, &mut self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+            }
+
+            if let Some(addr) = self.apu.dmc_pending_read() {
+                let data = self.cpu.read(addr, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+                self.apu.dmc_load_sample(data);
+                self.bus.dmc_stall_cycles = 4;
+            }
+        }
+
+        if self.ppu.nmi_occurred {
+            self.ppu.nmi_occurred = false;
+            self.cpu.nmi(&mut self.input_ports, &mut self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+        }
+
+        self.total_clock_ticks += 1;
+        
+        #[cfg(feature = "nestest")]
+        {
+            self.nestest_log = display_log.then(|| Self::nestest_format_log(&snapshot));
+        }
+    }
+
+    #[cfg(feature = "nestest")]
+    pub fn nestest_format_log(snapshot: &Snapshot) -> String {
+        use std::fmt::Write;
+        
+        let mut line = String::new();
+
+        let instruction = &snapshot.cpu.lookup[snapshot.cpu.read(&snapshot.bus, snapshot.cpu.pc) as usize];
+
+        // opcode
+        write!(&mut line, "{:04X}  ", snapshot.cpu.pc).unwrap();
+        // operands
+        let operands_nb = instruction.addr_mode.get_operands_nb();
+        let operands = (0..=operands_nb).map(|i| snapshot.cpu.read(&snapshot.bus, snapshot.cpu.pc.wrapping_add(i as u16)));
+        for operand in operands.clone() {
+            write!(&mut line, "{operand:02X} ").unwrap();
+        }
+        match operands_nb {
+            0 => write!(&mut line, "      ").unwrap(),
+            1 => write!(&mut line, "   ").unwrap(),
+            2 => write!(&mut line, "").unwrap(),
+            _ => {}
+        }
+        // instruction name
+        write!(&mut line, "{} ", instruction.name).unwrap();
+        // detailed operands
+        let operands_vec = operands.collect::<Vec<u8>>();
+        write!(&mut line, "{}", instruction.addr_mode.format_operands(&operands_vec, &snapshot.bus.ram, operands_vec[0], snapshot.cpu.pc, snapshot.cpu.x, snapshot.cpu.y)).unwrap();
+        write!(&mut line, "  ").unwrap();
+        // accumulator
+        write!(&mut line, "A:{:02X} ", snapshot.cpu.a).unwrap();
+        // x register
+        write!(&mut line, "X:{:02X} ", snapshot.cpu.x).unwrap();
+        // y register
+        write!(&mut line, "Y:{:02X} ", snapshot.cpu.y).unwrap();
+        // flags
+        write!(&mut line, "P:{:02X} ", snapshot.cpu.status).unwrap();
+        // stack pointer
+        write!(&mut line, "SP:{:02X}", snapshot.cpu.sp).unwrap();
+
+        line
+    }
+
+    pub const fn is_cpu_instruction_complete(&self) -> bool {
+        self.cpu.cycles == 0
+    }
+
+    /// Runs until the PPU finishes the frame
+    pub fn run_frame(&mut self) {
+        loop {
+            self.tick();
+            if self.is_ppu_frame_complete() {
+                break;
+            }
+        }
+        self.set_ppu_frame_complete(false);
+    }
+
+    pub const fn is_ppu_frame_complete(&self) -> bool {
+        self.ppu.is_frame_complete
+    }
+
+    pub fn set_ppu_frame_complete(&mut self, value: bool) {
+        self.ppu.is_frame_complete = value;
+    }
+
+    pub fn get_ram(&self, low: u16, high: u16) -> (u16, u16, &[u8]) {
+        if low > high {
+            return (low, high, &[]); // Otherwise taking the slice panics
+        }
+        (low, high, &self.bus.ram[low as usize..high as usize])
+    }
+
+    pub const fn get_cpu_info(&self) -> CpuInfo {
+        CpuInfo {
+            program_counter: self.cpu.pc,
+            reg_a: self.cpu.a,
+            reg_x: self.cpu.x,
+            reg_y: self.cpu.y,
+            stack_pointer: self.cpu.sp,
+            cycles: self.cpu.cycles,
+        }
+    }
+
+    pub const fn get_cpu_flags(&self) -> u8 {
+        self.cpu.status
+    }
+
+    /// The last frame, row by row
+    pub const fn get_screen(&self) -> &[Pixel] {
+        &self.screen.displayable_screen
+    }
+
+    /// Checksum of the last frame, to compare screens without keeping them around
+    pub fn get_screen_crc32(&self) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        for color in self.get_screen() {
+            hasher.update(&[color.r, color.g, color.b]);
+        }
+        hasher.finalize()
+    }
+
+    pub fn get_pattern_table(&mut self, index: u8) -> &[Pixel] {
+        self.ppu.fill_pattern_table(index, self.current_palette, &mut self.screen, &self.cartridge);
+
+        &self.screen.displayable_pattern_table[index as usize]
+    }
+
+    /// Audio produced since the last call, as mono samples centered around 0.0
+    pub fn take_audio_samples(&mut self) -> Vec<f32> {
+        self.apu.take_samples()
+    }
+
+    pub const fn get_audio_sample_rate(&self) -> u32 {
+        self.apu.sample_rate()
+    }
+
+    #[allow(dead_code)]
+    pub fn set_audio_sample_rate(&mut self, sample_rate: u32) {
+        self.apu.set_sample_rate(sample_rate);
+    }
+
+    /// Speeds up or slows down audio production by a small ratio to match the front end's consumption
+    pub fn set_audio_rate_adjustment(&mut self, adjustment: f64) {
+        self.apu.set_rate_adjustment(adjustment);
+    }
+
+    /// Starts writing the audio output to a 16-bit PCM WAV file at `path`,
+    /// with each channel also written to its own file next to it if `with_stems` is set
+    pub fn start_audio_recording(&mut self, path: &str, with_stems: bool) -> std::io::Result<()> {
+        self.apu.start_recording(std::path::Path::new(path), with_stems)
+    }
+
+    pub fn stop_audio_recording(&mut self) -> std::io::Result<()> {
+        self.apu.stop_recording()
+    }
+
+    pub const fn is_recording_audio(&self) -> bool {
+        self.apu.is_recording()
+    }
+
+    pub const fn get_channel_volume(&self, channel: Channel) -> f32 {
+        self.apu.channel_volume(channel)
+    }
+
+    pub fn set_channel_volume(&mut self, channel: Channel, volume: f32) {
+        self.apu.set_channel_volume(channel, volume);
+    }
+
+    pub fn cycle_palette(&mut self) {
+        self.current_palette = (self.current_palette + 1) & 0x07;
+    }
+
+    pub fn get_instruction_string_range(&mut self, start: u16, end: u16) -> Vec<String> {
+        let count = start;
+        let mut instruction_string = Vec::new();
+
+        let mut local_pc = start;
+        for _ in count..end {
+            let opcode = self.cpu.peek(local_pc, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+            let instruction = &self.cpu.lookup[opcode as usize];
+            
+            match instruction.addr_mode {
+                ADDRESSING_MODES::ACC => {
+                    instruction_string.push(format!("{opcode:02X} (ACC) {}", instruction.name));
+                    local_pc = local_pc.wrapping_add(1);
+                }
+                ADDRESSING_MODES::IMP => {
+                    instruction_string.push(format!("{opcode:02X} (IMP) {}", instruction.name));
+                    local_pc = local_pc.wrapping_add(1);
+                }
+                ADDRESSING_MODES::IMM => {
+                    let data = self.cpu.peek(local_pc.wrapping_add(1), &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+
+                    instruction_string.push(format!("{opcode:02X} (IMM) {} #${data:02X}", instruction.name));
+                    local_pc = local_pc.wrapping_add(2);
+                }
+                ADDRESSING_MODES::ABS => {
+                    let lo = self.cpu.peek(local_pc.wrapping_add(1), &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+                    let hi = self.cpu.peek(local_pc.wrapping_add(2), &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+                    let addr = (hi as u16) << 8 | lo as u16;
+
+                    instruction_string.push(format!("{opcode:02X} (ABS) {} ${addr:04X}", instruction.name));
+                    local_pc = local_pc.wrapping_add(3);
+                }
+                ADDRESSING_MODES::ABX => {
+                    let lo = self.cpu.peek(local_pc.wrapping_add(1), &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+                    let hi = self.cpu.peek(local_pc.wrapping_add(2), &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+                    let addr = (hi as u16) << 8 | lo as u16;
+
+                    instruction_string.push(format!("{opcode:02X} (ABSx) {} ${addr:04X}, X", instruction.name));
+                    local_pc = local_pc.wrapping_add(3);
+                }
+                ADDRESSING_MODES::ABY => {
+                    let lo = self.cpu.peek(local_pc.wrapping_add(1), &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+                    let hi = self.cpu.peek(local_pc.wrapping_add(2), &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+                    let addr = (hi as u16) << 8 | lo as u16;
+
+                    instruction_string.push(format!("{opcode:02X} {} ${addr:04X}, Y", instruction.name));
+                    local_pc = local_pc.wrapping_add(3);
+                }
+                ADDRESSING_MODES::ZP0 => {
+                    let addr = self.cpu.peek(local_pc.wrapping_add(1), &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+
+                    instruction_string.push(format!("{opcode:02X} {} ${addr:02X}", instruction.name));
+                    local_pc = local_pc.wrapping_add(2);
+                }
+                ADDRESSING_MODES::ZPX => {
+                    let addr = self.cpu.peek(local_pc.wrapping_add(1), &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+
+                    instruction_string.push(format!("{opcode:02X} {} ${addr:02X}, X", instruction.name));
+                    local_pc = local_pc.wrapping_add(2);
+                }
+                ADDRESSING_MODES::ZPY => {
+                    let addr = self.cpu.peek(local_pc.wrapping_add(1), &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+
+                    instruction_string.push(format!("{opcode:02X} {} ${addr:02X}, Y", instruction.name));
+                    local_pc = local_pc.wrapping_add(2);
+                }
+                ADDRESSING_MODES::REL => {
+                    let addr = self.cpu.peek(local_pc.wrapping_add(1), &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+
+                    instruction_string.push(format!("{opcode:02X} (REL) {} ${addr:02X} [{:04X}]", instruction.name, local_pc.wrapping_add(2).wrapping_add(addr as u16)));
+                    local_pc = local_pc.wrapping_add(2);
+                }
+                ADDRESSING_MODES::IND => {
+                    let lo = self.cpu.peek(local_pc + 1, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+                    let hi = self.cpu.peek(local_pc + 2, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+                    let ptr = (hi as u16) << 8 | lo as u16;
+                    let addr = if lo == 0xFF {
+                        (self.cpu.peek(ptr & 0xFF00, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus) as u16) | (self.cpu.peek(ptr, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus) as u16) << 8
+                    } else {
+                        (self.cpu.peek(ptr + 1, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus) as u16) << 8 | self.cpu.peek(ptr, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus) as u16
+                    };
+
+                    instruction_string.push(format!("{opcode:02X} {} (${addr:04X})", instruction.name));
+                    local_pc = local_pc.wrapping_add(3);
+                }
+                ADDRESSING_MODES::IZX => {
+                    let addr = self.cpu.peek(local_pc + 1, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus) as u16;
+                    let lo = self.cpu.peek((addr + self.cpu.x as u16) & 0x00FF, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+                    let hi = self.cpu.peek((addr + self.cpu.x as u16 + 1) & 0x00FF, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+                    let ptr = (hi as u16) << 8 | lo as u16;
+
+                    instruction_string.push(format!("{opcode:02X} {} (${:02X}, X) @ {:02X} = {ptr:04X}", instruction.name, addr, addr + self.cpu.x as u16));
+                    local_pc = local_pc.wrapping_add(2);
+                }
+                ADDRESSING_MODES::IZY => {
+                    let addr = self.cpu.peek(local_pc + 1, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus) as u16;
+                    let lo = self.cpu.peek(addr & 0x00FF, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+                    let hi = self.cpu.peek((addr + 1) & 0x00FF, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+                    let mut ptr = (hi as u16) << 8 | lo as u16;
+                    ptr = ptr.wrapping_add(self.cpu.y as u16);
+
+                    instruction_string.push(format!("{opcode:02X} {} (${addr:02X}), Y = {:04X}", instruction.name, ptr + self.cpu.y as u16));
+                    local_pc = local_pc.wrapping_add(2);
+                }
+            }
+        }
+
+        instruction_string
+    }
+}
+
+#[cfg(test)]
+use crate::tests::TestState;
+
+#[cfg(test)]
+impl Nes {
+    pub fn test_set_initial_state(&mut self, state: &TestState) {
+        self.cpu.pc = state.pc;
+        self.cpu.sp = state.s;
+        self.cpu.a = state.a;
+        self.cpu.x = state.x;
+        self.cpu.y = state.y;
+        self.cpu.status = state.p;
+
+        for (addr, data) in &state.ram {
+            println!("[setup] writing {:02X} ({}) to {:04X} ({})", *data, *data, *addr, *addr);
+            self.bus.ram[*addr as usize] = *data;
+        }
+        println!("[setup] pc {:04X} ({}) sp {:02X} a {:02X} x {:02X} y {:02X} status {:02X}", self.cpu.pc, self.cpu.pc, self.cpu.sp, self.cpu.a, self.cpu.x, self.cpu.y, self.cpu.status);
+
+        println!("Setup complete\n");
+    }
+
+    pub fn test_end_state(&mut self, state: &TestState) -> bool {
+        self.cpu.pc == state.pc &&
+        self.cpu.sp == state.s &&
+        self.cpu.a == state.a &&
+        self.cpu.x == state.x &&
+        self.cpu.y == state.y &&
+        self.cpu.status == state.p &&
+        {
+            for (addr, data) in &state.ram {
+                if self.test_read(*addr) != *data {
+                    return false;
+                }
+            }
+            true
+        }
+    }
+
+    pub fn test_read(&mut self, addr: u16) -> u8 {
+        self.cpu.read(addr, &mut self.input_ports,  &self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus)
+    }
+
+    pub fn test_write(&mut self, addr: u16, data: u8) {
+        self.cpu.write(addr, data, &mut self.input_ports, &mut self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+    }
+
+    /// Goes through the real bus, `test_read` and `test_write` only see the flat test memory
+    pub fn test_bus_read(&mut self, addr: u16) -> u8 {
+        self.bus.cpu_read(addr, false, &mut self.input_ports, &self.cartridge, &mut self.ppu, &mut self.apu)
+    }
+
+    pub fn test_bus_write(&mut self, addr: u16, data: u8) {
+        self.bus.cpu_write(addr, data, &mut self.input_ports, &mut self.cartridge, &mut self.ppu, &mut self.apu);
+    }
+
+    pub fn test_reset(&mut self) {
+        self.bus.ram = [0; 64 * 1024];
+
+        self.cpu.test_reset();
+    }
+
+    pub fn test_tick(&mut self) {
+        self.cpu.test_tick(&mut self.input_ports, &mut self.cartridge, &mut self.ppu, &mut self.apu, &mut self.bus);
+    }
+}
@@ -0,0 +1,396 @@
+#![allow(clippy::cast_precision_loss)]
+
+mod envelope;
+mod length_counter;
+mod pulse;
+mod triangle;
+mod noise;
+mod dmc;
+mod resampler;
+mod recorder;
+
+use crate::constants::{AUDIO_SAMPLE_RATE, CPU_CLOCK_RATE};
+use crate::nes::state::{SaveState, StateError, StateReader, StateWriter};
+use pulse::Pulse;
+use triangle::Triangle;
+use noise::Noise;
+use dmc::Dmc;
+use resampler::Resampler;
+use recorder::AudioRecorder;
+
+use std::io;
+use std::path::Path;
+
+/// Frame counter steps, in CPU cycles
+const FRAME_STEP_1: u32 = 7457;
+const FRAME_STEP_2: u32 = 14913;
+const FRAME_STEP_3: u32 = 22371;
+const FRAME_STEP_4: u32 = 29829;
+const FRAME_LENGTH_4_STEP: u32 = 29830;
+const FRAME_STEP_5: u32 = 37281;
+const FRAME_LENGTH_5_STEP: u32 = 37282;
+
+/// Samples are dropped past this many if the front end doesn't drain them
+const MAX_BUFFERED_SAMPLES: usize = AUDIO_SAMPLE_RATE as usize;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Channel {
+    Pulse1,
+    Pulse2,
+    Triangle,
+    Noise,
+    Dmc,
+    /// Cartridge audio, like the Famicom Disk System wavetable channel
+    Expansion,
+}
+
+impl Channel {
+    pub const ALL: [Self; 6] = [Self::Pulse1, Self::Pulse2, Self::Triangle, Self::Noise, Self::Dmc, Self::Expansion];
+}
+
+#[derive(Debug)]
+pub struct Component2A03 {
+    pulse_1: Pulse,
+    pulse_2: Pulse,
+    triangle: Triangle,
+    noise: Noise,
+    dmc: Dmc,
+
+    /// `false` for the 4-step sequence, `true` for the 5-step one
+    frame_mode: bool,
+    frame_irq_inhibit: bool,
+    frame_irq_flag: bool,
+    frame_cycle: u32,
+    /// Writes to $4017 reset the sequencer after a 3 or 4 CPU cycle delay
+    frame_reset_delay: u8,
+    /// Pulse timers are clocked every other CPU cycle
+    is_odd_cycle: bool,
+
+    /// Cartridge audio level, already on the scale of the mixer output
+    expansion_output: f32,
+    /// Gain applied to each channel before mixing, indexed by `Channel`
+    channel_volumes: [f32; Channel::ALL.len()],
+    resampler: Resampler,
+    recorder: Option<AudioRecorder>,
+}
+
+impl Component2A03 {
+    pub fn new() -> Self {
+        Self {
+            pulse_1: Pulse::new(true),
+            pulse_2: Pulse::new(false),
+            triangle: Triangle::new(),
+            noise: Noise::new(),
+            dmc: Dmc::new(),
+
+            frame_mode: false,
+            frame_irq_inhibit: false,
+            frame_irq_flag: false,
+            frame_cycle: 0,
+            frame_reset_delay: 0,
+            is_odd_cycle: false,
+
+            expansion_output: 0.0,
+            channel_volumes: [1.0; Channel::ALL.len()],
+            resampler: Resampler::new(CPU_CLOCK_RATE, AUDIO_SAMPLE_RATE, MAX_BUFFERED_SAMPLES),
+            recorder: None,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.cpu_write(0x4015, 0x00);
+        self.frame_irq_flag = false;
+        self.dmc.irq_flag = false;
+        self.frame_cycle = 0;
+        self.frame_reset_delay = 0;
+    }
+
+    /// Back to the power-up state, keeping the host settings: sample rate, channel volumes and the recording
+    pub fn power_on(&mut self) {
+        let mut apu = Self::new();
+        std::mem::swap(&mut apu.resampler, &mut self.resampler);
+        apu.channel_volumes = self.channel_volumes;
+        apu.recorder = self.recorder.take();
+        *self = apu;
+    }
+
+    /// `read_only` reads, from debuggers, don't acknowledge the frame IRQ
+    pub fn cpu_read(&mut self, addr: u16, read_only: bool) -> u8 {
+        let mut data = 0x00;
+
+        // Status
+        if addr == 0x4015 {
+            data |= self.pulse_1.length_counter.is_active() as u8;
+            data |= (self.pulse_2.length_counter.is_active() as u8) << 1;
+            data |= (self.triangle.length_counter.is_active() as u8) << 2;
+            data |= (self.noise.length_counter.is_active() as u8) << 3;
+            data |= (self.dmc.is_active() as u8) << 4;
+            data |= (self.frame_irq_flag as u8) << 6;
+            data |= (self.dmc.irq_flag as u8) << 7;
+
+            if !read_only {
+                self.frame_irq_flag = false;
+            }
+        }
+
+        data
+    }
+
+    pub fn cpu_write(&mut self, addr: u16, data: u8) {
+        match addr {
+            // Pulse 1
+            0x4000..=0x4003 => self.pulse_1.write(addr, data),
+            // Pulse 2
+            0x4004..=0x4007 => self.pulse_2.write(addr, data),
+            // Triangle
+            0x4008..=0x400B => self.triangle.write(addr, data),
+            // Noise
+            0x400C..=0x400F => self.noise.write(addr, data),
+            // DMC
+            0x4010..=0x4013 => self.dmc.write(addr, data),
+            // Status
+            0x4015 => {
+                self.pulse_1.length_counter.set_enabled(data & 0x01 != 0);
+                self.pulse_2.length_counter.set_enabled(data & 0x02 != 0);
+                self.triangle.length_counter.set_enabled(data & 0x04 != 0);
+                self.noise.length_counter.set_enabled(data & 0x08 != 0);
+                self.dmc.set_enabled(data & 0x10 != 0);
+            }
+            // Frame counter
+            0x4017 => {
+                self.frame_mode = data & 0x80 != 0;
+                self.frame_irq_inhibit = data & 0x40 != 0;
+                if self.frame_irq_inhibit {
+                    self.frame_irq_flag = false;
+                }
+                self.frame_reset_delay = if self.is_odd_cycle { 4 } else { 3 };
+            }
+            _ => {}
+        }
+    }
+
+    pub const fn is_frame_irq_asserted(&self) -> bool {
+        self.frame_irq_flag
+    }
+
+    pub const fn is_dmc_irq_asserted(&self) -> bool {
+        self.dmc.irq_flag
+    }
+
+    /// Address the DMC wants to read from, the caller is expected to answer with `dmc_load_sample`
+    pub const fn dmc_pending_read(&self) -> Option<u16> {
+        self.dmc.pending_read()
+    }
+
+    pub fn dmc_load_sample(&mut self, data: u8) {
+        self.dmc.load_sample(data);
+    }
+
+    fn clock_quarter_frame(&mut self) {
+        self.pulse_1.envelope.clock();
+        self.pulse_2.envelope.clock();
+        self.triangle.clock_linear_counter();
+        self.noise.envelope.clock();
+    }
+
+    fn clock_half_frame(&mut self) {
+        self.pulse_1.length_counter.clock();
+        self.pulse_1.clock_sweep();
+        self.pulse_2.length_counter.clock();
+        self.pulse_2.clock_sweep();
+        self.triangle.length_counter.clock();
+        self.noise.length_counter.clock();
+    }
+
+    fn tick_frame_counter(&mut self) {
+        if self.frame_reset_delay > 0 {
+            self.frame_reset_delay -= 1;
+            if self.frame_reset_delay == 0 {
+                self.frame_cycle = 0;
+                if self.frame_mode {
+                    self.clock_quarter_frame();
+                    self.clock_half_frame();
+                }
+                return;
+            }
+        }
+
+        self.frame_cycle += 1;
+
+        match (self.frame_cycle, self.frame_mode) {
+            (FRAME_STEP_1 | FRAME_STEP_3, _) => self.clock_quarter_frame(),
+            (FRAME_STEP_2, _) | (FRAME_STEP_4, false) | (FRAME_STEP_5, true) => {
+                self.clock_quarter_frame();
+                self.clock_half_frame();
+            }
+            _ => {}
+        }
+
+        if !self.frame_mode && !self.frame_irq_inhibit && self.frame_cycle >= FRAME_STEP_4 - 1 {
+            self.frame_irq_flag = true;
+        }
+
+        let frame_length = if self.frame_mode { FRAME_LENGTH_5_STEP } else { FRAME_LENGTH_4_STEP };
+        if self.frame_cycle >= frame_length {
+            self.frame_cycle = 0;
+        }
+    }
+
+    /// Handle clock cycles, called once per CPU cycle
+    pub fn tick(&mut self) {
+        self.tick_frame_counter();
+
+        self.triangle.clock_timer();
+        self.noise.clock_timer();
+        self.dmc.clock_timer();
+        if self.is_odd_cycle {
+            self.pulse_1.clock_timer();
+            self.pulse_2.clock_timer();
+        }
+        self.is_odd_cycle = !self.is_odd_cycle;
+
+        let output = self.output();
+        self.resampler.clock(output);
+
+        let stems = match &self.recorder {
+            Some(recorder) if recorder.has_stems() => Some(Channel::ALL.map(|channel| self.solo_output(channel))),
+            _ => None,
+        };
+        if let Some(recorder) = &mut self.recorder {
+            recorder.clock(output, stems);
+        }
+    }
+
+    /// Set before each `tick` by the cartridge
+    pub fn set_expansion_output(&mut self, output: f32) {
+        self.expansion_output = output;
+    }
+
+    /// Raw output level of a single channel, before mixing
+    fn channel_output(&self, channel: Channel) -> f32 {
+        let output = match channel {
+            Channel::Pulse1 => self.pulse_1.output() as f32,
+            Channel::Pulse2 => self.pulse_2.output() as f32,
+            Channel::Triangle => self.triangle.output() as f32,
+            Channel::Noise => self.noise.output() as f32,
+            Channel::Dmc => self.dmc.output() as f32,
+            Channel::Expansion => self.expansion_output,
+        };
+
+        output * self.channel_volumes[channel as usize]
+    }
+
+    /// Non-linear DAC mixer
+    fn mix(pulse: f32, triangle: f32, noise: f32, dmc: f32) -> f32 {
+        let pulse_out = if pulse > 0.0 { 95.88 / (8128.0 / pulse + 100.0) } else { 0.0 };
+
+        let tnd = triangle / 8227.0 + noise / 12241.0 + dmc / 22638.0;
+        let tnd_out = if tnd > 0.0 { 159.79 / (1.0 / tnd + 100.0) } else { 0.0 };
+
+        pulse_out + tnd_out
+    }
+
+    /// Mixed output of all channels, between 0.0 and 1.0 without expansion audio
+    pub fn output(&self) -> f32 {
+        Self::mix(
+            self.channel_output(Channel::Pulse1) + self.channel_output(Channel::Pulse2),
+            self.channel_output(Channel::Triangle),
+            self.channel_output(Channel::Noise),
+            self.channel_output(Channel::Dmc),
+        ) + self.channel_output(Channel::Expansion)
+    }
+
+    /// Output of the mixer if only `channel` was playing
+    fn solo_output(&self, channel: Channel) -> f32 {
+        let output = self.channel_output(channel);
+
+        match channel {
+            Channel::Pulse1 | Channel::Pulse2 => Self::mix(output, 0.0, 0.0, 0.0),
+            Channel::Triangle => Self::mix(0.0, output, 0.0, 0.0),
+            Channel::Noise => Self::mix(0.0, 0.0, output, 0.0),
+            Channel::Dmc => Self::mix(0.0, 0.0, 0.0, output),
+            Channel::Expansion => output,
+        }
+    }
+
+    pub const fn channel_volume(&self, channel: Channel) -> f32 {
+        self.channel_volumes[channel as usize]
+    }
+
+    /// Gain of a channel in the mixer, 1.0 being the hardware level
+    pub fn set_channel_volume(&mut self, channel: Channel, volume: f32) {
+        self.channel_volumes[channel as usize] = volume.max(0.0);
+    }
+
+    pub const fn sample_rate(&self) -> u32 {
+        self.resampler.sample_rate()
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.resampler.set_sample_rate(sample_rate);
+    }
+
+    /// Ratio applied on top of the sample rate, see `Resampler::set_rate_adjustment`
+    pub fn set_rate_adjustment(&mut self, adjustment: f64) {
+        self.resampler.set_rate_adjustment(adjustment);
+    }
+
+    /// Samples produced since the last call, at `sample_rate()`
+    pub fn take_samples(&mut self) -> Vec<f32> {
+        self.resampler.take_samples()
+    }
+
+    /// Records the output at the current sample rate, restarting any recording in progress
+    pub fn start_recording(&mut self, path: &Path, with_stems: bool) -> io::Result<()> {
+        self.stop_recording()?;
+        self.recorder = Some(AudioRecorder::create(path, self.sample_rate(), with_stems)?);
+
+        Ok(())
+    }
+
+    pub fn stop_recording(&mut self) -> io::Result<()> {
+        match self.recorder.take() {
+            Some(recorder) => recorder.finish(),
+            None => Ok(()),
+        }
+    }
+
+    pub const fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+}
+
+/// The resampler, recorder and mixer settings belong to the front-end and aren't part of the state
+impl SaveState for Component2A03 {
+    fn save_state(&self, state: &mut StateWriter) {
+        self.pulse_1.save_state(state);
+        self.pulse_2.save_state(state);
+        self.triangle.save_state(state);
+        self.noise.save_state(state);
+        self.dmc.save_state(state);
+
+        state.write(&self.frame_mode);
+        state.write(&self.frame_irq_inhibit);
+        state.write(&self.frame_irq_flag);
+        state.write(&self.frame_cycle);
+        state.write(&self.frame_reset_delay);
+        state.write(&self.is_odd_cycle);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.pulse_1.load_state(state)?;
+        self.pulse_2.load_state(state)?;
+        self.triangle.load_state(state)?;
+        self.noise.load_state(state)?;
+        self.dmc.load_state(state)?;
+
+        self.frame_mode = state.read()?;
+        self.frame_irq_inhibit = state.read()?;
+        self.frame_irq_flag = state.read()?;
+        self.frame_cycle = state.read()?;
+        self.frame_reset_delay = state.read()?;
+        self.is_odd_cycle = state.read()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+use crate::nes::state::{SaveState, StateError, StateReader, StateWriter};
+
+/// NTSC periods, in CPU cycles
+const RATE_TABLE: [u16; 16] = [
+    428, 380, 340, 320, 286, 254, 226, 214, 190, 160, 142, 128, 106, 84, 72, 54,
+];
+
+/// Delta modulation channel
+#[derive(Debug, Copy, Clone)]
+pub struct Dmc {
+    pub enabled: bool,
+    irq_enabled: bool,
+    pub irq_flag: bool,
+    looping: bool,
+    timer_period: u16,
+    timer: u16,
+
+    output_level: u8,
+
+    sample_address: u16,
+    sample_length: u16,
+    current_address: u16,
+    bytes_remaining: u16,
+    sample_buffer: Option<u8>,
+
+    shift_register: u8,
+    bits_remaining: u8,
+    silence: bool,
+}
+
+impl Dmc {
+    pub const fn new() -> Self {
+        Self {
+            enabled: false,
+            irq_enabled: false,
+            irq_flag: false,
+            looping: false,
+            timer_period: RATE_TABLE[0],
+            timer: 0,
+
+            output_level: 0,
+
+            sample_address: 0xC000,
+            sample_length: 1,
+            current_address: 0xC000,
+            bytes_remaining: 0,
+            sample_buffer: None,
+
+            shift_register: 0,
+            bits_remaining: 8,
+            silence: true,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, data: u8) {
+        match addr & 0x0003 {
+            // IRQ enable, loop, frequency
+            0x0000 => {
+                self.irq_enabled = data & 0x80 != 0;
+                self.looping = data & 0x40 != 0;
+                self.timer_period = RATE_TABLE[(data & 0x0F) as usize];
+                if !self.irq_enabled {
+                    self.irq_flag = false;
+                }
+            }
+            // Direct load
+            0x0001 => self.output_level = data & 0x7F,
+            // Sample address
+            0x0002 => self.sample_address = 0xC000 | ((data as u16) << 6),
+            // Sample length
+            0x0003 => self.sample_length = ((data as u16) << 4) | 0x0001,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Set from bit 4 of $4015
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.irq_flag = false;
+
+        if !enabled {
+            self.bytes_remaining = 0;
+        } else if self.bytes_remaining == 0 {
+            self.restart();
+        }
+    }
+
+    fn restart(&mut self) {
+        self.current_address = self.sample_address;
+        self.bytes_remaining = self.sample_length;
+    }
+
+    pub const fn is_active(&self) -> bool {
+        self.bytes_remaining > 0
+    }
+
+    /// Address the memory reader wants to fetch, if the sample buffer needs refilling
+    pub const fn pending_read(&self) -> Option<u16> {
+        if self.sample_buffer.is_none() && self.bytes_remaining > 0 {
+            Some(self.current_address)
+        } else {
+            None
+        }
+    }
+
+    /// Hands the byte fetched at `pending_read()` to the memory reader
+    pub fn load_sample(&mut self, data: u8) {
+        self.sample_buffer = Some(data);
+        // The address wraps around to $8000, not $0000
+        self.current_address = if self.current_address == 0xFFFF { 0x8000 } else { self.current_address + 1 };
+        self.bytes_remaining -= 1;
+
+        if self.bytes_remaining == 0 {
+            if self.looping {
+                self.restart();
+            } else if self.irq_enabled {
+                self.irq_flag = true;
+            }
+        }
+    }
+
+    /// Clocked every CPU cycle, the rate table already accounts for the APU divider
+    pub fn clock_timer(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+            return;
+        }
+        self.timer = self.timer_period - 1;
+
+        if !self.silence {
+            if self.shift_register & 0x01 != 0 {
+                if self.output_level <= 125 {
+                    self.output_level += 2;
+                }
+            } else if self.output_level >= 2 {
+                self.output_level -= 2;
+            }
+        }
+        self.shift_register >>= 1;
+
+        self.bits_remaining -= 1;
+        if self.bits_remaining == 0 {
+            self.bits_remaining = 8;
+            match self.sample_buffer.take() {
+                Some(sample) => {
+                    self.silence = false;
+                    self.shift_register = sample;
+                }
+                None => self.silence = true,
+            }
+        }
+    }
+
+    pub const fn output(&self) -> u8 {
+        self.output_level
+    }
+}
+
+impl SaveState for Dmc {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.enabled);
+        state.write(&self.irq_enabled);
+        state.write(&self.irq_flag);
+        state.write(&self.looping);
+        state.write(&self.timer_period);
+        state.write(&self.timer);
+        state.write(&self.output_level);
+        state.write(&self.sample_address);
+        state.write(&self.sample_length);
+        state.write(&self.current_address);
+        state.write(&self.bytes_remaining);
+        state.write(&self.sample_buffer);
+        state.write(&self.shift_register);
+        state.write(&self.bits_remaining);
+        state.write(&self.silence);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.enabled = state.read()?;
+        self.irq_enabled = state.read()?;
+        self.irq_flag = state.read()?;
+        self.looping = state.read()?;
+        self.timer_period = state.read()?;
+        self.timer = state.read()?;
+        self.output_level = state.read()?;
+        self.sample_address = state.read()?;
+        self.sample_length = state.read()?;
+        self.current_address = state.read()?;
+        self.bytes_remaining = state.read()?;
+        self.sample_buffer = state.read()?;
+        self.shift_register = state.read()?;
+        self.bits_remaining = state.read()?;
+        self.silence = state.read()?;
+
+        Ok(())
+    }
+}
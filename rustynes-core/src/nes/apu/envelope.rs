@@ -0,0 +1,85 @@
+use crate::nes::state::{SaveState, StateError, StateReader, StateWriter};
+
+#[derive(Debug, Copy, Clone)]
+pub struct Envelope {
+    start: bool,
+    divider: u8,
+    decay_level: u8,
+    /// Also used as the length counter halt flag by the channels
+    pub looping: bool,
+    constant_volume: bool,
+    /// Either the constant volume or the divider period
+    volume: u8,
+}
+
+impl Envelope {
+    pub const fn new() -> Self {
+        Self {
+            start: false,
+            divider: 0,
+            decay_level: 0,
+            looping: false,
+            constant_volume: false,
+            volume: 0,
+        }
+    }
+
+    /// `--LC VVVV` part of $4000/$4004/$400C
+    pub fn write(&mut self, data: u8) {
+        self.looping = data & 0x20 != 0;
+        self.constant_volume = data & 0x10 != 0;
+        self.volume = data & 0x0F;
+    }
+
+    pub fn restart(&mut self) {
+        self.start = true;
+    }
+
+    /// Clocked by the frame counter on every quarter frame
+    pub fn clock(&mut self) {
+        if self.start {
+            self.start = false;
+            self.decay_level = 15;
+            self.divider = self.volume;
+        } else if self.divider == 0 {
+            self.divider = self.volume;
+            if self.decay_level > 0 {
+                self.decay_level -= 1;
+            } else if self.looping {
+                self.decay_level = 15;
+            }
+        } else {
+            self.divider -= 1;
+        }
+    }
+
+    pub const fn output(&self) -> u8 {
+        if self.constant_volume {
+            self.volume
+        } else {
+            self.decay_level
+        }
+    }
+}
+
+impl SaveState for Envelope {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.start);
+        state.write(&self.divider);
+        state.write(&self.decay_level);
+        state.write(&self.looping);
+        state.write(&self.constant_volume);
+        state.write(&self.volume);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.start = state.read()?;
+        self.divider = state.read()?;
+        self.decay_level = state.read()?;
+        self.looping = state.read()?;
+        self.constant_volume = state.read()?;
+        self.volume = state.read()?;
+
+        Ok(())
+    }
+}
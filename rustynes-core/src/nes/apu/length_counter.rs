@@ -0,0 +1,65 @@
+use crate::nes::state::{SaveState, StateError, StateReader, StateWriter};
+
+const LENGTH_TABLE: [u8; 32] = [
+    10, 254, 20,  2, 40,  4, 80,  6, 160,  8, 60, 10, 14, 12, 26, 14,
+    12,  16, 24, 18, 48, 20, 96, 22, 192, 24, 72, 26, 16, 28, 32, 30,
+];
+
+#[derive(Debug, Copy, Clone)]
+pub struct LengthCounter {
+    enabled: bool,
+    pub halt: bool,
+    counter: u8,
+}
+
+impl LengthCounter {
+    pub const fn new() -> Self {
+        Self {
+            enabled: false,
+            halt: false,
+            counter: 0,
+        }
+    }
+
+    /// Set from the channel bit of $4015, disabling clears the counter immediately
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.counter = 0;
+        }
+    }
+
+    /// `LLLL L---` part of $4003/$4007/$400B/$400F
+    pub fn load(&mut self, index: u8) {
+        if self.enabled {
+            self.counter = LENGTH_TABLE[(index & 0x1F) as usize];
+        }
+    }
+
+    /// Clocked by the frame counter on every half frame
+    pub fn clock(&mut self) {
+        if !self.halt && self.counter > 0 {
+            self.counter -= 1;
+        }
+    }
+
+    pub const fn is_active(&self) -> bool {
+        self.counter > 0
+    }
+}
+
+impl SaveState for LengthCounter {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.enabled);
+        state.write(&self.halt);
+        state.write(&self.counter);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.enabled = state.read()?;
+        self.halt = state.read()?;
+        self.counter = state.read()?;
+
+        Ok(())
+    }
+}
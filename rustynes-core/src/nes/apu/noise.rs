@@ -0,0 +1,103 @@
+use super::envelope::Envelope;
+use super::length_counter::LengthCounter;
+use crate::nes::state::{SaveState, StateError, StateReader, StateWriter};
+
+/// NTSC periods, in CPU cycles
+const PERIOD_TABLE: [u16; 16] = [
+    4, 8, 16, 32, 64, 96, 128, 160, 202, 254, 380, 508, 762, 1016, 2034, 4068,
+];
+
+#[derive(Debug, Copy, Clone)]
+pub struct Noise {
+    /// Short mode taps bit 6 instead of bit 1
+    mode: bool,
+    shift_register: u16,
+    timer_period: u16,
+    timer: u16,
+
+    pub envelope: Envelope,
+    pub length_counter: LengthCounter,
+}
+
+impl Noise {
+    pub const fn new() -> Self {
+        Self {
+            mode: false,
+            shift_register: 1,
+            timer_period: PERIOD_TABLE[0],
+            timer: 0,
+
+            envelope: Envelope::new(),
+            length_counter: LengthCounter::new(),
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, data: u8) {
+        match addr & 0x0003 {
+            // Length counter halt, envelope
+            0x0000 => {
+                self.envelope.write(data);
+                self.length_counter.halt = self.envelope.looping;
+            }
+            // Unused
+            0x0001 => {}
+            // Mode, period
+            0x0002 => {
+                self.mode = data & 0x80 != 0;
+                self.timer_period = PERIOD_TABLE[(data & 0x0F) as usize];
+            }
+            // Length counter load
+            0x0003 => {
+                self.length_counter.load(data >> 3);
+                self.envelope.restart();
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Clocked every CPU cycle, the period table already accounts for the APU divider
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period - 1;
+
+            let tap = if self.mode { 6 } else { 1 };
+            let feedback = (self.shift_register & 0x0001) ^ ((self.shift_register >> tap) & 0x0001);
+            self.shift_register >>= 1;
+            self.shift_register |= feedback << 14;
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    pub const fn output(&self) -> u8 {
+        if !self.length_counter.is_active() || self.shift_register & 0x0001 != 0 {
+            return 0;
+        }
+
+        self.envelope.output()
+    }
+}
+
+impl SaveState for Noise {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.mode);
+        state.write(&self.shift_register);
+        state.write(&self.timer_period);
+        state.write(&self.timer);
+
+        self.envelope.save_state(state);
+        self.length_counter.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.mode = state.read()?;
+        self.shift_register = state.read()?;
+        self.timer_period = state.read()?;
+        self.timer = state.read()?;
+
+        self.envelope.load_state(state)?;
+        self.length_counter.load_state(state)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use super::envelope::Envelope;
+use super::length_counter::LengthCounter;
+use crate::nes::state::{SaveState, StateError, StateReader, StateWriter};
+
+const DUTY_TABLE: [[u8; 8]; 4] = [
+    [0, 1, 0, 0, 0, 0, 0, 0],
+    [0, 1, 1, 0, 0, 0, 0, 0],
+    [0, 1, 1, 1, 1, 0, 0, 0],
+    [1, 0, 0, 1, 1, 1, 1, 1],
+];
+
+#[derive(Debug, Copy, Clone)]
+pub struct Pulse {
+    /// Pulse 1 negates with one's complement, pulse 2 with two's complement
+    is_first_channel: bool,
+
+    duty: u8,
+    sequence_step: u8,
+    timer_period: u16,
+    timer: u16,
+
+    sweep_enabled: bool,
+    sweep_period: u8,
+    sweep_negate: bool,
+    sweep_shift: u8,
+    sweep_reload: bool,
+    sweep_divider: u8,
+
+    pub envelope: Envelope,
+    pub length_counter: LengthCounter,
+}
+
+impl Pulse {
+    pub const fn new(is_first_channel: bool) -> Self {
+        Self {
+            is_first_channel,
+
+            duty: 0,
+            sequence_step: 0,
+            timer_period: 0,
+            timer: 0,
+
+            sweep_enabled: false,
+            sweep_period: 0,
+            sweep_negate: false,
+            sweep_shift: 0,
+            sweep_reload: false,
+            sweep_divider: 0,
+
+            envelope: Envelope::new(),
+            length_counter: LengthCounter::new(),
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, data: u8) {
+        match addr & 0x0003 {
+            // Duty, length counter halt, envelope
+            0x0000 => {
+                self.duty = data >> 6;
+                self.envelope.write(data);
+                self.length_counter.halt = self.envelope.looping;
+            }
+            // Sweep unit
+            0x0001 => {
+                self.sweep_enabled = data & 0x80 != 0;
+                self.sweep_period = (data >> 4) & 0x07;
+                self.sweep_negate = data & 0x08 != 0;
+                self.sweep_shift = data & 0x07;
+                self.sweep_reload = true;
+            }
+            // Timer low
+            0x0002 => self.timer_period = (self.timer_period & 0x0700) | data as u16,
+            // Length counter load, timer high
+            0x0003 => {
+                self.timer_period = (self.timer_period & 0x00FF) | (((data & 0x07) as u16) << 8);
+                self.length_counter.load(data >> 3);
+                self.sequence_step = 0;
+                self.envelope.restart();
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Clocked every APU cycle (every other CPU cycle)
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+            self.sequence_step = (self.sequence_step + 1) & 0x07;
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    /// Clocked by the frame counter on every half frame
+    pub fn clock_sweep(&mut self) {
+        if self.sweep_divider == 0 && self.sweep_enabled && self.sweep_shift > 0 && !self.is_sweep_muting() {
+            self.timer_period = self.sweep_target_period();
+        }
+
+        if self.sweep_divider == 0 || self.sweep_reload {
+            self.sweep_divider = self.sweep_period;
+            self.sweep_reload = false;
+        } else {
+            self.sweep_divider -= 1;
+        }
+    }
+
+    fn sweep_target_period(&self) -> u16 {
+        let change = self.timer_period >> self.sweep_shift;
+
+        if self.sweep_negate {
+            let change = if self.is_first_channel { change + 1 } else { change };
+            self.timer_period.saturating_sub(change)
+        } else {
+            self.timer_period + change
+        }
+    }
+
+    /// The sweep unit mutes the channel even when it is disabled
+    fn is_sweep_muting(&self) -> bool {
+        self.timer_period < 8 || self.sweep_target_period() > 0x07FF
+    }
+
+    pub fn output(&self) -> u8 {
+        if !self.length_counter.is_active()
+        || self.is_sweep_muting()
+        || DUTY_TABLE[self.duty as usize][self.sequence_step as usize] == 0 {
+            return 0;
+        }
+
+        self.envelope.output()
+    }
+}
+
+impl SaveState for Pulse {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.duty);
+        state.write(&self.sequence_step);
+        state.write(&self.timer_period);
+        state.write(&self.timer);
+        state.write(&self.sweep_enabled);
+        state.write(&self.sweep_period);
+        state.write(&self.sweep_negate);
+        state.write(&self.sweep_shift);
+        state.write(&self.sweep_reload);
+        state.write(&self.sweep_divider);
+
+        self.envelope.save_state(state);
+        self.length_counter.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.duty = state.read()?;
+        self.sequence_step = state.read()?;
+        self.timer_period = state.read()?;
+        self.timer = state.read()?;
+        self.sweep_enabled = state.read()?;
+        self.sweep_period = state.read()?;
+        self.sweep_negate = state.read()?;
+        self.sweep_shift = state.read()?;
+        self.sweep_reload = state.read()?;
+        self.sweep_divider = state.read()?;
+
+        self.envelope.load_state(state)?;
+        self.length_counter.load_state(state)?;
+
+        Ok(())
+    }
+}
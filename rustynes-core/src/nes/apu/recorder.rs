@@ -0,0 +1,172 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use super::Channel;
+use super::resampler::Resampler;
+use crate::constants::CPU_CLOCK_RATE;
+
+/// Resampled audio is written out about once per frame
+const FLUSH_PERIOD: u32 = CPU_CLOCK_RATE / 60;
+const WAV_HEADER_SIZE: u32 = 44;
+
+/// Mono 16-bit PCM WAV file, the sizes in the header are filled in by `finish`
+#[derive(Debug)]
+pub struct WavWriter {
+    writer: BufWriter<File>,
+    data_size: u32,
+}
+
+impl WavWriter {
+    pub fn create(path: &Path, sample_rate: u32) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&0u32.to_le_bytes())?; // RIFF chunk size
+        writer.write_all(b"WAVE")?;
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?; // fmt chunk size
+        writer.write_all(&1u16.to_le_bytes())?; // PCM
+        writer.write_all(&1u16.to_le_bytes())?; // Channels
+        writer.write_all(&sample_rate.to_le_bytes())?;
+        writer.write_all(&(sample_rate * 2).to_le_bytes())?; // Byte rate
+        writer.write_all(&2u16.to_le_bytes())?; // Block align
+        writer.write_all(&16u16.to_le_bytes())?; // Bits per sample
+        writer.write_all(b"data")?;
+        writer.write_all(&0u32.to_le_bytes())?; // data chunk size
+
+        Ok(Self {
+            writer,
+            data_size: 0,
+        })
+    }
+
+    pub fn write_samples(&mut self, samples: &[f32]) -> io::Result<()> {
+        for sample in samples {
+            let sample = (sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16;
+            self.writer.write_all(&sample.to_le_bytes())?;
+        }
+        self.data_size += 2 * samples.len() as u32;
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.seek(SeekFrom::Start(4))?;
+        self.writer.write_all(&(WAV_HEADER_SIZE - 8 + self.data_size).to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(40))?;
+        self.writer.write_all(&self.data_size.to_le_bytes())?;
+        self.writer.flush()
+    }
+}
+
+#[derive(Debug)]
+struct Track {
+    resampler: Resampler,
+    writer: WavWriter,
+}
+
+impl Track {
+    fn create(path: &Path, sample_rate: u32) -> io::Result<Self> {
+        Ok(Self {
+            resampler: Resampler::new(CPU_CLOCK_RATE, sample_rate, sample_rate as usize),
+            writer: WavWriter::create(path, sample_rate)?,
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.write_samples(&self.resampler.take_samples())
+    }
+}
+
+/// Records the mixed output, and optionally each channel on its own, to WAV files.
+///
+/// It has its own resamplers so the recording isn't affected by the front end's rate adjustments
+#[derive(Debug)]
+pub struct AudioRecorder {
+    mix: Track,
+    /// One per `Channel`, if enabled
+    stems: Vec<Track>,
+    cycles_since_flush: u32,
+    /// The first write error, reported when the recording stops
+    error: Option<io::Error>,
+}
+
+impl AudioRecorder {
+    /// Stems are written next to `path`, suffixed by the channel name
+    pub fn create(path: &Path, sample_rate: u32, with_stems: bool) -> io::Result<Self> {
+        let stems = if with_stems {
+            Channel::ALL.iter().map(|channel| Track::create(&Self::stem_path(path, *channel), sample_rate)).collect::<io::Result<_>>()?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            mix: Track::create(path, sample_rate)?,
+            stems,
+            cycles_since_flush: 0,
+            error: None,
+        })
+    }
+
+    fn stem_path(path: &Path, channel: Channel) -> PathBuf {
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        let channel = match channel {
+            Channel::Pulse1 => "pulse1",
+            Channel::Pulse2 => "pulse2",
+            Channel::Triangle => "triangle",
+            Channel::Noise => "noise",
+            Channel::Dmc => "dmc",
+            Channel::Expansion => "expansion",
+        };
+
+        path.with_file_name(format!("{name}_{channel}.wav"))
+    }
+
+    pub const fn has_stems(&self) -> bool {
+        !self.stems.is_empty()
+    }
+
+    /// Called once per CPU cycle, `stems` are expected if they are enabled
+    pub fn clock(&mut self, mix: f32, stems: Option<[f32; Channel::ALL.len()]>) {
+        self.mix.resampler.clock(mix);
+        if let Some(stems) = stems {
+            for (track, output) in self.stems.iter_mut().zip(stems) {
+                track.resampler.clock(output);
+            }
+        }
+
+        self.cycles_since_flush += 1;
+        if self.cycles_since_flush >= FLUSH_PERIOD {
+            self.cycles_since_flush = 0;
+            if let Err(error) = self.flush() {
+                self.error.get_or_insert(error);
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.mix.flush()?;
+        for track in &mut self.stems {
+            track.flush()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+
+        self.flush()?;
+        self.mix.writer.finish()?;
+        for track in self.stems {
+            track.writer.finish()?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+#![allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+
+use std::f64::consts::PI;
+
+/// Width of the band-limited impulse, in output samples
+const KERNEL_WIDTH: usize = 16;
+/// Number of precomputed sub-sample offsets of the impulse
+const KERNEL_PHASES: usize = 64;
+/// Cutoff of the low-pass filter, relative to the output Nyquist frequency
+const KERNEL_CUTOFF: f64 = 0.9;
+/// Pole of the DC blocking filter
+const HIGH_PASS_POLE: f32 = 0.995;
+/// Completed samples are moved out of the delta buffer past this point
+const FLUSH_THRESHOLD: usize = 256;
+
+/// Converts the APU output, sampled once per CPU cycle, down to the audio sample rate.
+///
+/// Each change of amplitude is added to the output as a band-limited step,
+/// so the work done depends on how often the signal changes rather than on the input rate
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    kernel: Vec<[f32; KERNEL_WIDTH]>,
+
+    clock_rate: f64,
+    sample_rate: u32,
+    /// Output samples per input clock
+    ratio: f64,
+    /// Position of the next input clock, in output samples from the start of `deltas`
+    time: f64,
+
+    deltas: Vec<f32>,
+    amplitude: f32,
+    integrator: f32,
+    high_pass_input: f32,
+    high_pass_output: f32,
+
+    samples: Vec<f32>,
+    max_samples: usize,
+}
+
+impl Resampler {
+    pub fn new(clock_rate: u32, sample_rate: u32, max_samples: usize) -> Self {
+        let mut resampler = Self {
+            kernel: Self::build_kernel(),
+
+            clock_rate: clock_rate as f64,
+            sample_rate,
+            ratio: 0.0,
+            time: 0.0,
+
+            deltas: vec![0.0; FLUSH_THRESHOLD + KERNEL_WIDTH],
+            amplitude: 0.0,
+            integrator: 0.0,
+            high_pass_input: 0.0,
+            high_pass_output: 0.0,
+
+            samples: Vec::with_capacity(max_samples),
+            max_samples,
+        };
+        resampler.set_rate_adjustment(1.0);
+
+        resampler
+    }
+
+    /// Windowed sinc impulses, one per sub-sample offset, each normalized so steps keep their exact height
+    fn build_kernel() -> Vec<[f32; KERNEL_WIDTH]> {
+        let half_width = (KERNEL_WIDTH / 2) as f64;
+
+        (0..KERNEL_PHASES).map(|phase| {
+            let offset = phase as f64 / KERNEL_PHASES as f64;
+            let mut impulse = [0.0; KERNEL_WIDTH];
+
+            for (i, tap) in impulse.iter_mut().enumerate() {
+                let x = i as f64 - half_width + 1.0 - offset;
+                let sinc = if x == 0.0 { 1.0 } else { (PI * KERNEL_CUTOFF * x).sin() / (PI * KERNEL_CUTOFF * x) };
+                // Blackman window
+                let window = 0.42 + 0.5 * (PI * x / half_width).cos() + 0.08 * (2.0 * PI * x / half_width).cos();
+                *tap = (sinc * window) as f32;
+            }
+
+            let sum: f32 = impulse.iter().sum();
+            impulse.iter_mut().for_each(|tap| *tap /= sum);
+            impulse
+        }).collect()
+    }
+
+    pub const fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        self.set_rate_adjustment(1.0);
+    }
+
+    /// Slightly stretches or shrinks the output, used by front ends to keep their audio buffer at a steady fill
+    pub fn set_rate_adjustment(&mut self, adjustment: f64) {
+        self.ratio = self.sample_rate as f64 / self.clock_rate * adjustment;
+    }
+
+    /// Feeds the amplitude of one input clock
+    pub fn clock(&mut self, amplitude: f32) {
+        if amplitude != self.amplitude {
+            self.add_step(amplitude - self.amplitude);
+            self.amplitude = amplitude;
+        }
+
+        self.time += self.ratio;
+        if self.time as usize >= FLUSH_THRESHOLD {
+            self.flush();
+        }
+    }
+
+    fn add_step(&mut self, delta: f32) {
+        let index = self.time as usize;
+        let phase = ((self.time - index as f64) * KERNEL_PHASES as f64) as usize;
+
+        let impulse = &self.kernel[phase.min(KERNEL_PHASES - 1)];
+        for (sample, tap) in self.deltas[index..index + KERNEL_WIDTH].iter_mut().zip(impulse) {
+            *sample += delta * tap;
+        }
+    }
+
+    /// Moves the samples no future step can affect anymore to the output
+    fn flush(&mut self) {
+        let completed = self.time as usize;
+
+        for i in 0..completed {
+            self.integrator += self.deltas[i];
+
+            // DC blocker, the APU output is never negative
+            self.high_pass_output = self.integrator - self.high_pass_input + HIGH_PASS_POLE * self.high_pass_output;
+            self.high_pass_input = self.integrator;
+
+            if self.samples.len() < self.max_samples {
+                self.samples.push(self.high_pass_output);
+            }
+        }
+
+        self.deltas.copy_within(completed.., 0);
+        let len = self.deltas.len();
+        self.deltas[len - completed..].fill(0.0);
+        self.time -= completed as f64;
+    }
+
+    /// Samples produced since the last call
+    pub fn take_samples(&mut self) -> Vec<f32> {
+        self.flush();
+        std::mem::replace(&mut self.samples, Vec::with_capacity(self.max_samples))
+    }
+}
@@ -0,0 +1,123 @@
+use super::length_counter::LengthCounter;
+use crate::nes::state::{SaveState, StateError, StateReader, StateWriter};
+
+const SEQUENCE_TABLE: [u8; 32] = [
+    15, 14, 13, 12, 11, 10,  9,  8,  7,  6,  5,  4,  3,  2,  1,  0,
+     0,  1,  2,  3,  4,  5,  6,  7,  8,  9, 10, 11, 12, 13, 14, 15,
+];
+
+#[derive(Debug, Copy, Clone)]
+pub struct Triangle {
+    sequence_step: u8,
+    timer_period: u16,
+    timer: u16,
+
+    /// Also used as the length counter halt flag
+    control: bool,
+    linear_counter_period: u8,
+    linear_counter: u8,
+    linear_counter_reload: bool,
+
+    pub length_counter: LengthCounter,
+}
+
+impl Triangle {
+    pub const fn new() -> Self {
+        Self {
+            sequence_step: 0,
+            timer_period: 0,
+            timer: 0,
+
+            control: false,
+            linear_counter_period: 0,
+            linear_counter: 0,
+            linear_counter_reload: false,
+
+            length_counter: LengthCounter::new(),
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, data: u8) {
+        match addr & 0x0003 {
+            // Linear counter
+            0x0000 => {
+                self.control = data & 0x80 != 0;
+                self.linear_counter_period = data & 0x7F;
+                self.length_counter.halt = self.control;
+            }
+            // Unused
+            0x0001 => {}
+            // Timer low
+            0x0002 => self.timer_period = (self.timer_period & 0x0700) | data as u16,
+            // Length counter load, timer high
+            0x0003 => {
+                self.timer_period = (self.timer_period & 0x00FF) | (((data & 0x07) as u16) << 8);
+                self.length_counter.load(data >> 3);
+                self.linear_counter_reload = true;
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Clocked every CPU cycle
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+            if self.length_counter.is_active() && self.linear_counter > 0 {
+                self.sequence_step = (self.sequence_step + 1) & 0x1F;
+            }
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    /// Clocked by the frame counter on every quarter frame
+    pub fn clock_linear_counter(&mut self) {
+        if self.linear_counter_reload {
+            self.linear_counter = self.linear_counter_period;
+        } else if self.linear_counter > 0 {
+            self.linear_counter -= 1;
+        }
+
+        if !self.control {
+            self.linear_counter_reload = false;
+        }
+    }
+
+    pub const fn output(&self) -> u8 {
+        // Ultrasonic periods are inaudible on hardware but pop when emulated, so the output is held mid-way
+        if self.timer_period < 2 {
+            return 7;
+        }
+
+        SEQUENCE_TABLE[self.sequence_step as usize]
+    }
+}
+
+impl SaveState for Triangle {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.sequence_step);
+        state.write(&self.timer_period);
+        state.write(&self.timer);
+        state.write(&self.control);
+        state.write(&self.linear_counter_period);
+        state.write(&self.linear_counter);
+        state.write(&self.linear_counter_reload);
+
+        self.length_counter.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.sequence_step = state.read()?;
+        self.timer_period = state.read()?;
+        self.timer = state.read()?;
+        self.control = state.read()?;
+        self.linear_counter_period = state.read()?;
+        self.linear_counter = state.read()?;
+        self.linear_counter_reload = state.read()?;
+
+        self.length_counter.load_state(state)?;
+
+        Ok(())
+    }
+}
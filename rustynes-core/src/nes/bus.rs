@@ -0,0 +1,173 @@
+use crate::nes::{ComponentCartridge, Component2C02, Component2A03};
+use crate::nes::state::{SaveState, StateError, StateReader, StateWriter};
+
+use super::InputPorts;
+
+/// Only the 2K of internal RAM are saved, the rest of `Bus::ram` is the flat memory of the CPU tests
+const SAVED_RAM_SIZE: usize = 0x0800;
+
+/// Devices able to pull the shared IRQ line, which stays asserted as long as one of them holds it
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IrqSource {
+    ApuFrameCounter = (1 << 0),
+    ApuDmc = (1 << 1),
+    Mapper = (1 << 2),
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Bus {
+    pub ram: [u8; 64 * 1024],
+
+    pub dma_page: u8,
+    pub dma_addr: u8,
+    pub dma_data: u8,
+    pub is_dma_active: bool,
+    pub dma_wait_for_sync: bool,
+    /// CPU cycles left to stall while the DMC fetches a sample byte
+    pub dmc_stall_cycles: u8,
+    /// One bit per `IrqSource` currently asserting the IRQ line
+    pub irq_line: u8,
+    /// Last value seen on the CPU data bus, returned by reads nothing answers
+    pub open_bus: u8,
+}
+
+impl Bus {
+    pub const fn new() -> Self {
+        Self {
+            ram: [0; 64 * 1024],
+            dma_page: 0,
+            dma_addr: 0,
+            dma_data: 0,
+            is_dma_active: false,
+            dma_wait_for_sync: true,
+            dmc_stall_cycles: 0,
+            irq_line: 0,
+            open_bus: 0,
+        }
+    }
+
+    pub fn set_irq(&mut self, source: IrqSource, active: bool) {
+        if active {
+            self.irq_line |= source as u8;
+        } else {
+            self.irq_line &= !(source as u8);
+        }
+    }
+
+    pub const fn is_irq_asserted(&self) -> bool {
+        self.irq_line != 0
+    }
+
+    pub fn cpu_read(&mut self, addr: u16, read_only: bool, input_ports: &mut InputPorts, cartridge: &ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03) -> u8 {
+        let mut data = self.open_bus;
+
+        // Cartridge has priority over everything else (mappers)
+        if !cartridge.cpu_read(addr, read_only, &mut data) {
+            match addr {
+                // RAM range
+                0x0000..=0x1FFF => data = self.ram[(addr & 0x07FF) as usize],
+                // PPU write-only registers
+                0x2000..=0x3FFF if matches!(addr & 0x0007, 0x0000 | 0x0001 | 0x0003 | 0x0005 | 0x0006) => {}
+                // PPU range
+                0x2000..=0x3FFF => data = ppu.cpu_read(addr & 0x0007, read_only, cartridge),
+                // APU status, bit 5 isn't driven. The register is inside the CPU, so reading it leaves the
+                // external data bus untouched
+                0x4015 => return apu.cpu_read(addr, read_only) | (self.open_bus & 0x20),
+                // Controller range, only the low 5 bits are driven by the port. For a plain `LDA $4016` the others
+                // are the high byte of the address
+                0x4016..=0x4017 => {
+                    let device = &mut input_ports[(addr & 0x0001) as usize];
+                    let bits = if read_only { device.peek() } else { device.read() };
+                    data = (self.open_bus & 0xE0) | (bits & 0x1F);
+                }
+                _ => {}
+            };
+        }
+
+        if !read_only {
+            self.open_bus = data;
+        }
+
+        data
+    }
+
+    pub fn cpu_write(&mut self, addr: u16, data: u8, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03) {
+        self.open_bus = data;
+
+        // Cartridge has priority over everything else (mappers)
+        if cartridge.cpu_write(addr, data) {
+            return;
+        }
+        match addr {
+            // RAM range
+            0x0000..=0x1FFF => self.ram[(addr & 0x07FF) as usize] = data,
+            // PPU range
+            0x2000..=0x3FFF => ppu.cpu_write(addr & 0x0007, data, cartridge),
+            // APU range
+            0x4000..=0x4013 | 0x4015 => apu.cpu_write(addr, data),
+            // DMA range
+            0x4014 => {
+                self.dma_page = data;
+                self.dma_addr = 0x00;
+                self.is_dma_active = true;
+            }
+            // Controller strobe, seen by both ports
+            0x4016 => {
+                for device in input_ports.iter_mut() {
+                    device.write(data);
+                }
+            }
+            // APU frame counter, the second port is only read at this address
+            0x4017 => apu.cpu_write(addr, data),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+impl Bus {
+    pub fn test_read(&self, addr: u16) -> u8 {
+        let result = self.ram[addr as usize];
+        
+        println!("dbg [{}, {}, \"read\"]", addr, result);
+        crate::tests::cycles_trace::CYCLES.lock().unwrap().push((addr, result, "read".to_string()));
+        
+        result
+    }
+
+    pub fn test_write(&mut self, addr: u16, data: u8) {
+        println!("dbg [{}, {}, \"write\"]", addr, data);
+        crate::tests::cycles_trace::CYCLES.lock().unwrap().push((addr, data, "write".to_string()));
+        self.ram[addr as usize] = data;
+    }
+}
+
+impl SaveState for Bus {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.ram[..SAVED_RAM_SIZE]);
+
+        state.write(&self.dma_page);
+        state.write(&self.dma_addr);
+        state.write(&self.dma_data);
+        state.write(&self.is_dma_active);
+        state.write(&self.dma_wait_for_sync);
+        state.write(&self.dmc_stall_cycles);
+        state.write(&self.irq_line);
+        state.write(&self.open_bus);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        state.read_bytes_into(&mut self.ram[..SAVED_RAM_SIZE])?;
+
+        self.dma_page = state.read()?;
+        self.dma_addr = state.read()?;
+        self.dma_data = state.read()?;
+        self.is_dma_active = state.read()?;
+        self.dma_wait_for_sync = state.read()?;
+        self.dmc_stall_cycles = state.read()?;
+        self.irq_line = state.read()?;
+        self.open_bus = state.read()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,495 @@
+mod info;
+mod error;
+mod unif;
+mod fds;
+mod database;
+
+use std::io::{self, Read, Seek};
+use std::path::{Path, PathBuf};
+
+pub use info::{CartridgeInfo, ConsoleType, HeaderFormat, Timing};
+pub use error::CartridgeError;
+pub use database::GameDatabase;
+use unif::Unif;
+use crate::nes::mappers::{Mapper, mapper_000, mapper_001, mapper_002, mapper_003, mapper_004, mapper_007, mapper_066};
+use crate::nes::mappers::fds::Fds;
+use crate::nes::state::{SaveState, StateError, StateReader, StateWriter};
+
+const ZIP_MAGIC: [u8; 4] = *b"PK\x03\x04";
+/// Extensions looked for in zip archives
+const ROM_EXTENSIONS: [&str; 4] = [".nes", ".unf", ".unif", ".fds"];
+/// Looked for next to FDS images when no BIOS path is given
+const FDS_BIOS_NAME: &str = "disksys.rom";
+const FDS_BIOS_SIZE: usize = 8 * 1024;
+const TRAINER_SIZE: usize = 512;
+/// Where $7000 lands in the PRG-RAM
+const TRAINER_OFFSET: usize = 0x1000;
+
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct HeaderCartridge {
+    name: [u8; 4],
+    prg_rom_chunks: u8,
+    chr_rom_chunks: u8,
+    mapper1: u8,
+    mapper2: u8,
+    prg_ram_size: u8,
+    tv_system1: u8,
+    tv_system2: u8,
+    unused: [u8; 5],
+}
+
+impl HeaderCartridge {
+    pub const fn from_bytes(buffer: &[u8]) -> Self {
+        unsafe { buffer.as_ptr().cast::<Self>().read_unaligned() }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Mirror {
+    Hardware,
+    Horizontal,
+    Vertical,
+    OneScreenLo,
+    OneScreenHi,
+    /// Each nametable has its own memory, provided by the cartridge
+    FourScreen,
+}
+
+/// Options for images that need more than the file itself
+#[derive(Debug, Clone, Default)]
+pub struct LoadOptions {
+    /// Famicom Disk System BIOS, `disksys.rom` next to the image by default
+    pub fds_bios_path: Option<PathBuf>,
+    /// Game database checked before the built-in one, in the format of `cartridge/database.txt`
+    pub database_path: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub struct ComponentCartridge {
+    /// Program ROM
+    prg_rom: Vec<u8>,
+    /// Character ROM
+    chr_rom: Vec<u8>,
+    /// Work RAM at $6000-$7FFF, mapped by the mapper
+    prg_ram: Vec<u8>,
+    /// Extra nametable RAM on four-screen boards
+    vram: Vec<u8>,
+    /// Where the PRG-RAM is persisted, for boards with a battery
+    save_path: Option<PathBuf>,
+    
+    mapper: Box<dyn Mapper>,
+    pub hardware_mirror: Mirror,
+    info: CartridgeInfo,
+}
+
+impl ComponentCartridge {
+    pub fn new() -> Self {
+        Self {
+            prg_rom: Vec::new(),
+            chr_rom: Vec::new(),
+            prg_ram: Vec::new(),
+            vram: Vec::new(),
+            save_path: None,
+            mapper: Box::new(mapper_000::Mapper000::new(0, 0)),
+            hardware_mirror: Mirror::Horizontal,
+            info: CartridgeInfo::default(),
+        }
+    }
+
+    /// Loads an iNES, UNIF or FDS image, or the first one inside a zip archive. Battery saves are kept next to it
+    #[allow(dead_code)]
+    pub fn from_path(path: &str) -> Result<Self, CartridgeError> {
+        Self::from_path_with_options(path, &LoadOptions::default())
+    }
+
+    pub fn from_path_with_options(path: &str, options: &LoadOptions) -> Result<Self, CartridgeError> {
+        let file = std::fs::File::open(path)?;
+
+        Self::load(file, Some(Path::new(path)), options)
+    }
+
+    #[allow(dead_code)]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CartridgeError> {
+        Self::from_reader(io::Cursor::new(bytes))
+    }
+
+    /// Like `from_path`, but battery-backed RAM isn't persisted since there is no file to save next to
+    #[allow(dead_code)]
+    pub fn from_reader(reader: impl Read + Seek) -> Result<Self, CartridgeError> {
+        Self::from_reader_with_options(reader, &LoadOptions::default())
+    }
+
+    /// FDS images need `options.fds_bios_path` here, there is no directory to look for the BIOS in
+    #[allow(dead_code)]
+    pub fn from_reader_with_options(reader: impl Read + Seek, options: &LoadOptions) -> Result<Self, CartridgeError> {
+        Self::load(reader, None, options)
+    }
+
+    fn load(mut reader: impl Read + Seek, path: Option<&Path>, options: &LoadOptions) -> Result<Self, CartridgeError> {
+        let start = reader.stream_position()?;
+        let mut magic = [0; 4];
+        let found = Self::read_up_to(&mut reader, &mut magic)?;
+        reader.seek(io::SeekFrom::Start(start))?;
+
+        if found == magic.len() && magic == ZIP_MAGIC {
+            let rom = Self::extract_rom(reader)?;
+            return Self::parse(&mut rom.as_slice(), path, options);
+        }
+
+        Self::parse(&mut reader, path, options)
+    }
+
+    /// First `.nes`, `.unf` or `.fds` file in the archive
+    fn extract_rom(reader: impl Read + Seek) -> Result<Vec<u8>, CartridgeError> {
+        let mut archive = zip::ZipArchive::new(reader)?;
+
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i)?;
+            let name = file.name().to_ascii_lowercase();
+            if file.is_file() && ROM_EXTENSIONS.iter().any(|extension| name.ends_with(extension)) {
+                let mut rom = Vec::new();
+                file.read_to_end(&mut rom)?;
+                return Ok(rom);
+            }
+        }
+
+        Err(CartridgeError::NoRomInArchive)
+    }
+
+    /// Picks the parser from the magic, iNES being the default
+    fn parse(reader: &mut impl Read, path: Option<&Path>, options: &LoadOptions) -> Result<Self, CartridgeError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+
+        if data.starts_with(&unif::MAGIC) {
+            Self::parse_unif(&data, path)
+        } else if data.starts_with(&fds::MAGIC) || data.starts_with(&fds::DISK_MAGIC) {
+            Self::parse_fds(&data, path, options)
+        } else {
+            Self::parse_ines(&mut data.as_slice(), path, options)
+        }
+    }
+
+    fn parse_ines(reader: &mut &[u8], path: Option<&Path>, options: &LoadOptions) -> Result<Self, CartridgeError> {
+        let mut buffer: [u8; 16] = [0; 16];
+        
+        if Self::read_up_to(reader, &mut buffer)? < buffer.len() || buffer[0..4] != *b"NES\x1A" {
+            return Err(CartridgeError::BadMagic);
+        }
+        let header = HeaderCartridge::from_bytes(&buffer);
+
+        let mut info = CartridgeInfo::from_header(&header);
+        if info.prg_rom_size == 0 {
+            return Err(CartridgeError::Malformed("the header has no PRG ROM".to_string()));
+        }
+
+        let mut trainer = Vec::new();
+        if info.has_trainer {
+            trainer.resize(TRAINER_SIZE, 0);
+            let found = Self::read_up_to(reader, &mut trainer)?;
+            if found < trainer.len() {
+                return Err(CartridgeError::TruncatedTrainer { expected: trainer.len(), found });
+            }
+        }
+
+        // Sizes are checked against what is left before allocating, a broken NES 2.0 exponent can ask for
+        // more memory than there is
+        let found = reader.len();
+        if info.prg_rom_size > found {
+            return Err(CartridgeError::TruncatedPrg { expected: info.prg_rom_size, found });
+        }
+        let found = found - info.prg_rom_size;
+        if info.chr_rom_size > found {
+            return Err(CartridgeError::TruncatedChr { expected: info.chr_rom_size, found });
+        }
+
+        let mut prg_rom = vec![0; info.prg_rom_size];
+        Self::read_up_to(reader, &mut prg_rom)?;
+
+        let mut chr_rom = vec![0; info.chr_rom_size];
+        Self::read_up_to(reader, &mut chr_rom)?;
+
+        // NES 2.0 headers are trusted, only iNES ones are fixed
+        info.crc32 = GameDatabase::crc32(&prg_rom, &chr_rom);
+        if info.format == HeaderFormat::INes {
+            let database = options.database_path.as_ref()
+                .map(|path| GameDatabase::from_path(path).map_err(|error| CartridgeError::Database(path.clone(), error)))
+                .transpose()?;
+            let entry = database.as_ref()
+                .and_then(|database| database.find(info.crc32, &prg_rom, &chr_rom))
+                .or_else(|| GameDatabase::built_in().find(info.crc32, &prg_rom, &chr_rom));
+            if let Some(entry) = entry {
+                entry.apply(&mut info);
+            }
+        }
+
+        let mapper = Self::create_mapper(&info)?;
+        Self::build(info, prg_rom, chr_rom, &trainer, path, mapper)
+    }
+
+    fn parse_unif(data: &[u8], path: Option<&Path>) -> Result<Self, CartridgeError> {
+        let mut unif = Unif::parse(data)?;
+        unif.info.crc32 = GameDatabase::crc32(&unif.prg_rom, &unif.chr_rom);
+
+        let mapper = Self::create_mapper(&unif.info)?;
+        Self::build(unif.info, unif.prg_rom, unif.chr_rom, &[], path, mapper)
+    }
+
+    /// The BIOS is looked for next to the image, unless the options say where it is
+    fn parse_fds(data: &[u8], path: Option<&Path>, options: &LoadOptions) -> Result<Self, CartridgeError> {
+        let disk_sides = fds::disk_sides(data)?;
+
+        let bios_path = options.fds_bios_path.clone().or_else(|| path.map(|path| path.with_file_name(FDS_BIOS_NAME)));
+        let Some(bios_path) = bios_path else {
+            return Err(CartridgeError::MissingFdsBios(None));
+        };
+        let bios = match std::fs::read(&bios_path) {
+            Ok(bios) => bios,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Err(CartridgeError::MissingFdsBios(Some(bios_path))),
+            Err(error) => return Err(error.into()),
+        };
+        if bios.len() != FDS_BIOS_SIZE {
+            return Err(CartridgeError::Malformed(format!("the FDS BIOS should be {FDS_BIOS_SIZE} bytes, found {}", bios.len())));
+        }
+
+        let mut info = fds::info(bios.len());
+        // Tells disks apart, for save states
+        info.crc32 = GameDatabase::crc32(data, &[]);
+        Self::build(info, bios, Vec::new(), &[], path, Box::new(Fds::with_disk_sides(disk_sides)))
+    }
+
+    fn create_mapper(info: &CartridgeInfo) -> Result<Box<dyn Mapper>, CartridgeError> {
+        let mapper_id = info.mapper_id;
+        let prg_banks_count = info.prg_banks_count();
+        let chr_banks_count = info.chr_banks_count();
+
+        let mapper: Box<dyn Mapper> = match mapper_id {
+            0 => Box::new(mapper_000::Mapper000::new(prg_banks_count, chr_banks_count)),
+            1 => Box::new(mapper_001::Mapper001::new(prg_banks_count, chr_banks_count)),
+            2 => Box::new(mapper_002::Mapper002::new(prg_banks_count, chr_banks_count)),
+            3 => Box::new(mapper_003::Mapper003::new(prg_banks_count, chr_banks_count)),
+            4 => Box::new(mapper_004::Mapper004::new(prg_banks_count, chr_banks_count)),
+            7 => Box::new(mapper_007::Mapper007::new(prg_banks_count, chr_banks_count)),
+            66 => Box::new(mapper_066::Mapper066::new(prg_banks_count, chr_banks_count)),
+            _ => return Err(CartridgeError::UnsupportedMapper(mapper_id)),
+        };
+
+        Ok(mapper)
+    }
+
+    /// Allocates the RAM the info asks for, an empty `chr_rom` meaning the board has CHR RAM
+    fn build(info: CartridgeInfo, prg_rom: Vec<u8>, chr_rom: Vec<u8>, trainer: &[u8], path: Option<&Path>, mapper: Box<dyn Mapper>) -> Result<Self, CartridgeError> {
+        // CHR RAM uses the same storage, mappers expect at least 8K of it
+        let chr_rom = if chr_rom.is_empty() { vec![0; (info.chr_ram_size + info.chr_nvram_size).max(8 * 1024)] } else { chr_rom };
+
+        // The trainer needs RAM at $7000 even if the header doesn't ask for any
+        let prg_ram_size = info.prg_ram_size + info.prg_nvram_size;
+        let mut prg_ram = vec![0; if info.has_trainer { prg_ram_size.max(8 * 1024) } else { prg_ram_size }];
+        let save_path = path.filter(|_| info.has_battery).map(|path| path.with_extension("sav"));
+        if let Some(save_path) = &save_path {
+            match std::fs::read(save_path) {
+                Ok(save) => {
+                    let len = save.len().min(prg_ram.len());
+                    prg_ram[..len].copy_from_slice(&save[..len]);
+                }
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        // Copied last so it's always there when the game boots
+        if info.has_trainer {
+            prg_ram[TRAINER_OFFSET..TRAINER_OFFSET + TRAINER_SIZE].copy_from_slice(trainer);
+        }
+
+        let hardware_mirror = info.mirror;
+        let vram = if hardware_mirror == Mirror::FourScreen { vec![0; 4 * 1024] } else { Vec::new() };
+
+        Ok(Self {
+            prg_rom,
+            chr_rom,
+            prg_ram,
+            vram,
+            save_path,
+            mapper,
+            hardware_mirror,
+            info,
+        })
+    }
+
+    /// Writes the PRG-RAM to the `.sav` file next to the ROM, if the cartridge has a battery
+    pub fn save_battery(&self) -> io::Result<()> {
+        match &self.save_path {
+            Some(save_path) => std::fs::write(save_path, &self.prg_ram),
+            None => Ok(()),
+        }
+    }
+
+    /// Like `read_exact`, but reports how much was read when the file ends early
+    fn read_up_to(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        while read < buffer.len() {
+            match reader.read(&mut buffer[read..]) {
+                Ok(0) => break,
+                Ok(n) => read += n,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(read)
+    }
+
+    pub const fn info(&self) -> &CartridgeInfo {
+        &self.info
+    }
+
+    /// Queried on every nametable access, so mappers can switch it mid-frame
+    pub(crate) fn mirror(&self) -> Mirror {
+        // Four-screen boards ignore the mapper's mirroring
+        if self.hardware_mirror == Mirror::FourScreen {
+            return Mirror::FourScreen;
+        }
+
+        let mirror = self.mapper.mirror();
+
+        if mirror == Mirror::Hardware {
+            return self.hardware_mirror;
+        }
+
+        mirror
+    }
+
+    fn is_four_screen_addr(&self, addr: u16) -> bool {
+        !self.vram.is_empty() && (0x2000..=0x3EFF).contains(&addr)
+    }
+
+    pub(crate) fn ppu_tick(&mut self, addr: u16) {
+        self.mapper.ppu_tick(addr);
+    }
+
+    pub(crate) fn cpu_tick(&mut self) {
+        self.mapper.cpu_tick();
+    }
+
+    pub(crate) fn is_irq_asserted(&self) -> bool {
+        self.mapper.is_irq_asserted()
+    }
+
+    pub(crate) fn audio_output(&self) -> f32 {
+        self.mapper.audio_output()
+    }
+
+    pub fn disk_sides_count(&self) -> usize {
+        self.mapper.disk_sides_count()
+    }
+
+    pub fn disk_side(&self) -> Option<usize> {
+        self.mapper.disk_side()
+    }
+
+    pub fn insert_disk_side(&mut self, side: Option<usize>) {
+        self.mapper.insert_disk_side(side);
+    }
+
+    pub(crate) fn cpu_read(&self, addr: u16, read_only: bool, data: &mut u8) -> bool {
+        let mut mapped_addr = 0x0000;
+
+        if !self.prg_ram.is_empty() && self.mapper.prg_ram_map_read(addr, &mut mapped_addr) {
+            *data = self.prg_ram[mapped_addr as usize % self.prg_ram.len()];
+            return true;
+        }
+
+        if self.mapper.cpu_map_read(addr, read_only, &mut mapped_addr, data) {
+            if mapped_addr == 0xFFFFFFFF {
+                return true;
+            } else {
+                *data = self.prg_rom[mapped_addr as usize];
+            }
+            return true;
+        }
+
+        false
+    }
+
+    pub(crate) fn cpu_write(&mut self, addr: u16, data: u8) -> bool {
+        let mut mapped_addr = 0x0000;
+
+        if !self.prg_ram.is_empty() && self.mapper.prg_ram_map_write(addr, &mut mapped_addr) {
+            let len = self.prg_ram.len();
+            self.prg_ram[mapped_addr as usize % len] = data;
+            return true;
+        }
+
+        if self.mapper.cpu_map_write(addr, &mut mapped_addr, data) {
+            if mapped_addr == 0xFFFFFFFF {
+                return true;
+            } else {
+                self.prg_rom[mapped_addr as usize] = data;
+            }
+            return true;
+        }
+
+        false
+    }
+
+    pub(crate) fn ppu_read(&self, addr: u16, data: &mut u8) -> bool {
+        let mut mapped_addr = 0x0000;
+
+        if self.is_four_screen_addr(addr) {
+            *data = self.vram[(addr & 0x0FFF) as usize];
+            return true;
+        }
+
+        if self.mapper.ppu_map_read(addr, &mut mapped_addr) {
+            *data = self.chr_rom[mapped_addr as usize];
+            return true;
+        }
+
+        false
+    }
+
+    pub(crate) fn ppu_write(&mut self, addr: u16, data: u8) -> bool {
+        let mut mapped_addr = 0x000;
+
+        if self.is_four_screen_addr(addr) {
+            self.vram[(addr & 0x0FFF) as usize] = data;
+            return true;
+        }
+
+        if self.mapper.ppu_map_write(addr, &mut mapped_addr) {
+            self.chr_rom[mapped_addr as usize] = data;
+            return true;
+        }
+
+        false
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.mapper.reset();
+    }
+}
+
+impl SaveState for ComponentCartridge {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.prg_ram);
+        // Only CHR RAM can change
+        if self.info.chr_rom_size == 0 {
+            state.write_bytes(&self.chr_rom);
+        }
+        state.write_bytes(&self.vram);
+
+        self.mapper.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        state.read_bytes_into(&mut self.prg_ram)?;
+        if self.info.chr_rom_size == 0 {
+            state.read_bytes_into(&mut self.chr_rom)?;
+        }
+        state.read_bytes_into(&mut self.vram)?;
+
+        self.mapper.load_state(state)
+    }
+}
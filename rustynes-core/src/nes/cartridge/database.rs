@@ -0,0 +1,163 @@
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use super::{CartridgeInfo, Mirror, Timing};
+
+/// See the header of the file for its format
+const BUILT_IN: &str = include_str!("database.txt");
+
+/// What a game's header should have said
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameEntry {
+    pub crc32: u32,
+    /// Checked too when present, for games whose CRC32 collides
+    pub sha1: Option<[u8; 20]>,
+    pub mapper_id: u16,
+    pub submapper_id: u8,
+    /// `None` when the mapper controls it, the header is kept then
+    pub mirror: Option<Mirror>,
+    pub prg_ram_size: usize,
+    pub prg_nvram_size: usize,
+    pub chr_ram_size: usize,
+    pub chr_nvram_size: usize,
+    pub timing: Timing,
+    pub name: String,
+}
+
+impl GameEntry {
+    fn parse(line: &str) -> Option<Self> {
+        let mut columns = line.split_whitespace();
+
+        let crc32 = u32::from_str_radix(columns.next()?, 16).ok()?;
+        let sha1 = match columns.next()? {
+            "-" => None,
+            sha1 => Some(Self::parse_sha1(sha1)?),
+        };
+        let (mapper_id, submapper_id) = columns.next()?.split_once('.')?;
+        let mirror = match columns.next()? {
+            "H" => Some(Mirror::Horizontal),
+            "V" => Some(Mirror::Vertical),
+            "4" => Some(Mirror::FourScreen),
+            "1" => Some(Mirror::OneScreenLo),
+            "-" => None,
+            _ => return None,
+        };
+        let prg_ram_size = columns.next()?.parse().ok()?;
+        let prg_nvram_size = columns.next()?.parse().ok()?;
+        let chr_ram_size = columns.next()?.parse().ok()?;
+        let chr_nvram_size = columns.next()?.parse().ok()?;
+        let timing = match columns.next()? {
+            "NTSC" => Timing::Ntsc,
+            "PAL" => Timing::Pal,
+            "MULTI" => Timing::MultiRegion,
+            "DENDY" => Timing::Dendy,
+            _ => return None,
+        };
+        let name = columns.collect::<Vec<_>>().join(" ");
+
+        Some(Self {
+            crc32,
+            sha1,
+            mapper_id: mapper_id.parse().ok()?,
+            submapper_id: submapper_id.parse().ok()?,
+            mirror,
+            prg_ram_size,
+            prg_nvram_size,
+            chr_ram_size,
+            chr_nvram_size,
+            timing,
+            name,
+        })
+    }
+
+    fn parse_sha1(hex: &str) -> Option<[u8; 20]> {
+        if hex.len() != 40 {
+            return None;
+        }
+
+        let mut sha1 = [0; 20];
+        for (i, byte) in sha1.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+        }
+
+        Some(sha1)
+    }
+
+    /// Overrides what the header got wrong, the ROM sizes come from the file and are kept
+    pub fn apply(&self, info: &mut CartridgeInfo) {
+        info.mapper_id = self.mapper_id;
+        info.submapper_id = self.submapper_id;
+        if let Some(mirror) = self.mirror {
+            info.mirror = mirror;
+        }
+        info.prg_ram_size = self.prg_ram_size;
+        info.prg_nvram_size = self.prg_nvram_size;
+        info.chr_ram_size = self.chr_ram_size;
+        info.chr_nvram_size = self.chr_nvram_size;
+        info.has_battery = self.prg_nvram_size > 0 || self.chr_nvram_size > 0;
+        info.timing = self.timing;
+        info.is_database_match = true;
+    }
+}
+
+#[derive(Debug)]
+pub struct GameDatabase {
+    entries: Vec<GameEntry>,
+}
+
+impl GameDatabase {
+    /// Comments and malformed lines are skipped
+    pub fn parse(text: &str) -> Self {
+        let entries = text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(GameEntry::parse)
+            .collect();
+
+        Self { entries }
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    /// Parsed the first time it's needed
+    pub fn built_in() -> &'static Self {
+        static DATABASE: OnceLock<GameDatabase> = OnceLock::new();
+
+        DATABASE.get_or_init(|| Self::parse(BUILT_IN))
+    }
+
+    #[cfg(test)]
+    pub fn entries(&self) -> &[GameEntry] {
+        &self.entries
+    }
+
+    /// The SHA-1 is only computed if an entry with that CRC32 has one
+    pub fn find(&self, crc32: u32, prg_rom: &[u8], chr_rom: &[u8]) -> Option<&GameEntry> {
+        let mut sha1 = None;
+
+        self.entries.iter()
+            .filter(|entry| entry.crc32 == crc32)
+            .find(|entry| match entry.sha1 {
+                Some(expected) => *sha1.get_or_insert_with(|| Self::sha1(prg_rom, chr_rom)) == expected,
+                None => true,
+            })
+    }
+
+    /// The database key
+    pub fn crc32(prg_rom: &[u8], chr_rom: &[u8]) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(prg_rom);
+        hasher.update(chr_rom);
+        hasher.finalize()
+    }
+
+    fn sha1(prg_rom: &[u8], chr_rom: &[u8]) -> [u8; 20] {
+        let mut hasher = sha1_smol::Sha1::new();
+        hasher.update(prg_rom);
+        hasher.update(chr_rom);
+        hasher.digest().bytes()
+    }
+}
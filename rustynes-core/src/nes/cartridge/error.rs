@@ -0,0 +1,67 @@
+use std::{fmt, io};
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum CartridgeError {
+    Io(io::Error),
+    /// The file isn't an iNES, UNIF or FDS image
+    BadMagic,
+    /// Sizes are in bytes
+    TruncatedPrg { expected: usize, found: usize },
+    TruncatedChr { expected: usize, found: usize },
+    TruncatedTrainer { expected: usize, found: usize },
+    UnsupportedMapper(u16),
+    /// UNIF board name with no known mapper
+    UnsupportedBoard(String),
+    /// The image structure is broken
+    Malformed(String),
+    /// FDS images need the BIOS, which wasn't found at the path if there is one
+    MissingFdsBios(Option<PathBuf>),
+    /// The game database given in the load options couldn't be read
+    Database(PathBuf, io::Error),
+    Zip(zip::result::ZipError),
+    /// The zip archive has no `.nes`, `.unf` or `.fds` file
+    NoRomInArchive,
+}
+
+impl fmt::Display for CartridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::BadMagic => write!(f, "not an iNES, UNIF or FDS image"),
+            Self::TruncatedPrg { expected, found } => write!(f, "PRG ROM is truncated, expected {expected} bytes but found {found}"),
+            Self::TruncatedChr { expected, found } => write!(f, "CHR ROM is truncated, expected {expected} bytes but found {found}"),
+            Self::TruncatedTrainer { expected, found } => write!(f, "trainer is truncated, expected {expected} bytes but found {found}"),
+            Self::UnsupportedMapper(mapper_id) => write!(f, "mapper {mapper_id} is not supported"),
+            Self::UnsupportedBoard(board) => write!(f, "board {board} is not supported"),
+            Self::Malformed(reason) => write!(f, "malformed image, {reason}"),
+            Self::MissingFdsBios(Some(path)) => write!(f, "FDS BIOS not found at {}", path.display()),
+            Self::MissingFdsBios(None) => write!(f, "FDS images need the BIOS, none was given"),
+            Self::Database(path, error) => write!(f, "could not read the game database {}, {error}", path.display()),
+            Self::Zip(error) => write!(f, "{error}"),
+            Self::NoRomInArchive => write!(f, "no .nes, .unf or .fds file found in the archive"),
+        }
+    }
+}
+
+impl std::error::Error for CartridgeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) | Self::Database(_, error) => Some(error),
+            Self::Zip(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CartridgeError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<zip::result::ZipError> for CartridgeError {
+    fn from(error: zip::result::ZipError) -> Self {
+        Self::Zip(error)
+    }
+}
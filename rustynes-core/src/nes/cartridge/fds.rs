@@ -0,0 +1,80 @@
+use super::{CartridgeError, CartridgeInfo, HeaderFormat, Mirror};
+
+/// fwNES header
+pub const MAGIC: [u8; 4] = *b"FDS\x1A";
+/// Headerless images start with the disk info block
+pub const DISK_MAGIC: [u8; 15] = *b"\x01*NINTENDO-HVC*";
+const HEADER_SIZE: usize = 16;
+/// Bytes per side in a `.fds` image, the blocks without their gaps and CRCs
+pub const SIDE_SIZE: usize = 65500;
+
+/// The drive sees 28300 bits of gap before the first block
+const LEADING_GAP: usize = 28300 / 8;
+/// and 976 bits after each block
+const BLOCK_GAP: usize = 976 / 8;
+/// Gap end mark, it's the first bit set after a gap
+const START_MARK: u8 = 0x80;
+/// The BIOS doesn't check the CRCs, the RAM adapter only reports mismatches when writing
+const FAKE_CRC: [u8; 2] = [0x4D, 0x62];
+
+/// Splits a `.fds` image into sides as the drive reads them, with gaps, start marks and CRCs
+pub fn disk_sides(data: &[u8]) -> Result<Vec<Vec<u8>>, CartridgeError> {
+    let data = if data.starts_with(&MAGIC) { &data[HEADER_SIZE.min(data.len())..] } else { data };
+
+    let sides: Vec<Vec<u8>> = data.chunks_exact(SIDE_SIZE).map(raw_side).collect();
+    if sides.is_empty() {
+        return Err(CartridgeError::Malformed(format!("FDS image holds no disk side, sides are {SIDE_SIZE} bytes")));
+    }
+
+    Ok(sides)
+}
+
+fn raw_side(side: &[u8]) -> Vec<u8> {
+    let mut raw = vec![0; LEADING_GAP];
+
+    let mut offset = 0;
+    while offset < side.len() {
+        let block_size = match side[offset] {
+            // Disk info
+            1 => 56,
+            // File amount
+            2 => 2,
+            // File header
+            3 => 16,
+            // File data, its size is in the header right before it
+            4 if offset >= 3 => 1 + u16::from_le_bytes([side[offset - 3], side[offset - 2]]) as usize,
+            // Unused space
+            _ => break,
+        };
+        let Some(block) = side.get(offset..offset + block_size) else {
+            break;
+        };
+
+        raw.push(START_MARK);
+        raw.extend_from_slice(block);
+        raw.extend_from_slice(&FAKE_CRC);
+        raw.resize(raw.len() + BLOCK_GAP, 0);
+
+        offset += block_size;
+    }
+
+    // Room for the BIOS to write new files
+    raw.resize(raw.len().max(LEADING_GAP + SIDE_SIZE), 0);
+    raw
+}
+
+/// The BIOS is the 8K PRG ROM, the 32K of RAM and 8K of CHR RAM are in the RAM adapter
+pub fn info(bios_size: usize) -> CartridgeInfo {
+    CartridgeInfo {
+        format: HeaderFormat::Fds,
+        mapper_id: 20,
+
+        prg_rom_size: bios_size,
+        prg_ram_size: 32 * 1024,
+        chr_ram_size: 8 * 1024,
+
+        mirror: Mirror::Vertical,
+
+        ..CartridgeInfo::default()
+    }
+}
@@ -0,0 +1,247 @@
+use std::fmt;
+
+use super::{HeaderCartridge, Mirror};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HeaderFormat {
+    INes,
+    Nes2,
+    /// Chunked format identifying the board by name instead of a mapper number
+    Unif,
+    /// Famicom Disk System image
+    Fds,
+}
+
+/// CPU/PPU timing the game was made for
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Timing {
+    Ntsc,
+    Pal,
+    /// Runs on both NTSC and PAL consoles
+    MultiRegion,
+    Dendy,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConsoleType {
+    Nes,
+    VsSystem,
+    Playchoice10,
+    /// NES 2.0 extended console type, from byte 13
+    Extended(u8),
+}
+
+/// Everything the header tells about the cartridge, sizes are in bytes
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CartridgeInfo {
+    pub format: HeaderFormat,
+    pub mapper_id: u16,
+    pub submapper_id: u8,
+
+    pub prg_rom_size: usize,
+    pub chr_rom_size: usize,
+    /// Volatile PRG-RAM
+    pub prg_ram_size: usize,
+    /// Battery-backed PRG-RAM
+    pub prg_nvram_size: usize,
+    pub chr_ram_size: usize,
+    pub chr_nvram_size: usize,
+
+    pub mirror: Mirror,
+    pub has_battery: bool,
+    pub has_trainer: bool,
+
+    pub timing: Timing,
+    pub console_type: ConsoleType,
+    /// NES 2.0 default expansion device, 1 being the standard controllers
+    pub expansion_device: u8,
+    pub misc_roms_count: u8,
+
+    /// CRC32 of PRG+CHR, the game database key
+    pub crc32: u32,
+    /// The game database overrode what the header said
+    pub is_database_match: bool,
+}
+
+impl Default for CartridgeInfo {
+    fn default() -> Self {
+        Self {
+            format: HeaderFormat::INes,
+            mapper_id: 0,
+            submapper_id: 0,
+
+            prg_rom_size: 0,
+            chr_rom_size: 0,
+            prg_ram_size: 0,
+            prg_nvram_size: 0,
+            chr_ram_size: 0,
+            chr_nvram_size: 0,
+
+            mirror: Mirror::Horizontal,
+            has_battery: false,
+            has_trainer: false,
+
+            timing: Timing::Ntsc,
+            console_type: ConsoleType::Nes,
+            expansion_device: 0,
+            misc_roms_count: 0,
+
+            crc32: 0,
+            is_database_match: false,
+        }
+    }
+}
+
+impl CartridgeInfo {
+    pub fn from_header(header: &HeaderCartridge) -> Self {
+        let flags6 = header.mapper1;
+        let flags7 = header.mapper2;
+
+        let mirror = if flags6 & 0x08 != 0 {
+            Mirror::FourScreen
+        } else if flags6 & 0x01 != 0 {
+            Mirror::Vertical
+        } else {
+            Mirror::Horizontal
+        };
+        let has_battery = flags6 & 0x02 != 0;
+        let has_trainer = flags6 & 0x04 != 0;
+
+        if flags7 & 0x0C == 0x08 {
+            Self::from_nes2_header(header, mirror, has_battery, has_trainer)
+        } else {
+            Self::from_ines_header(header, mirror, has_battery, has_trainer)
+        }
+    }
+
+    fn from_ines_header(header: &HeaderCartridge, mirror: Mirror, has_battery: bool, has_trainer: bool) -> Self {
+        // Old dumping tools wrote their name in bytes 7-15 ("DiskDude!"), in which case all of them are garbage
+        let is_dirty = header.unused[1..].iter().any(|byte| *byte != 0);
+        let (flags7, byte8, byte9) = if is_dirty { (0, 0, 0) } else { (header.mapper2, header.prg_ram_size, header.tv_system1) };
+
+        // Byte 8 is the PRG-RAM size in 8K units, 0 meaning 8K for compatibility
+        let prg_ram_size = byte8.max(1) as usize * 8 * 1024;
+        let (prg_ram_size, prg_nvram_size) = if has_battery { (0, prg_ram_size) } else { (prg_ram_size, 0) };
+        let chr_ram_size = if header.chr_rom_chunks == 0 { 8 * 1024 } else { 0 };
+
+        let console_type = match flags7 & 0x03 {
+            1 => ConsoleType::VsSystem,
+            2 => ConsoleType::Playchoice10,
+            _ => ConsoleType::Nes,
+        };
+
+        Self {
+            format: HeaderFormat::INes,
+            mapper_id: ((flags7 & 0xF0) | (header.mapper1 >> 4)) as u16,
+            submapper_id: 0,
+
+            prg_rom_size: header.prg_rom_chunks as usize * 16 * 1024,
+            chr_rom_size: header.chr_rom_chunks as usize * 8 * 1024,
+            prg_ram_size,
+            prg_nvram_size,
+            chr_ram_size,
+            chr_nvram_size: 0,
+
+            mirror,
+            has_battery,
+            has_trainer,
+
+            timing: if byte9 & 0x01 != 0 { Timing::Pal } else { Timing::Ntsc },
+            console_type,
+            expansion_device: 0,
+            misc_roms_count: 0,
+
+            crc32: 0,
+            is_database_match: false,
+        }
+    }
+
+    fn from_nes2_header(header: &HeaderCartridge, mirror: Mirror, has_battery: bool, has_trainer: bool) -> Self {
+        let [chr_ram_shifts, timing, console_type, misc_roms, expansion_device] = header.unused;
+
+        let console_type = match header.mapper2 & 0x03 {
+            0 => ConsoleType::Nes,
+            1 => ConsoleType::VsSystem,
+            2 => ConsoleType::Playchoice10,
+            _ => ConsoleType::Extended(console_type & 0x0F),
+        };
+
+        let timing = match timing & 0x03 {
+            0 => Timing::Ntsc,
+            1 => Timing::Pal,
+            2 => Timing::MultiRegion,
+            _ => Timing::Dendy,
+        };
+
+        Self {
+            format: HeaderFormat::Nes2,
+            mapper_id: ((header.prg_ram_size & 0x0F) as u16) << 8 | (header.mapper2 & 0xF0) as u16 | (header.mapper1 >> 4) as u16,
+            submapper_id: header.prg_ram_size >> 4,
+
+            prg_rom_size: Self::rom_size(header.prg_rom_chunks, header.tv_system1 & 0x0F, 16 * 1024),
+            chr_rom_size: Self::rom_size(header.chr_rom_chunks, header.tv_system1 >> 4, 8 * 1024),
+            prg_ram_size: Self::ram_size(header.tv_system2 & 0x0F),
+            prg_nvram_size: Self::ram_size(header.tv_system2 >> 4),
+            chr_ram_size: Self::ram_size(chr_ram_shifts & 0x0F),
+            chr_nvram_size: Self::ram_size(chr_ram_shifts >> 4),
+
+            mirror,
+            has_battery,
+            has_trainer,
+
+            timing,
+            console_type,
+            expansion_device: expansion_device & 0x3F,
+            misc_roms_count: misc_roms & 0x03,
+
+            crc32: 0,
+            is_database_match: false,
+        }
+    }
+
+    /// Either a plain bank count, or an exponent-multiplier size when the MSB nibble is $F
+    fn rom_size(lsb: u8, msb: u8, bank_size: usize) -> usize {
+        if msb == 0x0F {
+            let exponent = (lsb >> 2) as u32;
+            let multiplier = (lsb & 0x03) as usize * 2 + 1;
+
+            2usize.saturating_pow(exponent).saturating_mul(multiplier)
+        } else {
+            ((msb as usize) << 8 | lsb as usize) * bank_size
+        }
+    }
+
+    /// RAM sizes are stored as a shift count, 64 << shift bytes, 0 meaning none
+    const fn ram_size(shift: u8) -> usize {
+        if shift == 0 { 0 } else { 64 << shift }
+    }
+
+    /// Mappers count PRG in 16K banks and CHR in 8K banks
+    pub fn prg_banks_count(&self) -> u8 {
+        u8::try_from(self.prg_rom_size.div_ceil(16 * 1024)).unwrap_or(u8::MAX)
+    }
+
+    pub fn chr_banks_count(&self) -> u8 {
+        u8::try_from(self.chr_rom_size.div_ceil(8 * 1024)).unwrap_or(u8::MAX)
+    }
+}
+
+impl fmt::Display for CartridgeInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let format = match self.format {
+            HeaderFormat::INes => "iNES",
+            HeaderFormat::Nes2 => "NES 2.0",
+            HeaderFormat::Unif => "UNIF",
+            HeaderFormat::Fds => "FDS",
+        };
+        writeln!(f, "{format} | Mapper {}.{} | {:?} mirroring | {:?} | {:?}", self.mapper_id, self.submapper_id, self.mirror, self.timing, self.console_type)?;
+        writeln!(f, "PRG ROM {}K | CHR ROM {}K | PRG RAM {}K | PRG NVRAM {}K | CHR RAM {}K | CHR NVRAM {}K",
+            self.prg_rom_size / 1024, self.chr_rom_size / 1024, self.prg_ram_size / 1024, self.prg_nvram_size / 1024, self.chr_ram_size / 1024, self.chr_nvram_size / 1024)?;
+        write!(f, "Battery {} | Trainer {} | Expansion device {} | Misc ROMs {} | CRC32 {:08X}", self.has_battery, self.has_trainer, self.expansion_device, self.misc_roms_count, self.crc32)?;
+        if self.is_database_match {
+            write!(f, " (header fixed from the game database)")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use super::{CartridgeError, CartridgeInfo, HeaderFormat, Mirror, Timing};
+
+pub const MAGIC: [u8; 4] = *b"UNIF";
+/// Magic, revision, then reserved bytes up to the first chunk
+const HEADER_SIZE: usize = 32;
+/// Chunk ID and little-endian length
+const CHUNK_HEADER_SIZE: usize = 8;
+
+/// Board name prefixes that don't tell anything about the mapper
+const BOARD_PREFIXES: [&str; 5] = ["NES-", "UNL-", "HVC-", "BTL-", "BMC-"];
+
+/// ROM and info from a UNIF image, PRG and CHR chunks are concatenated in chunk number order
+pub struct Unif {
+    pub info: CartridgeInfo,
+    pub prg_rom: Vec<u8>,
+    pub chr_rom: Vec<u8>,
+}
+
+impl Unif {
+    pub fn parse(data: &[u8]) -> Result<Self, CartridgeError> {
+        if data.len() < HEADER_SIZE || data[0..4] != MAGIC {
+            return Err(CartridgeError::BadMagic);
+        }
+
+        let mut board = None;
+        let mut prg_chunks: [Option<&[u8]>; 16] = [None; 16];
+        let mut chr_chunks: [Option<&[u8]>; 16] = [None; 16];
+        let mut mirror = Mirror::Horizontal;
+        let mut has_battery = false;
+        let mut timing = Timing::Ntsc;
+
+        let mut offset = HEADER_SIZE;
+        while offset < data.len() {
+            let Some(header) = data.get(offset..offset + CHUNK_HEADER_SIZE) else {
+                return Err(CartridgeError::Malformed(format!("chunk header at {offset:#X} is truncated")));
+            };
+            let id = &header[0..4];
+            let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+
+            let start = offset + CHUNK_HEADER_SIZE;
+            let Some(chunk) = start.checked_add(len).and_then(|end| data.get(start..end)) else {
+                return Err(CartridgeError::Malformed(format!("{} chunk is truncated", String::from_utf8_lossy(id))));
+            };
+
+            match id {
+                b"MAPR" => {
+                    let name = chunk.split(|byte| *byte == 0).next().unwrap_or_default();
+                    board = Some(String::from_utf8_lossy(name).trim().to_string());
+                }
+                [b'P', b'R', b'G', number] | [b'C', b'H', b'R', number] if number.is_ascii_hexdigit() => {
+                    let index = (*number as char).to_digit(16).unwrap_or_default() as usize;
+                    if id[0] == b'P' {
+                        prg_chunks[index] = Some(chunk);
+                    } else {
+                        chr_chunks[index] = Some(chunk);
+                    }
+                }
+                b"MIRR" => {
+                    mirror = match chunk.first() {
+                        Some(1) => Mirror::Vertical,
+                        Some(2) => Mirror::OneScreenLo,
+                        Some(3) => Mirror::OneScreenHi,
+                        Some(4) => Mirror::FourScreen,
+                        // 5 is mapper controlled, the mapper overrides it anyway
+                        _ => Mirror::Horizontal,
+                    };
+                }
+                b"BATR" => has_battery = chunk.first().is_none_or(|battery| *battery != 0),
+                b"TVCI" => {
+                    timing = match chunk.first() {
+                        Some(1) => Timing::Pal,
+                        Some(2) => Timing::MultiRegion,
+                        _ => Timing::Ntsc,
+                    };
+                }
+                // Name, checksums, dumper info, controllers...
+                _ => {}
+            }
+
+            offset = start + len;
+        }
+
+        let Some(board) = board else {
+            return Err(CartridgeError::Malformed("no MAPR chunk, the board is unknown".to_string()));
+        };
+        let mapper_id = Self::board_mapper(&board).ok_or(CartridgeError::UnsupportedBoard(board))?;
+
+        let prg_rom: Vec<u8> = prg_chunks.into_iter().flatten().flatten().copied().collect();
+        let chr_rom: Vec<u8> = chr_chunks.into_iter().flatten().flatten().copied().collect();
+        if prg_rom.is_empty() {
+            return Err(CartridgeError::Malformed("no PRG chunk".to_string()));
+        }
+
+        // Boards don't tell their RAM size, give them what iNES would
+        let (prg_ram_size, prg_nvram_size) = if has_battery { (0, 8 * 1024) } else { (8 * 1024, 0) };
+
+        let info = CartridgeInfo {
+            format: HeaderFormat::Unif,
+            mapper_id,
+
+            prg_rom_size: prg_rom.len(),
+            chr_rom_size: chr_rom.len(),
+            prg_ram_size,
+            prg_nvram_size,
+            chr_ram_size: if chr_rom.is_empty() { 8 * 1024 } else { 0 },
+
+            mirror,
+            has_battery,
+            timing,
+
+            ..CartridgeInfo::default()
+        };
+
+        Ok(Self { info, prg_rom, chr_rom })
+    }
+
+    /// Mapper number of a board, by its name like `NES-SLROM`
+    pub fn board_mapper(board: &str) -> Option<u16> {
+        let board = board.to_ascii_uppercase();
+        let name = BOARD_PREFIXES.iter().find_map(|prefix| board.strip_prefix(prefix)).unwrap_or(&board);
+
+        let mapper_id = match name {
+            _ if name.starts_with("NROM") => 0,
+            "SAROM" | "SBROM" | "SCROM" | "SEROM" | "SFROM" | "SGROM" | "SHROM" | "SJROM" | "SKROM" | "SLROM" | "SL1ROM" | "SNROM" | "SOROM" | "SUROM" | "SXROM" => 1,
+            "UNROM" | "UOROM" => 2,
+            "CNROM" => 3,
+            "TBROM" | "TEROM" | "TFROM" | "TGROM" | "TKROM" | "TLROM" | "TL1ROM" | "TNROM" | "TR1ROM" | "TSROM" | "TVROM" | "HKROM" => 4,
+            "AMROM" | "ANROM" | "AN1ROM" | "AOROM" => 7,
+            "GNROM" | "MHROM" => 66,
+            _ => return None,
+        };
+
+        Some(mapper_id)
+    }
+}
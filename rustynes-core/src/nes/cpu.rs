@@ -1,7 +1,8 @@
 mod addressing_modes;
 mod opcodes;
 
-use crate::nes::{Controller, ComponentCartridge, Component2C02, Bus, STACK_ADDRESS};
+use crate::nes::{InputPorts, ComponentCartridge, Component2C02, Component2A03, Bus, STACK_ADDRESS};
+use crate::nes::state::{SaveState, StateError, StateReader, StateWriter};
 
 #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -137,6 +138,9 @@ pub struct Component6502 {
     /// Only changed by the relative addressing mode
     pub addr_rel: u16,
     pub cycles: u8,
+    /// Interrupt disable flag as seen when interrupts were last polled,
+    /// CLI, SEI and PLP change the flag after the poll so their effect is delayed by one instruction
+    pub irq_disable_polled: bool,
 
     pub lookup: [Instruction; 256],
 }
@@ -146,10 +150,11 @@ pub struct Instruction {
     pub name: &'static str,
     pub cycles: u8,
     pub addr_mode: ADDRESSING_MODES,
-    pub addr_mode_fn: fn(&mut Component6502, &mut [Controller; 2], &mut ComponentCartridge, &mut Component2C02, &Bus),
-    pub opcode_fn: fn(&mut Component6502, &mut [Controller; 2], &mut ComponentCartridge, &mut Component2C02, &mut Bus),
+    pub addr_mode_fn: fn(&mut Component6502, &mut InputPorts, &mut ComponentCartridge, &mut Component2C02, &mut Component2A03, &mut Bus),
+    pub opcode_fn: fn(&mut Component6502, &mut InputPorts, &mut ComponentCartridge, &mut Component2C02, &mut Component2A03, &mut Bus),
 }
 
+#[derive(Debug, Copy, Clone)]
 pub enum Flags {
     /// bit 0 | Carry
     C = (1 << 0),
@@ -185,6 +190,7 @@ impl Component6502 {
             addr_abs: 0,
             addr_rel: 0,
             cycles: 0,
+            irq_disable_polled: false,
             
             lookup: [
                 // Row 0
@@ -478,22 +484,35 @@ impl Component6502 {
         }
     }
     
-    #[allow(clippy::unused_self)]
-    pub fn read(&self, addr: u16, controllers: &mut [Controller; 2], cartridge: &ComponentCartridge, ppu: &mut Component2C02, bus: &Bus) -> u8 {
+    #[allow(clippy::unused_self, clippy::too_many_arguments)]
+    #[cfg_attr(test, allow(unused_variables))]
+    pub fn read(&self, addr: u16, input_ports: &mut InputPorts, cartridge: &ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) -> u8 {
         #[cfg(test)]
         return bus.test_read(addr);
 
         #[cfg(not(test))]
-        bus.cpu_read(addr, false, controllers, cartridge, ppu)
+        bus.cpu_read(addr, false, input_ports, cartridge, ppu, apu)
     }
 
+    /// Like `read` but without side effects on the bus or devices, for debuggers
     #[allow(clippy::unused_self)]
-    pub fn write(&mut self, addr: u16, data: u8, controllers: &mut [Controller; 2], cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, bus: &mut Bus) {
+    #[cfg_attr(test, allow(unused_variables))]
+    pub fn peek(&self, addr: u16, input_ports: &mut InputPorts, cartridge: &ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) -> u8 {
+        #[cfg(test)]
+        return bus.ram[addr as usize];
+
+        #[cfg(not(test))]
+        bus.cpu_read(addr, true, input_ports, cartridge, ppu, apu)
+    }
+
+    #[allow(clippy::unused_self, clippy::too_many_arguments)]
+    #[cfg_attr(test, allow(unused_variables))]
+    pub fn write(&mut self, addr: u16, data: u8, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
         #[cfg(test)]
         return bus.test_write(addr, data);
 
         #[cfg(not(test))]
-        bus.cpu_write(addr, data, controllers, cartridge, ppu);
+        bus.cpu_write(addr, data, input_ports, cartridge, ppu, apu);
     }
 
     pub const fn get_flag(&self, flag: Flags) -> bool {
@@ -508,30 +527,37 @@ impl Component6502 {
         }
     }
 
-    pub fn fetch(&mut self, controllers: &mut [Controller; 2], cartridge: &ComponentCartridge, ppu: &mut Component2C02, bus: &Bus) -> u8 {
+    pub fn fetch(&mut self, input_ports: &mut InputPorts, cartridge: &ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) -> u8 {
         if (self.lookup[self.opcode as usize].addr_mode != ADDRESSING_MODES::ACC)
         && (self.lookup[self.opcode as usize].addr_mode != ADDRESSING_MODES::IMP) {
-            self.fetched = self.read(self.addr_abs, controllers, cartridge, ppu, bus);
+            self.fetched = self.read(self.addr_abs, input_ports, cartridge, ppu, apu, bus);
         }
         self.fetched
     }
 
     /// Handle clock cycles
-    pub fn tick(&mut self, controllers: &mut [Controller; 2], cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, bus: &mut Bus) {
+    pub fn tick(&mut self, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
         if self.cycles == 0 {
-            self.opcode = self.read(self.pc, controllers, cartridge, ppu, bus);
+            self.opcode = self.read(self.pc, input_ports, cartridge, ppu, apu, bus);
             
             self.pc = self.pc.wrapping_add(1);
             self.cycles = self.lookup[self.opcode as usize].cycles;
+            let irq_disable = self.get_flag(Flags::I);
             
-            (self.lookup[self.opcode as usize].addr_mode_fn)(self, controllers, cartridge, ppu, bus);
-            (self.lookup[self.opcode as usize].opcode_fn)(self, controllers, cartridge, ppu, bus);
+            (self.lookup[self.opcode as usize].addr_mode_fn)(self, input_ports, cartridge, ppu, apu, bus);
+            (self.lookup[self.opcode as usize].opcode_fn)(self, input_ports, cartridge, ppu, apu, bus);
+
+            self.irq_disable_polled = match self.opcode {
+                // CLI, SEI, PLP
+                0x58 | 0x78 | 0x28 => irq_disable,
+                _ => self.get_flag(Flags::I),
+            };
         }
         self.cycles -= 1;
     }
 
     /// Reset signal
-    pub fn reset(&mut self, controllers: &mut [Controller; 2], cartridge: &ComponentCartridge, ppu: &mut Component2C02, bus: &Bus) {
+    pub fn reset(&mut self, input_ports: &mut InputPorts, cartridge: &ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
         // Reset registers
         self.a = 0;
         self.x = 0;
@@ -540,12 +566,13 @@ impl Component6502 {
         self.sp = 0xFD;
         
         // Reset PC address is hardcoded at 0xFFFC and 0xFFFD
-        let lo = self.read(0xFFFC, controllers, cartridge, ppu, bus) as u16;
-        let hi = self.read(0xFFFD, controllers, cartridge, ppu, bus) as u16;
+        let lo = self.read(0xFFFC, input_ports, cartridge, ppu, apu, bus) as u16;
+        let hi = self.read(0xFFFD, input_ports, cartridge, ppu, apu, bus) as u16;
         self.pc = (hi << 8) | lo;
         
-        // Reset Flags
-        self.status = Flags::U as u8;
+        // Reset Flags, interrupts start disabled
+        self.status = Flags::U as u8 | Flags::I as u8;
+        self.irq_disable_polled = true;
 
         // Reset custom variables
         self.fetched = 0;
@@ -556,50 +583,49 @@ impl Component6502 {
         self.cycles = 8;
     }
 
-    /// Interrupt request signal
-    #[allow(dead_code)]
-    fn irq(&mut self, controllers: &mut [Controller; 2], cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, bus: &mut Bus) {
-        if !self.get_flag(Flags::I) {
-            // Push PC to stack (16 bits to write)
-            self.write(STACK_ADDRESS + self.sp as u16, ((self.pc >> 8) & 0x00FF) as u8, controllers, cartridge, ppu, bus);
-            self.sp = self.sp.wrapping_sub(1);
-            self.write(STACK_ADDRESS + self.sp as u16, (self.pc & 0x00FF) as u8, controllers, cartridge, ppu, bus);
-            self.sp = self.sp.wrapping_sub(1);
-
-            // Push Flags to stack
-            self.set_flag(Flags::B, false);
-            self.set_flag(Flags::U, true);
-            self.set_flag(Flags::I, true);
-            self.write(STACK_ADDRESS + self.sp as u16, self.status, controllers, cartridge, ppu, bus);
-            self.sp = self.sp.wrapping_sub(1);
-
-            // New PC address to handle the interrupt is 0xFFFE and 0xFFFF
-            let lo = self.read(0xFFFE, controllers, cartridge, ppu, bus) as u16;
-            let hi = self.read(0xFFFF, controllers, cartridge, ppu, bus) as u16;
-            self.pc = (hi << 8) | lo;
-
-            // Manually set cycles because interrupt request takes time
-            self.cycles = 7;
-        }
+    /// Interrupt request signal, the caller is responsible for checking `irq_disable_polled`
+    pub fn irq(&mut self, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
+        // Push PC to stack (16 bits to write)
+        self.write(STACK_ADDRESS + self.sp as u16, ((self.pc >> 8) & 0x00FF) as u8, input_ports, cartridge, ppu, apu, bus);
+        self.sp = self.sp.wrapping_sub(1);
+        self.write(STACK_ADDRESS + self.sp as u16, (self.pc & 0x00FF) as u8, input_ports, cartridge, ppu, apu, bus);
+        self.sp = self.sp.wrapping_sub(1);
+
+        // Push Flags to stack, I is only set once they are pushed so RTI restores it
+        self.set_flag(Flags::B, false);
+        self.set_flag(Flags::U, true);
+        self.write(STACK_ADDRESS + self.sp as u16, self.status, input_ports, cartridge, ppu, apu, bus);
+        self.sp = self.sp.wrapping_sub(1);
+        self.set_flag(Flags::I, true);
+        self.irq_disable_polled = true;
+
+        // New PC address to handle the interrupt is 0xFFFE and 0xFFFF
+        let lo = self.read(0xFFFE, input_ports, cartridge, ppu, apu, bus) as u16;
+        let hi = self.read(0xFFFF, input_ports, cartridge, ppu, apu, bus) as u16;
+        self.pc = (hi << 8) | lo;
+
+        // Manually set cycles because interrupt request takes time
+        self.cycles = 7;
     }
 
     /// Non-maskable interrupt request signal
-    pub fn nmi(&mut self, controllers: &mut [Controller; 2], cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, bus: &mut Bus) {
+    pub fn nmi(&mut self, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
         // Push PC to stack (16 bits to write)
-        self.write(STACK_ADDRESS + self.sp as u16, ((self.pc >> 8) & 0x00FF) as u8, controllers, cartridge, ppu, bus);
+        self.write(STACK_ADDRESS + self.sp as u16, ((self.pc >> 8) & 0x00FF) as u8, input_ports, cartridge, ppu, apu, bus);
         self.sp = self.sp.wrapping_sub(1);
-        self.write(STACK_ADDRESS + self.sp as u16, (self.pc & 0x00FF) as u8, controllers, cartridge, ppu, bus);
+        self.write(STACK_ADDRESS + self.sp as u16, (self.pc & 0x00FF) as u8, input_ports, cartridge, ppu, apu, bus);
         self.sp = self.sp.wrapping_sub(1);
 
-        // Push Flags to stack
+        // Push Flags to stack, I is only set once they are pushed so RTI restores it
         self.set_flag(Flags::B, false);
-        self.set_flag(Flags::I, true);
-        self.write(STACK_ADDRESS + self.sp as u16, self.status, controllers, cartridge, ppu, bus);
+        self.write(STACK_ADDRESS + self.sp as u16, self.status, input_ports, cartridge, ppu, apu, bus);
         self.sp = self.sp.wrapping_sub(1);
+        self.set_flag(Flags::I, true);
+        self.irq_disable_polled = true;
 
         // New PC address to handle the interrupt is 0xFFFA and 0xFFFB
-        let lo = self.read(0xFFFA, controllers, cartridge, ppu, bus) as u16;
-        let hi = self.read(0xFFFB, controllers, cartridge, ppu, bus) as u16;
+        let lo = self.read(0xFFFA, input_ports, cartridge, ppu, apu, bus) as u16;
+        let hi = self.read(0xFFFB, input_ports, cartridge, ppu, apu, bus) as u16;
         self.pc = (hi << 8) | lo;
 
         // Manually set cycles because non-maskable interrupt request takes time
@@ -608,6 +634,43 @@ impl Component6502 {
 
 }
 
+/// The `lookup` table is built by `new` and never changes
+impl SaveState for Component6502 {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.a);
+        state.write(&self.x);
+        state.write(&self.y);
+        state.write(&self.sp);
+        state.write(&self.pc);
+        state.write(&self.status);
+
+        state.write(&self.opcode);
+        state.write(&self.fetched);
+        state.write(&self.addr_abs);
+        state.write(&self.addr_rel);
+        state.write(&self.cycles);
+        state.write(&self.irq_disable_polled);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.a = state.read()?;
+        self.x = state.read()?;
+        self.y = state.read()?;
+        self.sp = state.read()?;
+        self.pc = state.read()?;
+        self.status = state.read()?;
+
+        self.opcode = state.read()?;
+        self.fetched = state.read()?;
+        self.addr_abs = state.read()?;
+        self.addr_rel = state.read()?;
+        self.cycles = state.read()?;
+        self.irq_disable_polled = state.read()?;
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 impl Component6502 {
     pub fn test_reset(&mut self) {
@@ -619,17 +682,24 @@ impl Component6502 {
         self.sp = 0;
     }
 
-    pub fn test_tick(&mut self, controllers: &mut [Controller; 2], cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, bus: &mut Bus) {
+    pub fn test_tick(&mut self, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
         if self.cycles == 0 {
             println!("Reading opcode at address: {}", self.pc);
-            self.opcode = self.read(self.pc, controllers, cartridge, ppu, bus);
+            self.opcode = self.read(self.pc, input_ports, cartridge, ppu, apu, bus);
             println!("\nExecuting opcode: {:02X} ({})", self.opcode, self.lookup[self.opcode as usize].name);
             
             self.pc = self.pc.wrapping_add(1);
             self.cycles = self.lookup[self.opcode as usize].cycles;
+            let irq_disable = self.get_flag(Flags::I);
             
-            (self.lookup[self.opcode as usize].addr_mode_fn)(self, controllers, cartridge, ppu, bus);
-            (self.lookup[self.opcode as usize].opcode_fn)(self, controllers, cartridge, ppu, bus);
+            (self.lookup[self.opcode as usize].addr_mode_fn)(self, input_ports, cartridge, ppu, apu, bus);
+            (self.lookup[self.opcode as usize].opcode_fn)(self, input_ports, cartridge, ppu, apu, bus);
+
+            self.irq_disable_polled = match self.opcode {
+                // CLI, SEI, PLP
+                0x58 | 0x78 | 0x28 => irq_disable,
+                _ => self.get_flag(Flags::I),
+            };
         }
         self.cycles -= 1;
     }
@@ -1,4 +1,4 @@
-use crate::nes::{Bus, Component2C02, Component6502, ComponentCartridge, Controller};
+use crate::nes::{Bus, Component2A03, Component2C02, Component6502, ComponentCartridge, InputPorts};
 
 const fn is_a_read_instruction(opcode: u8) -> bool {
     matches!(opcode,
@@ -43,26 +43,26 @@ const fn is_a_stack_instruction(opcode: u8) -> bool {
 #[allow(non_snake_case)]
 impl Component6502 {
     /// Accumulator addressing mode
-    pub fn addr_ACC(&mut self, _controllers: &mut [Controller; 2], _cartridge: &mut ComponentCartridge, _ppu: &mut Component2C02, _bus: &Bus) {
-        self.read(self.pc, _controllers, _cartridge, _ppu, _bus);
+    pub fn addr_ACC(&mut self, _controllers: &mut InputPorts, _cartridge: &mut ComponentCartridge, _ppu: &mut Component2C02, _apu: &mut Component2A03, _bus: &mut Bus) {
+        self.read(self.pc, _controllers, _cartridge, _ppu, _apu, _bus);
         self.fetched = self.a;
     }
 
     /// Immediate addressing mode
-    pub fn addr_IMM(&mut self, _controllers: &mut [Controller; 2], _cartridge: &mut ComponentCartridge, _ppu: &mut Component2C02, _bus: &Bus) {
+    pub fn addr_IMM(&mut self, _controllers: &mut InputPorts, _cartridge: &mut ComponentCartridge, _ppu: &mut Component2C02, _apu: &mut Component2A03, _bus: &mut Bus) {
         self.addr_abs = self.pc;
         self.pc = self.pc.wrapping_add(1);
     }
     
     /// Absolute addressing mode
-    pub fn addr_ABS(&mut self, controllers: &mut [Controller; 2], cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, bus: &Bus) {
-        let low = self.read(self.pc, controllers, cartridge, ppu, bus);
+    pub fn addr_ABS(&mut self, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
+        let low = self.read(self.pc, input_ports, cartridge, ppu, apu, bus);
         self.pc = self.pc.wrapping_add(1);
 
         let high = if self.opcode == 0x20 {
             0
         } else {
-            let res = self.read(self.pc, controllers, cartridge, ppu, bus);
+            let res = self.read(self.pc, input_ports, cartridge, ppu, apu, bus);
             self.pc = self.pc.wrapping_add(1);
             res
         };
@@ -75,10 +75,10 @@ impl Component6502 {
     }
     
     /// Absolute addressing mode with X offset
-    pub fn addr_ABX(&mut self, controllers: &mut [Controller; 2], cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, bus: &Bus) {
-        let low = self.read(self.pc, controllers, cartridge, ppu, bus);
+    pub fn addr_ABX(&mut self, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
+        let low = self.read(self.pc, input_ports, cartridge, ppu, apu, bus);
         self.pc = self.pc.wrapping_add(1);
-        let high = self.read(self.pc, controllers, cartridge, ppu, bus);
+        let high = self.read(self.pc, input_ports, cartridge, ppu, apu, bus);
         self.pc = self.pc.wrapping_add(1);
 
         let effective_address = ((high as u16) << 8) | low as u16;
@@ -87,20 +87,20 @@ impl Component6502 {
         if is_a_read_instruction(self.opcode) {
             if effective_address & 0xFF00 != absolute_address & 0xFF00 {
                 self.cycles += 1;
-                self.read(effective_address & 0xFF00 | absolute_address & 0x00FF, controllers, cartridge, ppu, bus);
+                self.read(effective_address & 0xFF00 | absolute_address & 0x00FF, input_ports, cartridge, ppu, apu, bus);
             }
         } else if is_a_read_modify_write_instruction(self.opcode) || is_a_write_instruction(self.opcode) {
-            self.read(effective_address & 0xFF00 | absolute_address & 0x00FF, controllers, cartridge, ppu, bus);
+            self.read(effective_address & 0xFF00 | absolute_address & 0x00FF, input_ports, cartridge, ppu, apu, bus);
         }
 
         self.addr_abs = absolute_address;
     }
     
     /// Absolute addressing mode with Y offset
-    pub fn addr_ABY(&mut self, controllers: &mut [Controller; 2], cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, bus: &Bus) {
-        let low = self.read(self.pc, controllers, cartridge, ppu, bus);
+    pub fn addr_ABY(&mut self, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
+        let low = self.read(self.pc, input_ports, cartridge, ppu, apu, bus);
         self.pc = self.pc.wrapping_add(1);
-        let high = self.read(self.pc, controllers, cartridge, ppu, bus);
+        let high = self.read(self.pc, input_ports, cartridge, ppu, apu, bus);
         self.pc = self.pc.wrapping_add(1);
 
         let effective_address = ((high as u16) << 8) | low as u16;
@@ -109,29 +109,29 @@ impl Component6502 {
         if is_a_read_instruction(self.opcode) {
             if effective_address & 0xFF00 != absolute_address & 0xFF00 {
                 self.cycles += 1;
-                self.read(effective_address & 0xFF00 | absolute_address & 0x00FF, controllers, cartridge, ppu, bus);
+                self.read(effective_address & 0xFF00 | absolute_address & 0x00FF, input_ports, cartridge, ppu, apu, bus);
             }
         } else if is_a_read_modify_write_instruction(self.opcode) || is_a_write_instruction(self.opcode) {
-            self.read(effective_address & 0xFF00 | absolute_address & 0x00FF, controllers, cartridge, ppu, bus);
+            self.read(effective_address & 0xFF00 | absolute_address & 0x00FF, input_ports, cartridge, ppu, apu, bus);
         }
 
         self.addr_abs = absolute_address;
     }
     
     /// Zero Page addressing mode
-    pub fn addr_ZP0(&mut self, controllers: &mut [Controller; 2], cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, bus: &Bus) {
-        let effective_address = self.read(self.pc, controllers, cartridge, ppu, bus);
+    pub fn addr_ZP0(&mut self, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
+        let effective_address = self.read(self.pc, input_ports, cartridge, ppu, apu, bus);
         self.pc = self.pc.wrapping_add(1);
         
         self.addr_abs = effective_address as u16;
     }
     
     /// Zero Page addressing mode with X offset
-    pub fn addr_ZPX(&mut self, controllers: &mut [Controller; 2], cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, bus: &Bus) {
-        let address = self.read(self.pc, controllers, cartridge, ppu, bus);
+    pub fn addr_ZPX(&mut self, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
+        let address = self.read(self.pc, input_ports, cartridge, ppu, apu, bus);
         self.pc = self.pc.wrapping_add(1);
 
-        self.read(address as u16, controllers, cartridge, ppu, bus);
+        self.read(address as u16, input_ports, cartridge, ppu, apu, bus);
 
         let effective_address = address.wrapping_add(self.x);
 
@@ -139,11 +139,11 @@ impl Component6502 {
     }
     
     /// Zero Page addressing mode with Y offset
-    pub fn addr_ZPY(&mut self, controllers: &mut [Controller; 2], cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, bus: &Bus) {
-        let address = self.read(self.pc, controllers, cartridge, ppu, bus);
+    pub fn addr_ZPY(&mut self, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
+        let address = self.read(self.pc, input_ports, cartridge, ppu, apu, bus);
         self.pc = self.pc.wrapping_add(1);
 
-        self.read(address as u16, controllers, cartridge, ppu, bus);
+        self.read(address as u16, input_ports, cartridge, ppu, apu, bus);
 
         let effective_address = address.wrapping_add(self.y);
 
@@ -152,15 +152,15 @@ impl Component6502 {
     
     /// Implied addressing mode
     #[allow(clippy::unused_self)]
-    pub fn addr_IMP(&mut self, controllers: &mut [Controller; 2], cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, bus: &Bus) {
+    pub fn addr_IMP(&mut self, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
         if self.opcode != 0x00 {
-            self.read(self.pc, controllers, cartridge, ppu, bus);
+            self.read(self.pc, input_ports, cartridge, ppu, apu, bus);
         }
     }
     
     /// Relative addressing mode
-    pub fn addr_REL(&mut self, controllers: &mut [Controller; 2], cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, bus: &Bus) {
-        let operand = self.read(self.pc, controllers, cartridge, ppu, bus);
+    pub fn addr_REL(&mut self, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
+        let operand = self.read(self.pc, input_ports, cartridge, ppu, apu, bus);
         self.pc = self.pc.wrapping_add(1);
 
         self.addr_rel = operand as i8 as u16;
@@ -168,43 +168,43 @@ impl Component6502 {
     
     /// Indirect addressing mode
     /// (implements a hardware bug)
-    pub fn addr_IND(&mut self, controllers: &mut [Controller; 2], cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, bus: &Bus) {
-        let ptr_lo = self.read(self.pc, controllers, cartridge, ppu, bus) as u16;
+    pub fn addr_IND(&mut self, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
+        let ptr_lo = self.read(self.pc, input_ports, cartridge, ppu, apu, bus) as u16;
         self.pc = self.pc.wrapping_add(1);
         
-        let ptr_hi = self.read(self.pc, controllers, cartridge, ppu, bus) as u16;
+        let ptr_hi = self.read(self.pc, input_ports, cartridge, ppu, apu, bus) as u16;
         self.pc = self.pc.wrapping_add(1);
         
         let ptr = (ptr_hi << 8) | ptr_lo;
         
         if ptr_lo == 0x00FF {
-            self.addr_abs = (self.read(ptr & 0xFF00, controllers, cartridge, ppu, bus) as u16) << 8 | self.read(ptr, controllers, cartridge, ppu, bus) as u16;
+            self.addr_abs = (self.read(ptr & 0xFF00, input_ports, cartridge, ppu, apu, bus) as u16) << 8 | self.read(ptr, input_ports, cartridge, ppu, apu, bus) as u16;
         } else {
-            self.addr_abs = (self.read(ptr + 1, controllers, cartridge, ppu, bus) as u16) << 8 | self.read(ptr, controllers, cartridge, ppu, bus) as u16;
+            self.addr_abs = (self.read(ptr + 1, input_ports, cartridge, ppu, apu, bus) as u16) << 8 | self.read(ptr, input_ports, cartridge, ppu, apu, bus) as u16;
         }
     }
     
     /// Indirect addressing mode with X offset (zero page)
-    pub fn addr_IZX(&mut self, controllers: &mut [Controller; 2], cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, bus: &Bus) {
-        let pointer = self.read(self.pc, controllers, cartridge, ppu, bus);
+    pub fn addr_IZX(&mut self, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
+        let pointer = self.read(self.pc, input_ports, cartridge, ppu, apu, bus);
         self.pc = self.pc.wrapping_add(1);
         
-        self.read(pointer as u16, controllers, cartridge, ppu, bus);
+        self.read(pointer as u16, input_ports, cartridge, ppu, apu, bus);
         let effective_addr = pointer.wrapping_add(self.x);
         
-        let low = self.read(effective_addr as u16, controllers, cartridge, ppu, bus);
-        let high = self.read(effective_addr.wrapping_add(1) as u16, controllers, cartridge, ppu, bus);
+        let low = self.read(effective_addr as u16, input_ports, cartridge, ppu, apu, bus);
+        let high = self.read(effective_addr.wrapping_add(1) as u16, input_ports, cartridge, ppu, apu, bus);
 
         self.addr_abs = ((high as u16) << 8) | low as u16;
     }
     
     /// Indirect addressing mode with Y offset (zero page)
-    pub fn addr_IZY(&mut self, controllers: &mut [Controller; 2], cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, bus: &Bus) {
-        let pointer = self.read(self.pc, controllers, cartridge, ppu, bus);
+    pub fn addr_IZY(&mut self, input_ports: &mut InputPorts, cartridge: &mut ComponentCartridge, ppu: &mut Component2C02, apu: &mut Component2A03, bus: &mut Bus) {
+        let pointer = self.read(self.pc, input_ports, cartridge, ppu, apu, bus);
         self.pc = self.pc.wrapping_add(1);
 
-        let low = self.read(pointer as u16, controllers, cartridge, ppu, bus);
-        let high = self.read(pointer.wrapping_add(1) as u16, controllers, cartridge, ppu, bus);
+        let low = self.read(pointer as u16, input_ports, cartridge, ppu, apu, bus);
+        let high = self.read(pointer.wrapping_add(1) as u16, input_ports, cartridge, ppu, apu, bus);
 
         let effective_address = ((high as u16) << 8) | low as u16;
         let indirect_address = effective_address.wrapping_add(self.y as u16);
@@ -212,10 +212,10 @@ impl Component6502 {
         if is_a_read_instruction(self.opcode) {
             if effective_address & 0xFF00 != indirect_address & 0xFF00 {
                 self.cycles += 1;
-                self.read(effective_address & 0xFF00 | indirect_address & 0x00FF, controllers, cartridge, ppu, bus);
+                self.read(effective_address & 0xFF00 | indirect_address & 0x00FF, input_ports, cartridge, ppu, apu, bus);
             }
         } else if is_a_read_modify_write_instruction(self.opcode) || is_a_write_instruction(self.opcode) {
-            self.read(effective_address & 0xFF00 | indirect_address & 0x00FF, controllers, cartridge, ppu, bus);
+            self.read(effective_address & 0xFF00 | indirect_address & 0x00FF, input_ports, cartridge, ppu, apu, bus);
         }
 
         self.addr_abs = indirect_address;
@@ -0,0 +1,55 @@
+mod controller;
+mod four_score;
+mod zapper;
+mod paddle;
+
+pub use controller::Controller;
+pub use four_score::FourScore;
+pub use zapper::Zapper;
+pub use paddle::ArkanoidPaddle;
+
+use crate::nes::{Pixel, StateError, StateReader, StateWriter};
+
+/// What is plugged into each controller port, read through $4016 and $4017
+pub type InputPorts = [Box<dyn InputDevice>; 2];
+
+/// Something plugged into a controller port.
+///
+/// Writes to $4016 go to both ports, each port has its own register to read from. Devices that need more
+/// than buttons, like light guns, get what they need through the defaulted methods.
+pub trait InputDevice {
+    /// $4016 was written, its low bit is the strobe line shared by both ports
+    fn write(&mut self, data: u8);
+    /// Bits 0-4 of a read from the register of the port, advancing the device to its next bit when it's serial
+    fn read(&mut self) -> u8;
+    /// What `read` would return, without advancing the device, for debuggers
+    fn peek(&self) -> u8;
+
+    /// Power cycle, inputs are kept
+    fn reset(&mut self) {}
+
+    /// Buttons of standard controller `pad`, in the order they are read, multitaps have more than one
+    fn buttons(&self, _pad: usize) -> u8 {
+        0
+    }
+    fn set_buttons(&mut self, _pad: usize, _state: u8) {}
+
+    /// Where the player points on the screen, in pixels, `None` when it's off screen, and whether the
+    /// trigger or button is held
+    fn set_pointer(&mut self, _position: Option<(u16, u16)>, _is_pressed: bool) {}
+
+    /// Called when the PPU starts drawing `scanline`, with the frame drawn up to there in `screen`
+    fn ppu_scanline(&mut self, _scanline: i16, _screen: &[Pixel]) {}
+
+    /// Latches and shift registers for save states
+    fn save_state(&self, _state: &mut StateWriter) {}
+    fn load_state(&mut self, _state: &mut StateReader<'_>) -> Result<(), StateError> {
+        Ok(())
+    }
+}
+
+impl core::fmt::Debug for dyn InputDevice {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "InputDevice")
+    }
+}
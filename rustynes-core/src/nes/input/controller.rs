@@ -0,0 +1,113 @@
+use crate::nes::{StateError, StateReader, StateWriter};
+
+use super::InputDevice;
+
+/// Standard joypad, an 8-bit shift register of its buttons.
+///
+/// The buttons are loaded into the register for as long as the strobe is high, the last load is what
+/// gets shifted out once it goes low. Reading while the strobe is high keeps returning the first button.
+#[derive(Debug, Copy, Clone)]
+pub struct Controller {
+    state: u8,
+    temp_state: u8,
+    strobe: bool,
+}
+
+impl Controller {
+    pub const A: u8 = 0x80;
+    pub const B: u8 = 0x40;
+    pub const SELECT: u8 = 0x20;
+    pub const START: u8 = 0x10;
+    pub const UP: u8 = 0x08;
+    pub const DOWN: u8 = 0x04;
+    pub const LEFT: u8 = 0x02;
+    pub const RIGHT: u8 = 0x01;
+
+    pub const fn new() -> Self {
+        Self {
+            state: 0,
+            temp_state: 0,
+            strobe: false,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn check_inputs(&mut self, a: bool, b: bool, select: bool, start: bool, up: bool, down: bool, left: bool, right: bool) {
+        self.state = 0x00;
+
+        self.state |= if a { Self::A } else { 0x00 };
+        self.state |= if b { Self::B } else { 0x00 };
+        self.state |= if select { Self::SELECT } else { 0x00 };
+        self.state |= if start { Self::START } else { 0x00 };
+        self.state |= if up { Self::UP } else { 0x00 };
+        self.state |= if down { Self::DOWN } else { 0x00 };
+        self.state |= if left { Self::LEFT } else { 0x00 };
+        self.state |= if right { Self::RIGHT } else { 0x00 };
+    }
+
+    /// Buttons pressed, in the order they are read
+    pub const fn get_state(&self) -> u8 {
+        self.state
+    }
+
+    pub fn set_state(&mut self, state: u8) {
+        self.state = state;
+    }
+}
+
+impl InputDevice for Controller {
+    fn write(&mut self, data: u8) {
+        // The register follows the buttons while the strobe is high, it keeps the last ones when it goes low
+        if self.strobe || data & 0x01 != 0 {
+            self.temp_state = self.state;
+        }
+        self.strobe = data & 0x01 != 0;
+    }
+
+    fn read(&mut self) -> u8 {
+        let data = self.peek();
+
+        if self.strobe {
+            self.temp_state = self.state;
+        }
+        // Official controllers shift in 1s, every read after the 8 buttons returns 1
+        self.temp_state = (self.temp_state << 1) | 0x01;
+    
+        data
+    }
+
+    fn peek(&self) -> u8 {
+        let register = if self.strobe { self.state } else { self.temp_state };
+
+        if register & 0x80 > 0 { 0x01 } else { 0x00 }
+    }
+
+    fn reset(&mut self) {
+        self.temp_state = 0;
+        self.strobe = false;
+    }
+
+    fn buttons(&self, pad: usize) -> u8 {
+        if pad == 0 { self.state } else { 0 }
+    }
+
+    fn set_buttons(&mut self, pad: usize, state: u8) {
+        if pad == 0 {
+            self.state = state;
+        }
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.state);
+        state.write(&self.temp_state);
+        state.write(&self.strobe);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.state = state.read()?;
+        self.temp_state = state.read()?;
+        self.strobe = state.read()?;
+
+        Ok(())
+    }
+}
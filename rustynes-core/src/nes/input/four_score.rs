@@ -0,0 +1,96 @@
+use crate::nes::{StateError, StateReader, StateWriter};
+
+use super::{Controller, InputDevice};
+
+/// Half of the NES Four Score multitap, the part on one port, so both ports need one.
+///
+/// A port reads 8 bits of its first controller, 8 bits of its second one, then the signature byte telling
+/// games a Four Score is plugged, and 1s after that. Port 1 has controllers 1 and 3, port 2 has controllers
+/// 2 and 4. The strobe works like the one of a `Controller`.
+#[derive(Debug, Copy, Clone)]
+pub struct FourScore {
+    pads: [Controller; 2],
+    signature: u8,
+    shift_register: u32,
+    strobe: bool,
+}
+
+impl FourScore {
+    /// Signatures of both ports, read after the controllers
+    const SIGNATURES: [u8; 2] = [0x10, 0x20];
+
+    /// Half plugged into `port`, 0 or 1
+    pub const fn new(port: usize) -> Self {
+        Self {
+            pads: [Controller::new(), Controller::new()],
+            signature: Self::SIGNATURES[port & 0x01],
+            shift_register: 0,
+            strobe: false,
+        }
+    }
+
+    fn load(&mut self) {
+        self.shift_register = (self.pads[0].get_state() as u32) << 16 | (self.pads[1].get_state() as u32) << 8 | self.signature as u32;
+    }
+}
+
+impl InputDevice for FourScore {
+    fn write(&mut self, data: u8) {
+        if self.strobe || data & 0x01 != 0 {
+            self.load();
+        }
+        self.strobe = data & 0x01 != 0;
+    }
+
+    fn read(&mut self) -> u8 {
+        let data = self.peek();
+
+        if self.strobe {
+            self.load();
+        }
+        self.shift_register = ((self.shift_register << 1) | 0x01) & 0x00FF_FFFF;
+
+        data
+    }
+
+    fn peek(&self) -> u8 {
+        if self.strobe {
+            self.pads[0].get_state() >> 7
+        } else {
+            ((self.shift_register >> 23) & 0x01) as u8
+        }
+    }
+
+    fn reset(&mut self) {
+        self.shift_register = 0;
+        self.strobe = false;
+    }
+
+    fn buttons(&self, pad: usize) -> u8 {
+        self.pads.get(pad).map_or(0, Controller::get_state)
+    }
+
+    fn set_buttons(&mut self, pad: usize, state: u8) {
+        if let Some(controller) = self.pads.get_mut(pad) {
+            controller.set_state(state);
+        }
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        for pad in &self.pads {
+            pad.save_state(state);
+        }
+        state.write(&self.shift_register);
+        state.write(&self.strobe);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        for pad in &mut self.pads {
+            pad.load_state(state)?;
+        }
+        self.shift_register = state.read()?;
+        self.strobe = state.read()?;
+
+        Ok(())
+    }
+}
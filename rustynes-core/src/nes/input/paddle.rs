@@ -0,0 +1,91 @@
+use crate::constants::NES_SCREEN_WIDTH;
+use crate::nes::{StateError, StateReader, StateWriter};
+
+use super::InputDevice;
+
+/// Vaus controller shipped with the NES Arkanoid, a knob read as an inverted 8-bit serial value and a button.
+/// The knob value is latched while the strobe is high, like the buttons of a `Controller`.
+#[derive(Debug, Copy, Clone)]
+pub struct ArkanoidPaddle {
+    position: u8,
+    is_button_pressed: bool,
+    shift_register: u8,
+    strobe: bool,
+}
+
+impl ArkanoidPaddle {
+    /// Knob values at both ends of its course, left then right
+    const RANGE: (u8, u8) = (0x62, 0xF2);
+
+    pub const fn new() -> Self {
+        Self {
+            position: Self::RANGE.0,
+            is_button_pressed: false,
+            shift_register: 0,
+            strobe: false,
+        }
+    }
+
+    /// Knob value, larger when turned right
+    pub const fn get_position(&self) -> u8 {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: u8) {
+        self.position = position.clamp(Self::RANGE.0, Self::RANGE.1);
+    }
+}
+
+impl InputDevice for ArkanoidPaddle {
+    fn write(&mut self, data: u8) {
+        if self.strobe || data & 0x01 != 0 {
+            self.shift_register = !self.position;
+        }
+        self.strobe = data & 0x01 != 0;
+    }
+
+    /// Bit 3 is set while the button is pressed, bit 4 is the next bit of the knob value
+    fn read(&mut self) -> u8 {
+        let data = self.peek();
+
+        if self.strobe {
+            self.shift_register = !self.position;
+        }
+        self.shift_register <<= 1;
+
+        data
+    }
+
+    fn peek(&self) -> u8 {
+        let register = if self.strobe { !self.position } else { self.shift_register };
+        let button = if self.is_button_pressed { 0x08 } else { 0x00 };
+
+        ((register >> 7) << 4) | button
+    }
+
+    fn reset(&mut self) {
+        self.shift_register = 0;
+        self.strobe = false;
+    }
+
+    /// The knob follows the horizontal position, across the width of the screen
+    fn set_pointer(&mut self, position: Option<(u16, u16)>, is_pressed: bool) {
+        if let Some((x, _)) = position {
+            let (left, right) = (Self::RANGE.0 as u16, Self::RANGE.1 as u16);
+            self.position = (left + x.min(NES_SCREEN_WIDTH - 1) * (right - left) / (NES_SCREEN_WIDTH - 1)) as u8;
+        }
+        self.is_button_pressed = is_pressed;
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.shift_register);
+        state.write(&self.strobe);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.shift_register = state.read()?;
+        self.strobe = state.read()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use crate::constants::{NES_SCREEN_HEIGHT, NES_SCREEN_WIDTH};
+use crate::nes::{Pixel, StateError, StateReader, StateWriter};
+
+use super::InputDevice;
+
+/// Light gun, it sees light when the spot it points at was drawn bright in the last few scanlines
+#[derive(Debug, Copy, Clone)]
+pub struct Zapper {
+    position: Option<(u16, u16)>,
+    is_trigger_pulled: bool,
+    /// Scanlines the photodiode still senses light for
+    light_scanlines_left: u8,
+}
+
+impl Zapper {
+    /// How long the photodiode keeps sensing after the beam drew a bright pixel
+    const LIGHT_SCANLINES: u8 = 20;
+    /// Average of the color channels counting as bright
+    const LIGHT_THRESHOLD: u16 = 0x80;
+
+    pub const fn new() -> Self {
+        Self {
+            position: None,
+            is_trigger_pulled: false,
+            light_scanlines_left: 0,
+        }
+    }
+
+    /// Whether the photodiode sees light right now
+    pub const fn is_light_sensed(&self) -> bool {
+        self.light_scanlines_left > 0
+    }
+}
+
+impl InputDevice for Zapper {
+    fn write(&mut self, _data: u8) {}
+
+    /// Bit 3 is cleared while light is sensed, bit 4 is set while the trigger is pulled
+    fn read(&mut self) -> u8 {
+        self.peek()
+    }
+
+    fn peek(&self) -> u8 {
+        let light = if self.is_light_sensed() { 0x00 } else { 0x08 };
+        let trigger = if self.is_trigger_pulled { 0x10 } else { 0x00 };
+
+        light | trigger
+    }
+
+    fn reset(&mut self) {
+        self.light_scanlines_left = 0;
+    }
+
+    fn set_pointer(&mut self, position: Option<(u16, u16)>, is_pressed: bool) {
+        self.position = position.filter(|&(x, y)| x < NES_SCREEN_WIDTH && y < NES_SCREEN_HEIGHT);
+        self.is_trigger_pulled = is_pressed;
+    }
+
+    fn ppu_scanline(&mut self, scanline: i16, screen: &[Pixel]) {
+        self.light_scanlines_left = self.light_scanlines_left.saturating_sub(1);
+
+        // The line the gun points at was just drawn
+        let Some((x, y)) = self.position else {
+            return;
+        };
+        if scanline - 1 == y as i16 {
+            let pixel = screen[y as usize * NES_SCREEN_WIDTH as usize + x as usize];
+            if (pixel.r as u16 + pixel.g as u16 + pixel.b as u16) / 3 >= Self::LIGHT_THRESHOLD {
+                self.light_scanlines_left = Self::LIGHT_SCANLINES;
+            }
+        }
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.light_scanlines_left);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.light_scanlines_left = state.read()?;
+
+        Ok(())
+    }
+}
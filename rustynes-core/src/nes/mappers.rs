@@ -0,0 +1,89 @@
+pub mod mapper_000;
+pub mod mapper_001;
+pub mod mapper_002;
+pub mod mapper_003;
+pub mod mapper_004;
+pub mod mapper_007;
+pub mod mapper_066;
+pub mod fds;
+
+use crate::nes::cartridge::Mirror;
+use crate::nes::state::{StateError, StateReader, StateWriter};
+
+pub trait Mapper {
+    fn new(prg_banks_count: u8, chr_banks_count: u8) -> Self where Self: Sized;
+    
+    /// `data` is only used when the mapper handles the read itself, signaled by `mapped_addr` being 0xFFFFFFFF
+    /// `read_only` reads (debugger peeks) must not have side effects on the mapper
+    fn cpu_map_read(&self, addr: u16, read_only: bool, mapped_addr: &mut u32, data: &mut u8) -> bool;
+    fn cpu_map_write(&mut self, addr: u16, mapped_addr: &mut u32, data: u8) -> bool;
+    
+    fn ppu_map_read(&self, addr: u16, mapped_addr: &mut u32) -> bool;
+    fn ppu_map_write(&self, addr: u16, mapped_addr: &mut u32) -> bool;
+
+    /// Maps $6000-$7FFF to an offset in the cartridge PRG-RAM, returns false while it's disabled
+    fn prg_ram_map_read(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        if (0x6000..=0x7FFF).contains(&addr) {
+            *mapped_addr = (addr & 0x1FFF) as u32;
+            return true;
+        }
+
+        false
+    }
+    fn prg_ram_map_write(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        self.prg_ram_map_read(addr, mapped_addr)
+    }
+
+    fn reset(&mut self) {}
+
+    /// Called every PPU cycle with the address currently on the PPU bus
+    fn ppu_tick(&mut self, _addr: u16) {}
+
+    /// Called every CPU cycle
+    fn cpu_tick(&mut self) {}
+
+    fn mirror(&self) -> Mirror {
+        Mirror::Hardware
+    }
+
+    /// Whether the mapper is currently pulling the CPU IRQ line
+    fn is_irq_asserted(&self) -> bool {
+        false
+    }
+
+    /// Expansion audio level, on the scale of the APU mixer output
+    fn audio_output(&self) -> f32 {
+        0.0
+    }
+
+    /// Disk sides of a Famicom Disk System image, 0 for cartridges
+    fn disk_sides_count(&self) -> usize {
+        0
+    }
+
+    /// Side currently inserted, or about to be
+    fn disk_side(&self) -> Option<usize> {
+        None
+    }
+
+    /// Ejects the disk, then inserts `side` once the BIOS had time to notice
+    fn insert_disk_side(&mut self, _side: Option<usize>) {}
+
+    /// Registers and counters for save states, the bank counts come from the cartridge and are left out
+    fn save_state(&self, _state: &mut StateWriter) {}
+    fn load_state(&mut self, _state: &mut StateReader<'_>) -> Result<(), StateError> {
+        Ok(())
+    }
+}
+
+impl Default for Box<dyn Mapper> {
+    fn default() -> Self {
+        Box::new(mapper_000::Mapper000::new(0, 0))
+    }
+}
+
+impl core::fmt::Debug for dyn Mapper {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Mapper")
+    }
+}
@@ -0,0 +1,519 @@
+mod audio;
+
+use std::cell::Cell;
+
+use crate::nes::Mirror;
+use crate::nes::mappers::Mapper;
+use crate::nes::state::{SaveState, StateError, StateReader, StateWriter};
+use audio::FdsAudio;
+
+/// CPU cycles the drive takes to spin up and reach the start of the disk
+const HEAD_RETURN_DELAY: u32 = 50000;
+/// CPU cycles per byte, the disk spins at about 96.4 kbit/s
+const BYTE_DELAY: u32 = 150;
+/// CPU cycles the disk stays ejected when switching sides, the BIOS has to see it out of the drive
+const INSERT_DELAY: u32 = 1_800_000;
+
+/// Famicom Disk System RAM adapter, the BIOS in `prg_rom` and the 32K of RAM in the cartridge PRG-RAM
+pub struct Fds {
+    /// Raw sides, with gaps and CRCs, see `cartridge::fds`
+    disk_sides: Vec<Vec<u8>>,
+    disk_side: Option<usize>,
+    /// Side waiting to be inserted once `insert_delay` runs out
+    next_disk_side: Option<usize>,
+    insert_delay: u32,
+
+    irq_reload: u16,
+    irq_counter: u16,
+    irq_repeat: bool,
+    irq_enabled: bool,
+    timer_irq_pending: Cell<bool>,
+    disk_irq_pending: Cell<bool>,
+
+    disk_io_enabled: bool,
+    sound_io_enabled: bool,
+
+    motor_on: bool,
+    reset_transfer: bool,
+    read_mode: bool,
+    mirror: Mirror,
+    crc_control: bool,
+    previous_crc_control: bool,
+    disk_ready: bool,
+    disk_irq_enabled: bool,
+    external_connector: u8,
+
+    read_data: u8,
+    write_data: u8,
+    transfer_complete: Cell<bool>,
+
+    disk_position: usize,
+    delay: u32,
+    end_of_head: bool,
+    scanning_disk: bool,
+    gap_ended: bool,
+    crc_accumulator: u16,
+
+    audio: FdsAudio,
+}
+
+impl Fds {
+    pub fn with_disk_sides(disk_sides: Vec<Vec<u8>>) -> Self {
+        let mut fds = Self::new(0, 0);
+        fds.disk_side = if disk_sides.is_empty() { None } else { Some(0) };
+        fds.disk_sides = disk_sides;
+        fds
+    }
+
+    fn is_disk_inserted(&self) -> bool {
+        self.disk_side.is_some()
+    }
+
+    /// CRC-16 as the RAM adapter computes it, LSB first
+    fn update_crc(&mut self, data: u8) {
+        for bit in 0..8 {
+            let carry = self.crc_accumulator & 0x01 != 0;
+            self.crc_accumulator >>= 1;
+            if carry {
+                self.crc_accumulator ^= 0x8408;
+            }
+            if data & (1 << bit) != 0 {
+                self.crc_accumulator ^= 0x8000;
+            }
+        }
+    }
+
+    fn clock_timer_irq(&mut self) {
+        if !self.irq_enabled {
+            return;
+        }
+
+        if self.irq_counter == 0 {
+            self.timer_irq_pending.set(true);
+            self.irq_counter = self.irq_reload;
+            if !self.irq_repeat {
+                self.irq_enabled = false;
+            }
+        } else {
+            self.irq_counter -= 1;
+        }
+    }
+
+    /// Moves the disk under the head, one byte every `BYTE_DELAY` cycles
+    fn clock_drive(&mut self) {
+        if self.insert_delay > 0 {
+            self.insert_delay -= 1;
+            if self.insert_delay == 0 {
+                self.disk_side = self.next_disk_side;
+            }
+        }
+
+        let Some(side) = self.disk_side.filter(|_| self.motor_on) else {
+            self.end_of_head = true;
+            self.scanning_disk = false;
+            return;
+        };
+
+        if self.reset_transfer && !self.scanning_disk {
+            return;
+        }
+
+        if self.end_of_head {
+            self.delay = HEAD_RETURN_DELAY;
+            self.end_of_head = false;
+            self.disk_position = 0;
+            self.gap_ended = false;
+            return;
+        }
+
+        if self.delay > 0 {
+            self.delay -= 1;
+            return;
+        }
+
+        self.scanning_disk = true;
+        let mut irq = self.disk_irq_enabled;
+
+        if self.read_mode {
+            let data = self.disk_sides[side][self.disk_position];
+            if !self.previous_crc_control {
+                self.update_crc(data);
+            }
+
+            if !self.disk_ready {
+                self.gap_ended = false;
+                self.crc_accumulator = 0;
+            } else if data != 0 && !self.gap_ended {
+                // The 0x80 start mark ends the gap, it isn't passed on to the CPU
+                self.gap_ended = true;
+                irq = false;
+            }
+
+            if self.gap_ended {
+                self.transfer_complete.set(true);
+                self.read_data = data;
+                if irq {
+                    self.disk_irq_pending.set(true);
+                }
+            }
+        } else {
+            let mut data = 0x00;
+            if !self.crc_control {
+                self.transfer_complete.set(true);
+                data = self.write_data;
+                if irq {
+                    self.disk_irq_pending.set(true);
+                }
+            }
+
+            if !self.disk_ready {
+                data = 0x00;
+            }
+
+            if self.crc_control {
+                if !self.previous_crc_control {
+                    self.update_crc(0x00);
+                    self.update_crc(0x00);
+                }
+                data = (self.crc_accumulator & 0xFF) as u8;
+                self.crc_accumulator >>= 8;
+            } else {
+                self.update_crc(data);
+            }
+
+            self.disk_sides[side][self.disk_position] = data;
+            self.gap_ended = false;
+        }
+
+        self.previous_crc_control = self.crc_control;
+        self.disk_position += 1;
+
+        if self.disk_position >= self.disk_sides[side].len() {
+            self.motor_on = false;
+            if irq {
+                self.disk_irq_pending.set(true);
+            }
+        } else {
+            self.delay = BYTE_DELAY;
+        }
+    }
+
+    fn read_register(&self, addr: u16, read_only: bool) -> Option<u8> {
+        if (0x4040..=0x4092).contains(&addr) {
+            return self.sound_io_enabled.then(|| self.audio.cpu_read(addr));
+        }
+
+        if !self.disk_io_enabled {
+            return None;
+        }
+
+        match addr {
+            // Disk status
+            0x4030 => {
+                let mut data = 0x00;
+                if self.timer_irq_pending.get() { data |= 0x01; }
+                if self.transfer_complete.get() { data |= 0x02; }
+                if self.end_of_head { data |= 0x40; }
+
+                if !read_only {
+                    self.transfer_complete.set(false);
+                    self.timer_irq_pending.set(false);
+                    self.disk_irq_pending.set(false);
+                }
+                Some(data)
+            }
+            // Read data
+            0x4031 => {
+                if !read_only {
+                    self.transfer_complete.set(false);
+                    self.disk_irq_pending.set(false);
+                }
+                Some(self.read_data)
+            }
+            // Drive status: disk missing, not ready, write protected
+            0x4032 => {
+                let mut data = 0x40;
+                if !self.is_disk_inserted() { data |= 0x01; }
+                if !self.is_disk_inserted() || !self.scanning_disk { data |= 0x02; }
+                if !self.is_disk_inserted() { data |= 0x04; }
+                Some(data)
+            }
+            // External connector, bit 7 being the battery status
+            0x4033 => Some(0x80),
+            _ => None,
+        }
+    }
+
+    fn write_register(&mut self, addr: u16, data: u8) -> bool {
+        match addr {
+            0x4020 => self.irq_reload = (self.irq_reload & 0xFF00) | data as u16,
+            0x4021 => self.irq_reload = (self.irq_reload & 0x00FF) | (data as u16) << 8,
+            // Timer IRQ control
+            0x4022 => {
+                if self.disk_io_enabled {
+                    self.irq_repeat = data & 0x01 != 0;
+                    self.irq_enabled = data & 0x02 != 0;
+                    if self.irq_enabled {
+                        self.irq_counter = self.irq_reload;
+                    } else {
+                        self.timer_irq_pending.set(false);
+                    }
+                }
+            }
+            // Master I/O enable
+            0x4023 => {
+                self.disk_io_enabled = data & 0x01 != 0;
+                self.sound_io_enabled = data & 0x02 != 0;
+                if !self.disk_io_enabled {
+                    self.irq_enabled = false;
+                    self.timer_irq_pending.set(false);
+                    self.disk_irq_pending.set(false);
+                }
+            }
+            // Write data
+            0x4024 => {
+                if self.disk_io_enabled {
+                    self.write_data = data;
+                    self.transfer_complete.set(false);
+                    self.disk_irq_pending.set(false);
+                }
+            }
+            // Drive control
+            0x4025 => {
+                if self.disk_io_enabled {
+                    self.motor_on = data & 0x01 != 0;
+                    self.reset_transfer = data & 0x02 != 0;
+                    self.read_mode = data & 0x04 != 0;
+                    self.mirror = if data & 0x08 != 0 { Mirror::Horizontal } else { Mirror::Vertical };
+                    self.crc_control = data & 0x10 != 0;
+                    self.disk_ready = data & 0x40 != 0;
+                    self.disk_irq_enabled = data & 0x80 != 0;
+                    self.disk_irq_pending.set(false);
+                }
+            }
+            0x4026 => {
+                if self.disk_io_enabled {
+                    self.external_connector = data;
+                }
+            }
+            0x4040..=0x4092 => {
+                if self.sound_io_enabled {
+                    self.audio.cpu_write(addr, data);
+                }
+            }
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+impl Mapper for Fds {
+    fn new(_prg_banks_count: u8, _chr_banks_count: u8) -> Self {
+        Self {
+            disk_sides: Vec::new(),
+            disk_side: None,
+            next_disk_side: None,
+            insert_delay: 0,
+
+            irq_reload: 0,
+            irq_counter: 0,
+            irq_repeat: false,
+            irq_enabled: false,
+            timer_irq_pending: Cell::new(false),
+            disk_irq_pending: Cell::new(false),
+
+            disk_io_enabled: false,
+            sound_io_enabled: false,
+
+            motor_on: false,
+            reset_transfer: false,
+            read_mode: true,
+            mirror: Mirror::Vertical,
+            crc_control: false,
+            previous_crc_control: false,
+            disk_ready: false,
+            disk_irq_enabled: false,
+            external_connector: 0,
+
+            read_data: 0,
+            write_data: 0,
+            transfer_complete: Cell::new(false),
+
+            disk_position: 0,
+            delay: 0,
+            end_of_head: true,
+            scanning_disk: false,
+            gap_ended: false,
+            crc_accumulator: 0,
+
+            audio: FdsAudio::new(),
+        }
+    }
+
+    fn cpu_map_read(&self, addr: u16, read_only: bool, mapped_addr: &mut u32, data: &mut u8) -> bool {
+        // BIOS
+        if addr >= 0xE000 {
+            *mapped_addr = (addr & 0x1FFF) as u32;
+            return true;
+        }
+
+        if let Some(value) = self.read_register(addr, read_only) {
+            *mapped_addr = 0xFFFFFFFF;
+            *data = value;
+            return true;
+        }
+
+        false
+    }
+
+    fn cpu_map_write(&mut self, addr: u16, mapped_addr: &mut u32, data: u8) -> bool {
+        if addr >= 0xE000 || self.write_register(addr, data) {
+            *mapped_addr = 0xFFFFFFFF;
+            return true;
+        }
+
+        false
+    }
+
+    fn ppu_map_read(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        if addr <= 0x1FFF {
+            *mapped_addr = addr as u32;
+            return true;
+        }
+
+        false
+    }
+
+    fn ppu_map_write(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        self.ppu_map_read(addr, mapped_addr)
+    }
+
+    /// The RAM adapter has 32K of RAM at $6000-$DFFF
+    fn prg_ram_map_read(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        if (0x6000..=0xDFFF).contains(&addr) {
+            *mapped_addr = (addr - 0x6000) as u32;
+            return true;
+        }
+
+        false
+    }
+
+    fn reset(&mut self) {
+        let disk_sides = std::mem::take(&mut self.disk_sides);
+        let disk_side = self.disk_side;
+
+        *self = Self::new(0, 0);
+        self.disk_sides = disk_sides;
+        self.disk_side = disk_side;
+    }
+
+    fn cpu_tick(&mut self) {
+        self.clock_timer_irq();
+        self.clock_drive();
+        self.audio.tick();
+    }
+
+    fn mirror(&self) -> Mirror {
+        self.mirror
+    }
+
+    fn is_irq_asserted(&self) -> bool {
+        self.timer_irq_pending.get() || self.disk_irq_pending.get()
+    }
+
+    fn audio_output(&self) -> f32 {
+        self.audio.mixer_output()
+    }
+
+    fn disk_sides_count(&self) -> usize {
+        self.disk_sides.len()
+    }
+
+    fn disk_side(&self) -> Option<usize> {
+        if self.insert_delay > 0 { self.next_disk_side } else { self.disk_side }
+    }
+
+    fn insert_disk_side(&mut self, side: Option<usize>) {
+        self.disk_side = None;
+        self.next_disk_side = side.filter(|side| *side < self.disk_sides.len());
+        self.insert_delay = INSERT_DELAY;
+    }
+
+    /// The disks are saved too since the BIOS writes to them
+    fn save_state(&self, state: &mut StateWriter) {
+        for side in &self.disk_sides {
+            state.write_bytes(side);
+        }
+
+        state.write(&self.disk_side);
+        state.write(&self.next_disk_side);
+        state.write(&self.insert_delay);
+        state.write(&self.irq_reload);
+        state.write(&self.irq_counter);
+        state.write(&self.irq_repeat);
+        state.write(&self.irq_enabled);
+        state.write(&self.timer_irq_pending);
+        state.write(&self.disk_irq_pending);
+        state.write(&self.disk_io_enabled);
+        state.write(&self.sound_io_enabled);
+        state.write(&self.motor_on);
+        state.write(&self.reset_transfer);
+        state.write(&self.read_mode);
+        state.write(&self.mirror);
+        state.write(&self.crc_control);
+        state.write(&self.previous_crc_control);
+        state.write(&self.disk_ready);
+        state.write(&self.disk_irq_enabled);
+        state.write(&self.external_connector);
+        state.write(&self.read_data);
+        state.write(&self.write_data);
+        state.write(&self.transfer_complete);
+        state.write(&self.disk_position);
+        state.write(&self.delay);
+        state.write(&self.end_of_head);
+        state.write(&self.scanning_disk);
+        state.write(&self.gap_ended);
+        state.write(&self.crc_accumulator);
+
+        self.audio.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        for side in &mut self.disk_sides {
+            state.read_bytes_into(side)?;
+        }
+
+        self.disk_side = state.read()?;
+        self.next_disk_side = state.read()?;
+        self.insert_delay = state.read()?;
+        self.irq_reload = state.read()?;
+        self.irq_counter = state.read()?;
+        self.irq_repeat = state.read()?;
+        self.irq_enabled = state.read()?;
+        self.timer_irq_pending = state.read()?;
+        self.disk_irq_pending = state.read()?;
+        self.disk_io_enabled = state.read()?;
+        self.sound_io_enabled = state.read()?;
+        self.motor_on = state.read()?;
+        self.reset_transfer = state.read()?;
+        self.read_mode = state.read()?;
+        self.mirror = state.read()?;
+        self.crc_control = state.read()?;
+        self.previous_crc_control = state.read()?;
+        self.disk_ready = state.read()?;
+        self.disk_irq_enabled = state.read()?;
+        self.external_connector = state.read()?;
+        self.read_data = state.read()?;
+        self.write_data = state.read()?;
+        self.transfer_complete = state.read()?;
+        self.disk_position = state.read()?;
+        self.delay = state.read()?;
+        self.end_of_head = state.read()?;
+        self.scanning_disk = state.read()?;
+        self.gap_ended = state.read()?;
+        self.crc_accumulator = state.read()?;
+
+        self.audio.load_state(state)
+    }
+}
@@ -0,0 +1,354 @@
+#![allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+
+use crate::nes::state::{SaveState, StateError, StateReader, StateWriter};
+
+/// Master volume ($4089) as a multiplier of the gain, out of 1152
+const MASTER_VOLUMES: [u32; 4] = [36, 24, 17, 14];
+/// Modulation table entries, `None` resets the counter
+const MOD_ADJUSTMENTS: [Option<i8>; 8] = [Some(0), Some(1), Some(2), Some(4), None, Some(-4), Some(-2), Some(-1)];
+/// Used to scale the output: at full volume the channel is about 2.4 times as loud as a 2A03 pulse at full volume
+const FULL_SCALE: f32 = 2.4 * 0.1494;
+/// The RC filter on the output has a cutoff around 2 kHz
+const LOW_PASS_FACTOR: f32 = 0.0068;
+
+/// Volume or modulation envelope, both work the same way
+#[derive(Debug)]
+struct Envelope {
+    speed: u8,
+    gain: u8,
+    increase: bool,
+    disabled: bool,
+    timer: u32,
+}
+
+impl Envelope {
+    const fn new() -> Self {
+        Self {
+            speed: 0,
+            gain: 0,
+            increase: false,
+            disabled: true,
+            timer: 0,
+        }
+    }
+
+    fn write(&mut self, data: u8, master_speed: u8) {
+        self.speed = data & 0x3F;
+        self.increase = data & 0x40 != 0;
+        self.disabled = data & 0x80 != 0;
+        self.reset_timer(master_speed);
+
+        // The speed is used as the gain directly when the envelope is off
+        if self.disabled {
+            self.gain = self.speed;
+        }
+    }
+
+    fn reset_timer(&mut self, master_speed: u8) {
+        self.timer = 8 * (self.speed as u32 + 1) * master_speed as u32;
+    }
+
+    /// Returns whether the gain changed
+    fn clock(&mut self, master_speed: u8) -> bool {
+        if self.disabled || master_speed == 0 {
+            return false;
+        }
+
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer > 0 {
+            return false;
+        }
+
+        self.reset_timer(master_speed);
+        if self.increase && self.gain < 32 {
+            self.gain += 1;
+        } else if !self.increase && self.gain > 0 {
+            self.gain -= 1;
+        }
+
+        true
+    }
+}
+
+/// The 2C33 wavetable channel, with its frequency modulation unit
+#[derive(Debug)]
+pub struct FdsAudio {
+    wave_table: [u8; 64],
+    wave_write_enabled: bool,
+    wave_position: u8,
+    wave_accumulator: u32,
+    /// 12-bit
+    wave_frequency: u16,
+    wave_halted: bool,
+    volume: Envelope,
+    master_volume: u8,
+
+    mod_table: [u8; 64],
+    mod_position: u8,
+    mod_accumulator: u16,
+    /// 12-bit
+    mod_frequency: u16,
+    mod_halted: bool,
+    /// 7-bit signed
+    mod_counter: i8,
+    /// Pitch offset applied to the wave frequency
+    mod_output: i32,
+    modulation: Envelope,
+
+    envelopes_halted: bool,
+    master_envelope_speed: u8,
+
+    output: f32,
+}
+
+impl FdsAudio {
+    pub const fn new() -> Self {
+        Self {
+            wave_table: [0; 64],
+            wave_write_enabled: false,
+            wave_position: 0,
+            wave_accumulator: 0,
+            wave_frequency: 0,
+            wave_halted: true,
+            volume: Envelope::new(),
+            master_volume: 0,
+
+            mod_table: [0; 64],
+            mod_position: 0,
+            mod_accumulator: 0,
+            mod_frequency: 0,
+            mod_halted: true,
+            mod_counter: 0,
+            mod_output: 0,
+            modulation: Envelope::new(),
+
+            envelopes_halted: false,
+            master_envelope_speed: 0xE8,
+
+            output: 0.0,
+        }
+    }
+
+    pub fn cpu_read(&self, addr: u16) -> u8 {
+        match addr {
+            // The table reads back the current sample while it's playing
+            0x4040..=0x407F if self.wave_write_enabled => self.wave_table[(addr & 0x3F) as usize],
+            0x4040..=0x407F => self.wave_table[self.wave_position as usize],
+            0x4090 => self.volume.gain | 0x40,
+            0x4092 => self.modulation.gain | 0x40,
+            _ => 0x00,
+        }
+    }
+
+    pub fn cpu_write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x4040..=0x407F if self.wave_write_enabled => self.wave_table[(addr & 0x3F) as usize] = data & 0x3F,
+            // Volume envelope
+            0x4080 => self.volume.write(data, self.master_envelope_speed),
+            // Frequency low
+            0x4082 => self.wave_frequency = (self.wave_frequency & 0x0F00) | data as u16,
+            // Frequency high
+            0x4083 => {
+                self.wave_frequency = (self.wave_frequency & 0x00FF) | ((data as u16 & 0x0F) << 8);
+                self.envelopes_halted = data & 0x40 != 0;
+                self.wave_halted = data & 0x80 != 0;
+
+                if self.wave_halted {
+                    self.wave_position = 0;
+                    self.wave_accumulator = 0;
+                }
+                if self.envelopes_halted {
+                    self.volume.reset_timer(self.master_envelope_speed);
+                    self.modulation.reset_timer(self.master_envelope_speed);
+                }
+            }
+            // Modulation envelope
+            0x4084 => {
+                self.modulation.write(data, self.master_envelope_speed);
+                self.update_mod_output();
+            }
+            // Modulation counter
+            0x4085 => {
+                self.set_mod_counter((data & 0x7F) as i32);
+                self.update_mod_output();
+            }
+            // Modulation frequency low
+            0x4086 => self.mod_frequency = (self.mod_frequency & 0x0F00) | data as u16,
+            // Modulation frequency high
+            0x4087 => {
+                self.mod_frequency = (self.mod_frequency & 0x00FF) | ((data as u16 & 0x0F) << 8);
+                self.mod_halted = data & 0x80 != 0;
+                if self.mod_halted {
+                    self.mod_accumulator = 0;
+                }
+            }
+            // Modulation table, only writable while modulation is halted. Each write fills two entries
+            0x4088 if self.mod_halted => {
+                self.mod_table[self.mod_position as usize] = data & 0x07;
+                self.mod_table[(self.mod_position as usize + 1) & 0x3F] = data & 0x07;
+                self.mod_position = (self.mod_position + 2) & 0x3F;
+            }
+            // Master volume, and wave table write enable
+            0x4089 => {
+                self.master_volume = data & 0x03;
+                self.wave_write_enabled = data & 0x80 != 0;
+            }
+            // Envelope speed
+            0x408A => self.master_envelope_speed = data,
+            _ => {}
+        }
+    }
+
+    /// Wraps to the 7-bit signed counter
+    fn set_mod_counter(&mut self, value: i32) {
+        self.mod_counter = (((value + 64) & 0x7F) - 64) as i8;
+    }
+
+    /// Pitch offset from the modulation counter and gain, with the hardware's rounding
+    fn update_mod_output(&mut self) {
+        let mut temp = self.mod_counter as i32 * self.modulation.gain as i32;
+        let remainder = temp & 0x0F;
+        temp >>= 4;
+        if remainder > 0 && temp & 0x80 == 0 {
+            temp += if self.mod_counter < 0 { -1 } else { 2 };
+        }
+
+        if temp >= 192 {
+            temp -= 256;
+        } else if temp < -64 {
+            temp += 256;
+        }
+
+        temp *= self.wave_frequency as i32;
+        let remainder = temp & 0x3F;
+        temp >>= 6;
+        if remainder >= 32 {
+            temp += 1;
+        }
+
+        self.mod_output = temp;
+    }
+
+    fn clock_modulator(&mut self) {
+        if self.mod_halted || self.mod_frequency == 0 {
+            return;
+        }
+
+        let (accumulator, overflow) = self.mod_accumulator.overflowing_add(self.mod_frequency);
+        self.mod_accumulator = accumulator;
+        if overflow {
+            let counter = match MOD_ADJUSTMENTS[self.mod_table[self.mod_position as usize] as usize] {
+                Some(adjustment) => self.mod_counter as i32 + adjustment as i32,
+                None => 0,
+            };
+            self.set_mod_counter(counter);
+            self.mod_position = (self.mod_position + 1) & 0x3F;
+            self.update_mod_output();
+        }
+    }
+
+    /// Called once per CPU cycle
+    pub fn tick(&mut self) {
+        if !self.wave_halted && !self.envelopes_halted {
+            self.volume.clock(self.master_envelope_speed);
+            if self.modulation.clock(self.master_envelope_speed) {
+                self.update_mod_output();
+            }
+        }
+
+        self.clock_modulator();
+
+        // The channel holds its output while the table is being written
+        if !self.wave_halted && !self.wave_write_enabled {
+            let frequency = self.wave_frequency as i32 + self.mod_output;
+            if frequency > 0 {
+                self.wave_accumulator += frequency as u32;
+                if self.wave_accumulator > 0xFFFF {
+                    self.wave_accumulator -= 0x10000;
+                    self.wave_position = (self.wave_position + 1) & 0x3F;
+                }
+            }
+        }
+
+        let level = if self.wave_write_enabled {
+            self.output
+        } else {
+            let gain = self.volume.gain.min(32) as u32 * MASTER_VOLUMES[self.master_volume as usize];
+            (self.wave_table[self.wave_position as usize] as u32 * gain / 1152) as f32
+        };
+        self.output += (level - self.output) * LOW_PASS_FACTOR;
+    }
+
+    /// On the scale of the APU mixer output, `output` being between 0.0 and 63.0
+    pub fn mixer_output(&self) -> f32 {
+        self.output / 63.0 * FULL_SCALE
+    }
+}
+
+impl SaveState for Envelope {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.speed);
+        state.write(&self.gain);
+        state.write(&self.increase);
+        state.write(&self.disabled);
+        state.write(&self.timer);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.speed = state.read()?;
+        self.gain = state.read()?;
+        self.increase = state.read()?;
+        self.disabled = state.read()?;
+        self.timer = state.read()?;
+
+        Ok(())
+    }
+}
+
+impl SaveState for FdsAudio {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.wave_table);
+        state.write(&self.wave_write_enabled);
+        state.write(&self.wave_position);
+        state.write(&self.wave_accumulator);
+        state.write(&self.wave_frequency);
+        state.write(&self.wave_halted);
+        self.volume.save_state(state);
+        state.write(&self.master_volume);
+        state.write(&self.mod_table);
+        state.write(&self.mod_position);
+        state.write(&self.mod_accumulator);
+        state.write(&self.mod_frequency);
+        state.write(&self.mod_halted);
+        state.write(&self.mod_counter);
+        state.write(&self.mod_output);
+        self.modulation.save_state(state);
+        state.write(&self.envelopes_halted);
+        state.write(&self.master_envelope_speed);
+        state.write(&self.output);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.wave_table = state.read()?;
+        self.wave_write_enabled = state.read()?;
+        self.wave_position = state.read()?;
+        self.wave_accumulator = state.read()?;
+        self.wave_frequency = state.read()?;
+        self.wave_halted = state.read()?;
+        self.volume.load_state(state)?;
+        self.master_volume = state.read()?;
+        self.mod_table = state.read()?;
+        self.mod_position = state.read()?;
+        self.mod_accumulator = state.read()?;
+        self.mod_frequency = state.read()?;
+        self.mod_halted = state.read()?;
+        self.mod_counter = state.read()?;
+        self.mod_output = state.read()?;
+        self.modulation.load_state(state)?;
+        self.envelopes_halted = state.read()?;
+        self.master_envelope_speed = state.read()?;
+        self.output = state.read()?;
+
+        Ok(())
+    }
+}
@@ -13,7 +13,7 @@ impl Mapper for Mapper000 {
         }
     }
 
-    fn cpu_map_read(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+    fn cpu_map_read(&self, addr: u16, _read_only: bool, mapped_addr: &mut u32, _data: &mut u8) -> bool {
         if addr >= 0x8000 {
             *mapped_addr = if self.prg_banks_count > 1 { (addr & 0x7FFF) as u32 } else { (addr & 0x3FFF) as u32 };
             return true;
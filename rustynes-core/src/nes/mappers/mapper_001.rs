@@ -0,0 +1,191 @@
+use crate::nes::Mirror;
+use crate::nes::mappers::Mapper;
+use crate::nes::state::{StateError, StateReader, StateWriter};
+
+/// MMC1
+pub struct Mapper001 {
+    prg_banks_count: u8,
+    chr_banks_count: u8,
+
+    /// Registers are written one bit at a time, the 5th write commits the value
+    shift_register: u8,
+    shift_count: u8,
+
+    control: u8,
+    chr_bank_0: u8,
+    chr_bank_1: u8,
+    prg_bank: u8,
+}
+
+impl Mapper001 {
+    /// 256K PRG outer bank, only used by 512K boards (SUROM) through the CHR registers
+    const fn prg_outer_bank(&self) -> usize {
+        if self.prg_banks_count > 16 {
+            (self.chr_bank_0 & 0x10) as usize
+        } else {
+            0
+        }
+    }
+
+    const fn is_prg_ram_enabled(&self) -> bool {
+        self.prg_bank & 0x10 == 0
+    }
+
+    fn write_register(&mut self, addr: u16, data: u8) {
+        match addr {
+            // Control
+            0x8000..=0x9FFF => self.control = data,
+            // CHR bank 0
+            0xA000..=0xBFFF => self.chr_bank_0 = data,
+            // CHR bank 1
+            0xC000..=0xDFFF => self.chr_bank_1 = data,
+            // PRG bank
+            0xE000..=0xFFFF => self.prg_bank = data,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Mapper for Mapper001 {
+    fn new(prg_banks_count: u8, chr_banks_count: u8) -> Self {
+        Self {
+            prg_banks_count,
+            chr_banks_count,
+
+            shift_register: 0,
+            shift_count: 0,
+
+            control: 0x0C,
+            chr_bank_0: 0,
+            chr_bank_1: 0,
+            prg_bank: 0,
+        }
+    }
+
+    fn cpu_map_read(&self, addr: u16, _read_only: bool, mapped_addr: &mut u32, _data: &mut u8) -> bool {
+        // PRG ROM
+        if addr >= 0x8000 {
+            let bank = match (self.control >> 2) & 0x03 {
+                // 32K mode, the low bit of the bank number is ignored
+                0 | 1 => (self.prg_bank & 0x0E) as usize + ((addr >= 0xC000) as usize),
+                // First bank fixed at $8000, switchable bank at $C000
+                2 => if addr < 0xC000 { 0 } else { (self.prg_bank & 0x0F) as usize },
+                // Switchable bank at $8000, last bank fixed at $C000
+                3 => if addr < 0xC000 { (self.prg_bank & 0x0F) as usize } else { 0x0F },
+                _ => unreachable!(),
+            } | self.prg_outer_bank();
+
+            let bank = bank % self.prg_banks_count.max(1) as usize;
+            *mapped_addr = (bank * 0x4000 + (addr & 0x3FFF) as usize) as u32;
+            return true;
+        }
+
+        false
+    }
+
+    fn cpu_map_write(&mut self, addr: u16, mapped_addr: &mut u32, data: u8) -> bool {
+        // Load register
+        if addr >= 0x8000 {
+            if data & 0x80 != 0 {
+                self.shift_register = 0;
+                self.shift_count = 0;
+                self.control |= 0x0C;
+            } else {
+                self.shift_register |= (data & 0x01) << self.shift_count;
+                self.shift_count += 1;
+
+                if self.shift_count == 5 {
+                    self.write_register(addr, self.shift_register);
+                    self.shift_register = 0;
+                    self.shift_count = 0;
+                }
+            }
+
+            *mapped_addr = 0xFFFFFFFF;
+            return true;
+        }
+
+        false
+    }
+
+    fn ppu_map_read(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        if addr > 0x1FFF {
+            return false;
+        }
+
+        let bank = if self.control & 0x10 == 0 {
+            // 8K mode, the low bit of the bank number is ignored
+            (self.chr_bank_0 & 0x1E) as usize + ((addr >= 0x1000) as usize)
+        } else if addr < 0x1000 {
+            self.chr_bank_0 as usize
+        } else {
+            self.chr_bank_1 as usize
+        };
+
+        // Banks are 4K, and CHR RAM boards only have 8K
+        let bank = bank % (self.chr_banks_count.max(1) as usize * 2);
+        *mapped_addr = (bank * 0x1000 + (addr & 0x0FFF) as usize) as u32;
+
+        true
+    }
+
+    fn ppu_map_write(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        if self.chr_banks_count == 0 {
+            return self.ppu_map_read(addr, mapped_addr);
+        }
+
+        false
+    }
+
+    fn prg_ram_map_read(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        if (0x6000..=0x7FFF).contains(&addr) && self.is_prg_ram_enabled() {
+            *mapped_addr = (addr & 0x1FFF) as u32;
+            return true;
+        }
+
+        false
+    }
+
+    fn prg_ram_map_write(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        self.prg_ram_map_read(addr, mapped_addr)
+    }
+
+    fn reset(&mut self) {
+        self.shift_register = 0;
+        self.shift_count = 0;
+        self.control = 0x0C;
+        self.chr_bank_0 = 0;
+        self.chr_bank_1 = 0;
+        self.prg_bank = 0;
+    }
+
+    fn mirror(&self) -> Mirror {
+        match self.control & 0x03 {
+            0 => Mirror::OneScreenLo,
+            1 => Mirror::OneScreenHi,
+            2 => Mirror::Vertical,
+            3 => Mirror::Horizontal,
+            _ => unreachable!(),
+        }
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.shift_register);
+        state.write(&self.shift_count);
+        state.write(&self.control);
+        state.write(&self.chr_bank_0);
+        state.write(&self.chr_bank_1);
+        state.write(&self.prg_bank);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.shift_register = state.read()?;
+        self.shift_count = state.read()?;
+        self.control = state.read()?;
+        self.chr_bank_0 = state.read()?;
+        self.chr_bank_1 = state.read()?;
+        self.prg_bank = state.read()?;
+
+        Ok(())
+    }
+}
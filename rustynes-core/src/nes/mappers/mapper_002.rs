@@ -1,4 +1,5 @@
 use crate::nes::mappers::Mapper;
+use crate::nes::state::{StateError, StateReader, StateWriter};
 
 pub struct Mapper002 {
     prg_banks_count: u8,
@@ -24,7 +25,7 @@ impl Mapper for Mapper002 {
         }
     }
     
-    fn cpu_map_read(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+    fn cpu_map_read(&self, addr: u16, _read_only: bool, mapped_addr: &mut u32, _data: &mut u8) -> bool {
         match addr {
             // 0x8000..=0xBFFF => {
             //     *mapped_addr = (self.prg_bank_select_low as u16).wrapping_mul(0x4000).wrapping_add(addr & 0x3FFF) as u32;
@@ -91,4 +92,14 @@ impl Mapper for Mapper002 {
 
         self.prg_top_bank = 0;
     }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.prg_top_bank);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.prg_top_bank = state.read()?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,75 @@
+use crate::nes::mappers::Mapper;
+use crate::nes::state::{StateError, StateReader, StateWriter};
+
+/// CNROM
+pub struct Mapper003 {
+    prg_banks_count: u8,
+    chr_banks_count: u8,
+
+    chr_bank: u8,
+}
+
+impl Mapper for Mapper003 {
+    fn new(prg_banks_count: u8, chr_banks_count: u8) -> Self {
+        Self {
+            prg_banks_count,
+            chr_banks_count,
+
+            chr_bank: 0,
+        }
+    }
+
+    fn cpu_map_read(&self, addr: u16, _read_only: bool, mapped_addr: &mut u32, _data: &mut u8) -> bool {
+        // Same layout as NROM, 16K boards are mirrored
+        if addr >= 0x8000 {
+            *mapped_addr = if self.prg_banks_count > 1 { (addr & 0x7FFF) as u32 } else { (addr & 0x3FFF) as u32 };
+            return true;
+        }
+
+        false
+    }
+
+    fn cpu_map_write(&mut self, addr: u16, mapped_addr: &mut u32, data: u8) -> bool {
+        // CHR bank select
+        if addr >= 0x8000 {
+            self.chr_bank = data;
+            *mapped_addr = 0xFFFFFFFF;
+            return true;
+        }
+
+        false
+    }
+
+    fn ppu_map_read(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        if addr <= 0x1FFF {
+            let bank = self.chr_bank as usize % self.chr_banks_count.max(1) as usize;
+            *mapped_addr = (bank * 0x2000 + addr as usize) as u32;
+            return true;
+        }
+
+        false
+    }
+
+    fn ppu_map_write(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        if addr <= 0x1FFF && self.chr_banks_count == 0 {
+            *mapped_addr = addr as u32;
+            return true;
+        }
+
+        false
+    }
+
+    fn reset(&mut self) {
+        self.chr_bank = 0;
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.chr_bank);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.chr_bank = state.read()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+use crate::nes::Mirror;
+use crate::nes::mappers::Mapper;
+use crate::nes::state::{StateError, StateReader, StateWriter};
+
+/// PPU cycles A12 has to stay low before a rising edge clocks the IRQ counter,
+/// the MMC3 actually filters on a few CPU cycles
+const A12_LOW_FILTER: u8 = 10;
+
+/// MMC3
+pub struct Mapper004 {
+    prg_banks_count: u8,
+    chr_banks_count: u8,
+
+    /// R0-R7, selected by the low bits of `bank_select`
+    bank_registers: [u8; 8],
+    bank_select: u8,
+    mirror: Mirror,
+
+    prg_ram_enabled: bool,
+    prg_ram_write_protected: bool,
+
+    irq_latch: u8,
+    irq_counter: u8,
+    irq_reload: bool,
+    irq_enabled: bool,
+    irq_pending: bool,
+    /// PPU cycles since A12 was last seen high
+    a12_low_cycles: u8,
+}
+
+impl Mapper004 {
+    const fn prg_banks_8k(&self) -> usize {
+        self.prg_banks_count as usize * 2
+    }
+
+    /// 1K banks, CHR RAM boards only have 8K
+    const fn chr_banks_1k(&self) -> usize {
+        if self.chr_banks_count == 0 { 8 } else { self.chr_banks_count as usize * 8 }
+    }
+
+    fn chr_bank(&self, addr: u16) -> usize {
+        // CHR inversion swaps the 2K and 1K halves
+        let addr = if self.bank_select & 0x80 != 0 { addr ^ 0x1000 } else { addr };
+
+        let bank = match addr {
+            0x0000..=0x07FF => (self.bank_registers[0] & 0xFE) as usize + ((addr >> 10) & 0x01) as usize,
+            0x0800..=0x0FFF => (self.bank_registers[1] & 0xFE) as usize + ((addr >> 10) & 0x01) as usize,
+            0x1000..=0x13FF => self.bank_registers[2] as usize,
+            0x1400..=0x17FF => self.bank_registers[3] as usize,
+            0x1800..=0x1BFF => self.bank_registers[4] as usize,
+            0x1C00..=0x1FFF => self.bank_registers[5] as usize,
+            _ => unreachable!(),
+        };
+
+        bank % self.chr_banks_1k()
+    }
+
+    fn clock_irq_counter(&mut self) {
+        if self.irq_counter == 0 || self.irq_reload {
+            self.irq_counter = self.irq_latch;
+            self.irq_reload = false;
+        } else {
+            self.irq_counter -= 1;
+        }
+
+        if self.irq_counter == 0 && self.irq_enabled {
+            self.irq_pending = true;
+        }
+    }
+}
+
+impl Mapper for Mapper004 {
+    fn new(prg_banks_count: u8, chr_banks_count: u8) -> Self {
+        Self {
+            prg_banks_count,
+            chr_banks_count,
+
+            bank_registers: [0, 2, 4, 5, 6, 7, 0, 1],
+            bank_select: 0,
+            mirror: Mirror::Hardware,
+
+            prg_ram_enabled: true,
+            prg_ram_write_protected: false,
+
+            irq_latch: 0,
+            irq_counter: 0,
+            irq_reload: false,
+            irq_enabled: false,
+            irq_pending: false,
+            a12_low_cycles: 0,
+        }
+    }
+
+    fn cpu_map_read(&self, addr: u16, _read_only: bool, mapped_addr: &mut u32, _data: &mut u8) -> bool {
+        // PRG ROM
+        if addr >= 0x8000 {
+            let second_last = self.prg_banks_8k().saturating_sub(2);
+            let prg_mode = self.bank_select & 0x40 != 0;
+
+            let bank = match (addr, prg_mode) {
+                (0x8000..=0x9FFF, false) | (0xC000..=0xDFFF, true) => (self.bank_registers[6] & 0x3F) as usize,
+                (0x8000..=0x9FFF, true) | (0xC000..=0xDFFF, false) => second_last,
+                (0xA000..=0xBFFF, _) => (self.bank_registers[7] & 0x3F) as usize,
+                (0xE000..=0xFFFF, _) => self.prg_banks_8k().saturating_sub(1),
+                _ => unreachable!(),
+            } % self.prg_banks_8k().max(1);
+
+            *mapped_addr = (bank * 0x2000 + (addr & 0x1FFF) as usize) as u32;
+            return true;
+        }
+
+        false
+    }
+
+    fn cpu_map_write(&mut self, addr: u16, mapped_addr: &mut u32, data: u8) -> bool {
+        match (addr, addr & 0x0001) {
+            // Bank select
+            (0x8000..=0x9FFF, 0) => self.bank_select = data,
+            // Bank data
+            (0x8000..=0x9FFF, _) => self.bank_registers[(self.bank_select & 0x07) as usize] = data,
+            // Mirroring
+            (0xA000..=0xBFFF, 0) => self.mirror = if data & 0x01 == 0 { Mirror::Vertical } else { Mirror::Horizontal },
+            // PRG RAM protect
+            (0xA000..=0xBFFF, _) => {
+                self.prg_ram_enabled = data & 0x80 != 0;
+                self.prg_ram_write_protected = data & 0x40 != 0;
+            }
+            // IRQ latch
+            (0xC000..=0xDFFF, 0) => self.irq_latch = data,
+            // IRQ reload
+            (0xC000..=0xDFFF, _) => {
+                self.irq_counter = 0;
+                self.irq_reload = true;
+            }
+            // IRQ disable, also acknowledges any pending interrupt
+            (0xE000..=0xFFFF, 0) => {
+                self.irq_enabled = false;
+                self.irq_pending = false;
+            }
+            // IRQ enable
+            (0xE000..=0xFFFF, _) => self.irq_enabled = true,
+            _ => return false,
+        }
+
+        *mapped_addr = 0xFFFFFFFF;
+        true
+    }
+
+    fn ppu_map_read(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        if addr <= 0x1FFF {
+            *mapped_addr = (self.chr_bank(addr) * 0x0400 + (addr & 0x03FF) as usize) as u32;
+            return true;
+        }
+
+        false
+    }
+
+    fn ppu_map_write(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        if self.chr_banks_count == 0 {
+            return self.ppu_map_read(addr, mapped_addr);
+        }
+
+        false
+    }
+
+    fn prg_ram_map_read(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        if (0x6000..=0x7FFF).contains(&addr) && self.prg_ram_enabled {
+            *mapped_addr = (addr & 0x1FFF) as u32;
+            return true;
+        }
+
+        false
+    }
+
+    fn prg_ram_map_write(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        !self.prg_ram_write_protected && self.prg_ram_map_read(addr, mapped_addr)
+    }
+
+    fn reset(&mut self) {
+        self.bank_registers = [0, 2, 4, 5, 6, 7, 0, 1];
+        self.bank_select = 0;
+        self.mirror = Mirror::Hardware;
+
+        self.prg_ram_enabled = true;
+        self.prg_ram_write_protected = false;
+
+        self.irq_latch = 0;
+        self.irq_counter = 0;
+        self.irq_reload = false;
+        self.irq_enabled = false;
+        self.irq_pending = false;
+        self.a12_low_cycles = 0;
+    }
+
+    fn ppu_tick(&mut self, addr: u16) {
+        if addr & 0x1000 == 0 {
+            self.a12_low_cycles = self.a12_low_cycles.saturating_add(1);
+            return;
+        }
+
+        if self.a12_low_cycles >= A12_LOW_FILTER {
+            self.clock_irq_counter();
+        }
+        self.a12_low_cycles = 0;
+    }
+
+    fn mirror(&self) -> Mirror {
+        self.mirror
+    }
+
+    fn is_irq_asserted(&self) -> bool {
+        self.irq_pending
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.bank_registers);
+        state.write(&self.bank_select);
+        state.write(&self.mirror);
+        state.write(&self.prg_ram_enabled);
+        state.write(&self.prg_ram_write_protected);
+        state.write(&self.irq_latch);
+        state.write(&self.irq_counter);
+        state.write(&self.irq_reload);
+        state.write(&self.irq_enabled);
+        state.write(&self.irq_pending);
+        state.write(&self.a12_low_cycles);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.bank_registers = state.read()?;
+        self.bank_select = state.read()?;
+        self.mirror = state.read()?;
+        self.prg_ram_enabled = state.read()?;
+        self.prg_ram_write_protected = state.read()?;
+        self.irq_latch = state.read()?;
+        self.irq_counter = state.read()?;
+        self.irq_reload = state.read()?;
+        self.irq_enabled = state.read()?;
+        self.irq_pending = state.read()?;
+        self.a12_low_cycles = state.read()?;
+
+        Ok(())
+    }
+}
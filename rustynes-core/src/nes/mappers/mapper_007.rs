@@ -0,0 +1,87 @@
+use crate::nes::Mirror;
+use crate::nes::mappers::Mapper;
+use crate::nes::state::{StateError, StateReader, StateWriter};
+
+/// AxROM
+pub struct Mapper007 {
+    prg_banks_count: u8,
+    chr_banks_count: u8,
+
+    /// 32K bank
+    prg_bank: u8,
+    mirror: Mirror,
+}
+
+impl Mapper for Mapper007 {
+    fn new(prg_banks_count: u8, chr_banks_count: u8) -> Self {
+        Self {
+            prg_banks_count,
+            chr_banks_count,
+
+            prg_bank: 0,
+            mirror: Mirror::OneScreenLo,
+        }
+    }
+
+    fn cpu_map_read(&self, addr: u16, _read_only: bool, mapped_addr: &mut u32, _data: &mut u8) -> bool {
+        if addr >= 0x8000 {
+            let bank = self.prg_bank as usize % (self.prg_banks_count as usize / 2).max(1);
+            let mask = if self.prg_banks_count > 1 { 0x7FFF } else { 0x3FFF };
+            *mapped_addr = (bank * 0x8000 + (addr & mask) as usize) as u32;
+            return true;
+        }
+
+        false
+    }
+
+    fn cpu_map_write(&mut self, addr: u16, mapped_addr: &mut u32, data: u8) -> bool {
+        // PRG bank select, bit 4 selects the nametable
+        if addr >= 0x8000 {
+            self.prg_bank = data & 0x07;
+            self.mirror = if data & 0x10 == 0 { Mirror::OneScreenLo } else { Mirror::OneScreenHi };
+            *mapped_addr = 0xFFFFFFFF;
+            return true;
+        }
+
+        false
+    }
+
+    fn ppu_map_read(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        if addr <= 0x1FFF {
+            *mapped_addr = addr as u32;
+            return true;
+        }
+
+        false
+    }
+
+    fn ppu_map_write(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        if addr <= 0x1FFF && self.chr_banks_count == 0 {
+            *mapped_addr = addr as u32;
+            return true;
+        }
+
+        false
+    }
+
+    fn reset(&mut self) {
+        self.prg_bank = 0;
+        self.mirror = Mirror::OneScreenLo;
+    }
+
+    fn mirror(&self) -> Mirror {
+        self.mirror
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.prg_bank);
+        state.write(&self.mirror);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.prg_bank = state.read()?;
+        self.mirror = state.read()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use crate::nes::mappers::Mapper;
+use crate::nes::state::{StateError, StateReader, StateWriter};
+
+/// GxROM
+pub struct Mapper066 {
+    prg_banks_count: u8,
+    chr_banks_count: u8,
+
+    /// 32K bank
+    prg_bank: u8,
+    /// 8K bank
+    chr_bank: u8,
+}
+
+impl Mapper for Mapper066 {
+    fn new(prg_banks_count: u8, chr_banks_count: u8) -> Self {
+        Self {
+            prg_banks_count,
+            chr_banks_count,
+
+            prg_bank: 0,
+            chr_bank: 0,
+        }
+    }
+
+    fn cpu_map_read(&self, addr: u16, _read_only: bool, mapped_addr: &mut u32, _data: &mut u8) -> bool {
+        if addr >= 0x8000 {
+            let bank = self.prg_bank as usize % (self.prg_banks_count as usize / 2).max(1);
+            let mask = if self.prg_banks_count > 1 { 0x7FFF } else { 0x3FFF };
+            *mapped_addr = (bank * 0x8000 + (addr & mask) as usize) as u32;
+            return true;
+        }
+
+        false
+    }
+
+    fn cpu_map_write(&mut self, addr: u16, mapped_addr: &mut u32, data: u8) -> bool {
+        // Bank select, PRG in bits 4-5 and CHR in bits 0-1
+        if addr >= 0x8000 {
+            self.prg_bank = (data >> 4) & 0x03;
+            self.chr_bank = data & 0x03;
+            *mapped_addr = 0xFFFFFFFF;
+            return true;
+        }
+
+        false
+    }
+
+    fn ppu_map_read(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        if addr <= 0x1FFF {
+            let bank = self.chr_bank as usize % self.chr_banks_count.max(1) as usize;
+            *mapped_addr = (bank * 0x2000 + addr as usize) as u32;
+            return true;
+        }
+
+        false
+    }
+
+    fn ppu_map_write(&self, addr: u16, mapped_addr: &mut u32) -> bool {
+        if addr <= 0x1FFF && self.chr_banks_count == 0 {
+            *mapped_addr = addr as u32;
+            return true;
+        }
+
+        false
+    }
+
+    fn reset(&mut self) {
+        self.prg_bank = 0;
+        self.chr_bank = 0;
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.prg_bank);
+        state.write(&self.chr_bank);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.prg_bank = state.read()?;
+        self.chr_bank = state.read()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,278 @@
+use std::fmt::{self, Write};
+use std::io;
+use std::path::Path;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+use super::{Controller, FourScore, Nes, StateError};
+
+/// Buttons in the order of an FM2 input field, the character at index `i` is bit `i` of the controller state
+const FM2_BUTTONS: [char; 8] = ['R', 'L', 'D', 'U', 'T', 'S', 'B', 'A'];
+
+#[derive(Debug)]
+pub enum MovieError {
+    Io(io::Error),
+    /// Only version 3 of the FM2 format exists
+    UnsupportedVersion(String),
+    /// Line number, starting at 1, and what's wrong with it
+    Malformed(usize, String),
+    /// The start state doesn't load on this machine
+    State(StateError),
+}
+
+impl fmt::Display for MovieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::UnsupportedVersion(version) => write!(f, "FM2 version {version} is not supported, expected 3"),
+            Self::Malformed(line, reason) => write!(f, "malformed movie at line {line}, {reason}"),
+            Self::State(error) => write!(f, "movie start state: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for MovieError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::State(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MovieError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<StateError> for MovieError {
+    fn from(error: StateError) -> Self {
+        Self::State(error)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MovieFrame {
+    /// `SOFT_RESET`, `POWER` and `FDS_SELECT_SIDE` bits, applied before the frame runs
+    pub commands: u8,
+    /// Same layout as `Controller` states, 3 and 4 are only used with a Four Score
+    pub controllers: [u8; 4],
+}
+
+impl MovieFrame {
+    pub const SOFT_RESET: u8 = 0x01;
+    pub const POWER: u8 = 0x02;
+    /// FCEUX also has a separate eject command, switching sides already ejects the disk here
+    pub const FDS_SELECT_SIDE: u8 = 0x08;
+}
+
+/// Controller states for every frame, from power-on or from a save state, in FCEUX's `.fm2` format.
+///
+/// Playing a movie on the same cartridge always ends up with the same machine state, so they double
+/// as end-to-end tests: play one and check the screen at a few frames.
+#[derive(Debug, Clone, Default)]
+pub struct Movie {
+    pub frames: Vec<MovieFrame>,
+    /// Four controllers on a Four Score instead of two
+    pub four_score: bool,
+    /// Save state to load before the first frame, the movie starts from power-on without one
+    pub start_state: Option<Vec<u8>>,
+
+    pub rom_filename: String,
+    pub rerecord_count: u32,
+    pub comments: Vec<String>,
+    /// Header lines this emulator doesn't use, kept as they were when exporting
+    other_headers: Vec<(String, String)>,
+}
+
+impl Movie {
+    /// Empty movie starting from power-on
+    pub fn new(rom_filename: &str) -> Self {
+        Self {
+            rom_filename: rom_filename.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Empty movie starting from the current state of `nes`
+    pub fn from_state(rom_filename: &str, nes: &Nes) -> Self {
+        Self {
+            start_state: Some(nes.save_state()),
+            ..Self::new(rom_filename)
+        }
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, MovieError> {
+        Self::from_fm2(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.to_fm2())
+    }
+
+    pub fn from_fm2(text: &str) -> Result<Self, MovieError> {
+        let mut movie = Self::default();
+        let mut version = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim_end_matches('\r');
+
+            if let Some(input) = line.strip_prefix('|') {
+                movie.frames.push(Self::parse_frame(input, movie.pads_count()).ok_or_else(|| MovieError::Malformed(line_number, format!("bad input line `{line}`")))?);
+                continue;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "version" => version = Some(value.to_string()),
+                "romFilename" => movie.rom_filename = value.to_string(),
+                "rerecordCount" => movie.rerecord_count = value.parse().map_err(|_| MovieError::Malformed(line_number, format!("bad rerecord count `{value}`")))?,
+                "comment" => movie.comments.push(value.to_string()),
+                "savestate" => {
+                    let data = value.strip_prefix("base64:").unwrap_or(value);
+                    let state = BASE64.decode(data).map_err(|error| MovieError::Malformed(line_number, format!("bad save state, {error}")))?;
+                    movie.start_state = Some(state);
+                }
+                "fourscore" => movie.four_score = value.trim() == "1",
+                // Rewritten when exporting
+                "emuVersion" | "port0" | "port1" | "port2" => {}
+                _ => movie.other_headers.push((key.to_string(), value.to_string())),
+            }
+        }
+
+        match version.as_deref() {
+            Some("3") => Ok(movie),
+            Some(version) => Err(MovieError::UnsupportedVersion(version.to_string())),
+            None => Err(MovieError::Malformed(1, "no version".to_string())),
+        }
+    }
+
+    fn pads_count(&self) -> usize {
+        if self.four_score { 4 } else { 2 }
+    }
+
+    /// `commands|port0|port1|port2|`, or `commands|pad1|pad2|pad3|pad4|port2|` with a Four Score, a port field
+    /// is empty when nothing is plugged in
+    fn parse_frame(input: &str, pads_count: usize) -> Option<MovieFrame> {
+        let mut fields = input.split('|');
+        let commands = fields.next()?.trim().parse().ok()?;
+
+        let mut controllers = [0; 4];
+        for controller in &mut controllers[..pads_count] {
+            let field = fields.next().unwrap_or("");
+            if field.is_empty() {
+                continue;
+            }
+            if field.chars().count() != FM2_BUTTONS.len() {
+                return None;
+            }
+
+            *controller = field.chars()
+                .enumerate()
+                .filter(|&(_, button)| button != '.' && button != ' ')
+                .fold(0, |state, (i, _)| state | (1 << i));
+        }
+
+        Some(MovieFrame { commands, controllers })
+    }
+
+    pub fn to_fm2(&self) -> String {
+        let mut fm2 = String::new();
+
+        // Writing to a String can't fail
+        let _ = writeln!(fm2, "version 3");
+        let _ = writeln!(fm2, "emuVersion 0");
+        let _ = writeln!(fm2, "rerecordCount {}", self.rerecord_count);
+        let _ = writeln!(fm2, "romFilename {}", self.rom_filename);
+        for (key, value) in &self.other_headers {
+            let _ = writeln!(fm2, "{key} {value}");
+        }
+        let _ = writeln!(fm2, "fourscore {}", u8::from(self.four_score));
+        let _ = writeln!(fm2, "port0 1");
+        let _ = writeln!(fm2, "port1 1");
+        let _ = writeln!(fm2, "port2 0");
+        for comment in &self.comments {
+            let _ = writeln!(fm2, "comment {comment}");
+        }
+        if let Some(state) = &self.start_state {
+            let _ = writeln!(fm2, "savestate base64:{}", BASE64.encode(state));
+        }
+
+        for frame in &self.frames {
+            let _ = write!(fm2, "|{}|", frame.commands);
+            for &state in &frame.controllers[..self.pads_count()] {
+                for (i, button) in FM2_BUTTONS.into_iter().enumerate() {
+                    fm2.push(if state & (1 << i) != 0 { button } else { '.' });
+                }
+                fm2.push('|');
+            }
+            fm2.push_str("|\n");
+        }
+
+        fm2
+    }
+
+    /// Puts `nes` where the movie starts, with the controllers it was recorded with
+    pub fn rewind_to_start(&self, nes: &mut Nes) -> Result<(), MovieError> {
+        nes.input_ports = if self.four_score {
+            [Box::new(FourScore::new(0)), Box::new(FourScore::new(1))]
+        } else {
+            [Box::new(Controller::new()), Box::new(Controller::new())]
+        };
+        match &self.start_state {
+            Some(state) => nes.load_state(state)?,
+            None => nes.power_on(),
+        }
+
+        Ok(())
+    }
+
+    /// To be called right before running each frame while recording, once the controllers have their inputs
+    pub fn push_frame(&mut self, nes: &Nes, commands: u8) {
+        self.frames.push(MovieFrame {
+            commands,
+            controllers: nes.get_pad_states(),
+        });
+    }
+
+    /// Runs the commands of frame `index` and gives the controllers its inputs, false past the end
+    pub fn apply_frame(&self, index: usize, nes: &mut Nes) -> bool {
+        let Some(frame) = self.frames.get(index) else {
+            return false;
+        };
+
+        if frame.commands & MovieFrame::POWER != 0 {
+            nes.power_on();
+        } else if frame.commands & MovieFrame::SOFT_RESET != 0 {
+            nes.reset();
+        }
+        if frame.commands & MovieFrame::FDS_SELECT_SIDE != 0 {
+            nes.switch_disk_side();
+        }
+
+        nes.set_pad_states(frame.controllers);
+
+        true
+    }
+
+    /// Plays the whole movie from its start, `on_frame` is called after each frame with its index
+    #[allow(dead_code)]
+    pub fn play(&self, nes: &mut Nes, mut on_frame: impl FnMut(usize, &Nes)) -> Result<(), MovieError> {
+        self.rewind_to_start(nes)?;
+
+        for index in 0..self.frames.len() {
+            self.apply_frame(index, nes);
+            nes.run_frame();
+            on_frame(index, nes);
+        }
+
+        Ok(())
+    }
+}
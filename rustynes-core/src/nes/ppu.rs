@@ -2,108 +2,110 @@
 
 mod registers;
 mod oam;
+mod pixel;
 
-use raylib::color::Color;
 use crate::constants::{NES_SCREEN_HEIGHT, NES_SCREEN_WIDTH};
 use crate::nes::cartridge::{ComponentCartridge, Mirror};
+use crate::nes::state::{SaveState, StateError, StateReader, StateWriter};
 use registers::{RegisterControl, RegisterLoopy, RegisterMask, RegisterStatus};
 use oam::{EntryOA, OAM};
+pub use pixel::Pixel;
 
 #[derive(Debug)]
 pub struct ScreenData {
-    pub displayable_screen: Box<[Color]>,
-    screen_palette: Box<[Color; 64]>,
+    pub displayable_screen: Box<[Pixel]>,
+    screen_palette: Box<[Pixel; 64]>,
     #[allow(dead_code)]
-    displayable_name_table: Box<[Box<[Color]>; 2]>,
-    pub displayable_pattern_table: Box<[Box<[Color]>; 2]>,
+    displayable_name_table: Box<[Box<[Pixel]>; 2]>,
+    pub displayable_pattern_table: Box<[Box<[Pixel]>; 2]>,
 }
 
 impl ScreenData {
     pub fn new() -> Self {
         Self {
-            // displayable_screen: [Color::BLANK; NES_SCREEN_WIDTH as usize * NES_SCREEN_HEIGHT as usize],
-            // displayable_screen: Box::new([Color::BLANK; NES_SCREEN_WIDTH as usize * NES_SCREEN_HEIGHT as usize]),
-            displayable_screen: vec![Color::BLANK; NES_SCREEN_WIDTH as usize * NES_SCREEN_HEIGHT as usize].into_boxed_slice(),
+            // displayable_screen: [Pixel::BLANK; NES_SCREEN_WIDTH as usize * NES_SCREEN_HEIGHT as usize],
+            // displayable_screen: Box::new([Pixel::BLANK; NES_SCREEN_WIDTH as usize * NES_SCREEN_HEIGHT as usize]),
+            displayable_screen: vec![Pixel::BLANK; NES_SCREEN_WIDTH as usize * NES_SCREEN_HEIGHT as usize].into_boxed_slice(),
             screen_palette: {
-                // let mut screen_palette = [Color::BLANK; 64];
-                let mut screen_palette = Box::new([Color::BLANK; 64]);
-                screen_palette[0x00] = Color::new(84, 84, 84, 255);
-	            screen_palette[0x01] = Color::new(0, 30, 116, 255);
-	            screen_palette[0x02] = Color::new(8, 16, 144, 255);
-	            screen_palette[0x03] = Color::new(48, 0, 136, 255);
-	            screen_palette[0x04] = Color::new(68, 0, 100, 255);
-	            screen_palette[0x05] = Color::new(92, 0, 48, 255);
-	            screen_palette[0x06] = Color::new(84, 4, 0, 255);
-	            screen_palette[0x07] = Color::new(60, 24, 0, 255);
-	            screen_palette[0x08] = Color::new(32, 42, 0, 255);
-	            screen_palette[0x09] = Color::new(8, 58, 0, 255);
-	            screen_palette[0x0A] = Color::new(0, 64, 0, 255);
-	            screen_palette[0x0B] = Color::new(0, 60, 0, 255);
-	            screen_palette[0x0C] = Color::new(0, 50, 60, 255);
-	            screen_palette[0x0D] = Color::new(0, 0, 0, 255);
-	            screen_palette[0x0E] = Color::new(0, 0, 0, 255);
-	            screen_palette[0x0F] = Color::new(0, 0, 0, 255);
-
-	            screen_palette[0x10] = Color::new(152, 150, 152, 255);
-	            screen_palette[0x11] = Color::new(8, 76, 196, 255);
-	            screen_palette[0x12] = Color::new(48, 50, 236, 255);
-	            screen_palette[0x13] = Color::new(92, 30, 228, 255);
-	            screen_palette[0x14] = Color::new(136, 20, 176, 255);
-	            screen_palette[0x15] = Color::new(160, 20, 100, 255);
-	            screen_palette[0x16] = Color::new(152, 34, 32, 255);
-	            screen_palette[0x17] = Color::new(120, 60, 0, 255);
-	            screen_palette[0x18] = Color::new(84, 90, 0, 255);
-	            screen_palette[0x19] = Color::new(40, 114, 0, 255);
-	            screen_palette[0x1A] = Color::new(8, 124, 0, 255);
-	            screen_palette[0x1B] = Color::new(0, 118, 40, 255);
-	            screen_palette[0x1C] = Color::new(0, 102, 120, 255);
-	            screen_palette[0x1D] = Color::new(0, 0, 0, 255);
-	            screen_palette[0x1E] = Color::new(0, 0, 0, 255);
-	            screen_palette[0x1F] = Color::new(0, 0, 0, 255);
-
-	            screen_palette[0x20] = Color::new(236, 238, 236, 255);
-	            screen_palette[0x21] = Color::new(76, 154, 236, 255);
-	            screen_palette[0x22] = Color::new(120, 124, 236, 255);
-	            screen_palette[0x23] = Color::new(176, 98, 236, 255);
-	            screen_palette[0x24] = Color::new(228, 84, 236, 255);
-	            screen_palette[0x25] = Color::new(236, 88, 180, 255);
-	            screen_palette[0x26] = Color::new(236, 106, 100, 255);
-	            screen_palette[0x27] = Color::new(212, 136, 32, 255);
-	            screen_palette[0x28] = Color::new(160, 170, 0, 255);
-	            screen_palette[0x29] = Color::new(116, 196, 0, 255);
-	            screen_palette[0x2A] = Color::new(76, 208, 32, 255);
-	            screen_palette[0x2B] = Color::new(56, 204, 108, 255);
-	            screen_palette[0x2C] = Color::new(56, 180, 204, 255);
-	            screen_palette[0x2D] = Color::new(60, 60, 60, 255);
-	            screen_palette[0x2E] = Color::new(0, 0, 0, 255);
-	            screen_palette[0x2F] = Color::new(0, 0, 0, 255);
-
-	            screen_palette[0x30] = Color::new(236, 238, 236, 255);
-	            screen_palette[0x31] = Color::new(168, 204, 236, 255);
-	            screen_palette[0x32] = Color::new(188, 188, 236, 255);
-	            screen_palette[0x33] = Color::new(212, 178, 236, 255);
-	            screen_palette[0x34] = Color::new(236, 174, 236, 255);
-	            screen_palette[0x35] = Color::new(236, 174, 212, 255);
-	            screen_palette[0x36] = Color::new(236, 180, 176, 255);
-	            screen_palette[0x37] = Color::new(228, 196, 144, 255);
-	            screen_palette[0x38] = Color::new(204, 210, 120, 255);
-	            screen_palette[0x39] = Color::new(180, 222, 120, 255);
-	            screen_palette[0x3A] = Color::new(168, 226, 144, 255);
-	            screen_palette[0x3B] = Color::new(152, 226, 180, 255);
-	            screen_palette[0x3C] = Color::new(160, 214, 228, 255);
-	            screen_palette[0x3D] = Color::new(160, 162, 160, 255);
-	            screen_palette[0x3E] = Color::new(0, 0, 0, 255);
-	            screen_palette[0x3F] = Color::new(0, 0, 0, 255);
+                // let mut screen_palette = [Pixel::BLANK; 64];
+                let mut screen_palette = Box::new([Pixel::BLANK; 64]);
+                screen_palette[0x00] = Pixel::new(84, 84, 84, 255);
+	            screen_palette[0x01] = Pixel::new(0, 30, 116, 255);
+	            screen_palette[0x02] = Pixel::new(8, 16, 144, 255);
+	            screen_palette[0x03] = Pixel::new(48, 0, 136, 255);
+	            screen_palette[0x04] = Pixel::new(68, 0, 100, 255);
+	            screen_palette[0x05] = Pixel::new(92, 0, 48, 255);
+	            screen_palette[0x06] = Pixel::new(84, 4, 0, 255);
+	            screen_palette[0x07] = Pixel::new(60, 24, 0, 255);
+	            screen_palette[0x08] = Pixel::new(32, 42, 0, 255);
+	            screen_palette[0x09] = Pixel::new(8, 58, 0, 255);
+	            screen_palette[0x0A] = Pixel::new(0, 64, 0, 255);
+	            screen_palette[0x0B] = Pixel::new(0, 60, 0, 255);
+	            screen_palette[0x0C] = Pixel::new(0, 50, 60, 255);
+	            screen_palette[0x0D] = Pixel::new(0, 0, 0, 255);
+	            screen_palette[0x0E] = Pixel::new(0, 0, 0, 255);
+	            screen_palette[0x0F] = Pixel::new(0, 0, 0, 255);
+
+	            screen_palette[0x10] = Pixel::new(152, 150, 152, 255);
+	            screen_palette[0x11] = Pixel::new(8, 76, 196, 255);
+	            screen_palette[0x12] = Pixel::new(48, 50, 236, 255);
+	            screen_palette[0x13] = Pixel::new(92, 30, 228, 255);
+	            screen_palette[0x14] = Pixel::new(136, 20, 176, 255);
+	            screen_palette[0x15] = Pixel::new(160, 20, 100, 255);
+	            screen_palette[0x16] = Pixel::new(152, 34, 32, 255);
+	            screen_palette[0x17] = Pixel::new(120, 60, 0, 255);
+	            screen_palette[0x18] = Pixel::new(84, 90, 0, 255);
+	            screen_palette[0x19] = Pixel::new(40, 114, 0, 255);
+	            screen_palette[0x1A] = Pixel::new(8, 124, 0, 255);
+	            screen_palette[0x1B] = Pixel::new(0, 118, 40, 255);
+	            screen_palette[0x1C] = Pixel::new(0, 102, 120, 255);
+	            screen_palette[0x1D] = Pixel::new(0, 0, 0, 255);
+	            screen_palette[0x1E] = Pixel::new(0, 0, 0, 255);
+	            screen_palette[0x1F] = Pixel::new(0, 0, 0, 255);
+
+	            screen_palette[0x20] = Pixel::new(236, 238, 236, 255);
+	            screen_palette[0x21] = Pixel::new(76, 154, 236, 255);
+	            screen_palette[0x22] = Pixel::new(120, 124, 236, 255);
+	            screen_palette[0x23] = Pixel::new(176, 98, 236, 255);
+	            screen_palette[0x24] = Pixel::new(228, 84, 236, 255);
+	            screen_palette[0x25] = Pixel::new(236, 88, 180, 255);
+	            screen_palette[0x26] = Pixel::new(236, 106, 100, 255);
+	            screen_palette[0x27] = Pixel::new(212, 136, 32, 255);
+	            screen_palette[0x28] = Pixel::new(160, 170, 0, 255);
+	            screen_palette[0x29] = Pixel::new(116, 196, 0, 255);
+	            screen_palette[0x2A] = Pixel::new(76, 208, 32, 255);
+	            screen_palette[0x2B] = Pixel::new(56, 204, 108, 255);
+	            screen_palette[0x2C] = Pixel::new(56, 180, 204, 255);
+	            screen_palette[0x2D] = Pixel::new(60, 60, 60, 255);
+	            screen_palette[0x2E] = Pixel::new(0, 0, 0, 255);
+	            screen_palette[0x2F] = Pixel::new(0, 0, 0, 255);
+
+	            screen_palette[0x30] = Pixel::new(236, 238, 236, 255);
+	            screen_palette[0x31] = Pixel::new(168, 204, 236, 255);
+	            screen_palette[0x32] = Pixel::new(188, 188, 236, 255);
+	            screen_palette[0x33] = Pixel::new(212, 178, 236, 255);
+	            screen_palette[0x34] = Pixel::new(236, 174, 236, 255);
+	            screen_palette[0x35] = Pixel::new(236, 174, 212, 255);
+	            screen_palette[0x36] = Pixel::new(236, 180, 176, 255);
+	            screen_palette[0x37] = Pixel::new(228, 196, 144, 255);
+	            screen_palette[0x38] = Pixel::new(204, 210, 120, 255);
+	            screen_palette[0x39] = Pixel::new(180, 222, 120, 255);
+	            screen_palette[0x3A] = Pixel::new(168, 226, 144, 255);
+	            screen_palette[0x3B] = Pixel::new(152, 226, 180, 255);
+	            screen_palette[0x3C] = Pixel::new(160, 214, 228, 255);
+	            screen_palette[0x3D] = Pixel::new(160, 162, 160, 255);
+	            screen_palette[0x3E] = Pixel::new(0, 0, 0, 255);
+	            screen_palette[0x3F] = Pixel::new(0, 0, 0, 255);
 
                 // screen_palette
                 screen_palette
             },
-            displayable_name_table: Box::new([vec![Color::BLANK; 256 * 240].into_boxed_slice(), vec![Color::BLANK; 256 * 240].into_boxed_slice()]),
-            displayable_pattern_table: Box::new([vec![Color::BLANK; 128 * 128].into_boxed_slice(), vec![Color::BLANK; 128 * 128].into_boxed_slice()]),
+            displayable_name_table: Box::new([vec![Pixel::BLANK; 256 * 240].into_boxed_slice(), vec![Pixel::BLANK; 256 * 240].into_boxed_slice()]),
+            displayable_pattern_table: Box::new([vec![Pixel::BLANK; 128 * 128].into_boxed_slice(), vec![Pixel::BLANK; 128 * 128].into_boxed_slice()]),
         }
     }
 
-    pub fn draw_pixel_screen(&mut self, x: u16, y: u16, color: Color) {
+    pub fn draw_pixel_screen(&mut self, x: u16, y: u16, color: Pixel) {
         if x >= NES_SCREEN_WIDTH || y >= NES_SCREEN_HEIGHT {
             return;
         }
@@ -111,7 +113,7 @@ impl ScreenData {
         self.displayable_screen[y as usize * NES_SCREEN_WIDTH as usize + x as usize] = color;
     }
 
-    pub fn draw_pixel_pattern_table(&mut self, index: u8, x: u16, y: u16, color: Color) {
+    pub fn draw_pixel_pattern_table(&mut self, index: u8, x: u16, y: u16, color: Pixel) {
         if x >= 128 || y >= 128 {
             return;
         }
@@ -142,6 +144,8 @@ pub struct Component2C02 {
     vram_addr: RegisterLoopy,
     tram_addr: RegisterLoopy,
     fine_x: u8,
+    /// Last address put on the PPU bus, watched by some mappers
+    pub address_bus: u16,
 
     // Background
     bg_next_tile_id: u8,
@@ -189,6 +193,7 @@ impl Component2C02 {
             vram_addr: RegisterLoopy::new(),
             tram_addr: RegisterLoopy::new(),
             fine_x: 0,
+            address_bus: 0,
 
             bg_next_tile_id: 0,
             bg_next_tile_attribute: 0,
@@ -215,7 +220,16 @@ impl Component2C02 {
         }
     }
     
-    #[allow(clippy::match_same_arms)]
+    /// Scanline being drawn, -1 for the pre-render one
+    pub const fn get_scanline(&self) -> i16 {
+        self.scanline
+    }
+
+    /// Dot of the scanline being drawn
+    pub const fn get_cycle(&self) -> i16 {
+        self.cycle
+    }
+
     pub fn cpu_read(&mut self, addr: u16, read_only: bool, cartridge: &ComponentCartridge) -> u8 {
         let mut data = 0x00;
 
@@ -227,8 +241,10 @@ impl Component2C02 {
             // Status
             0x0002 => {
                 data = (self.reg_status.into_bits()) & 0xE0 | (self.ppu_data_buffer & 0x1F);
-                self.reg_status.set_vertical_blank(false);
-                self.address_latch = 0;
+                if !read_only {
+                    self.reg_status.set_vertical_blank(false);
+                    self.address_latch = 0;
+                }
             }
             // OAM Address
             0x0003 => {}
@@ -239,6 +255,9 @@ impl Component2C02 {
             // PPU Address
             0x0006 => {}
             // PPU Data
+            0x0007 if read_only => {
+                data = if self.vram_addr.into_bits() >= 0x3F00 { self.ppu_read(self.vram_addr.into_bits(), read_only, cartridge) } else { self.ppu_data_buffer };
+            }
             0x0007 => {
                 data = self.ppu_data_buffer;
                 self.ppu_data_buffer = self.ppu_read(self.vram_addr.into_bits(), read_only, cartridge);
@@ -248,6 +267,7 @@ impl Component2C02 {
                 }
 
                 self.vram_addr = RegisterLoopy::from_bits(if self.reg_control.increment_mode() { self.vram_addr.into_bits().wrapping_add(32) } else { self.vram_addr.into_bits().wrapping_add(1) });
+                self.address_bus = self.vram_addr.into_bits() & 0x3FFF;
             }
 
             _ => {}
@@ -293,6 +313,7 @@ impl Component2C02 {
                 } else {
                     self.tram_addr = RegisterLoopy::from_bits((self.tram_addr.into_bits() & 0xFF00) | data as u16);
                     self.vram_addr = self.tram_addr;
+                    self.address_bus = self.vram_addr.into_bits() & 0x3FFF;
                     self.address_latch = 0;
                 }
             }
@@ -300,12 +321,25 @@ impl Component2C02 {
             0x0007 => {
                 self.ppu_write(self.vram_addr.into_bits(), data, cartridge);
                 self.vram_addr = RegisterLoopy::from_bits(if self.reg_control.increment_mode() { self.vram_addr.into_bits().wrapping_add(32) } else { self.vram_addr.into_bits().wrapping_add(1) });
+                self.address_bus = self.vram_addr.into_bits() & 0x3FFF;
             }
 
             _ => {},
         };
     }
 
+    /// Internal nametable an address in $2000-$3EFF lands in
+    const fn name_table_index(addr: u16, mirror: Mirror) -> Option<usize> {
+        match mirror {
+            Mirror::Vertical => Some(((addr >> 10) & 0x01) as usize),
+            Mirror::Horizontal => Some(((addr >> 11) & 0x01) as usize),
+            Mirror::OneScreenLo => Some(0),
+            Mirror::OneScreenHi => Some(1),
+            // Four-screen nametables are handled by the cartridge
+            Mirror::Hardware | Mirror::FourScreen => None,
+        }
+    }
+
     pub fn ppu_read(&self, mut addr: u16, _read_only: bool, cartridge: &ComponentCartridge) -> u8 {
         let mut data = 0x00;
         addr &= 0x3FFF;
@@ -319,23 +353,8 @@ impl Component2C02 {
             0x0000..=0x1FFF => data = self.pattern_table[(addr & 0x1000 >> 12) as usize][(addr & 0x0FFF) as usize],
             // Name Table range
             0x2000..=0x3EFF => {
-                addr &= 0x0FFF;
-                match cartridge.mirror() {
-                    Mirror::Vertical => {
-                        match addr {
-                            0x0000..=0x03FF | 0x0800..=0x0BFF => data = self.name_table[0][(addr & 0x03FF) as usize],
-                            0x0400..=0x07FF | 0x0C00..=0x0FFF => data = self.name_table[1][(addr & 0x03FF) as usize],
-                            _ => {}
-                        }
-                    }
-                    Mirror::Horizontal => {
-                        match addr {
-                            0x0000..=0x07FF => data = self.name_table[0][(addr & 0x03FF) as usize],
-                            0x0800..=0x0FFF => data = self.name_table[1][(addr & 0x03FF) as usize],
-                            _ => {}
-                        }
-                    }
-                    _ => {}
+                if let Some(table) = Self::name_table_index(addr, cartridge.mirror()) {
+                    data = self.name_table[table][(addr & 0x03FF) as usize];
                 }
             }
             // Palette RAM range
@@ -368,23 +387,8 @@ impl Component2C02 {
             0x0000..=0x1FFF => self.pattern_table[(addr & 0x1000 >> 12) as usize][(addr & 0x0FFF) as usize] = data,
             // Name Table range
             0x2000..=0x3EFF => {
-                addr &= 0x0FFF;
-                match cartridge.mirror() {
-                    Mirror::Vertical => {
-                        match addr {
-                            0x0000..=0x03FF | 0x0800..=0x0BFF => self.name_table[0][(addr & 0x03FF) as usize] = data,
-                            0x0400..=0x07FF | 0x0C00..=0x0FFF => self.name_table[1][(addr & 0x03FF) as usize] = data,
-                            _ => {}
-                        }
-                    }
-                    Mirror::Horizontal => {
-                        match addr {
-                            0x0000..=0x07FF => self.name_table[0][(addr & 0x03FF) as usize] = data,
-                            0x0800..=0x0FFF => self.name_table[1][(addr & 0x03FF) as usize] = data,
-                            _ => {}
-                        }
-                    }
-                    _ => {}
+                if let Some(table) = Self::name_table_index(addr, cartridge.mirror()) {
+                    self.name_table[table][(addr & 0x03FF) as usize] = data;
                 }
             }
             // Palette RAM range
@@ -477,6 +481,15 @@ impl Component2C02 {
         }
     }
 
+    /// Rendering fetches only drive the address bus while rendering is enabled
+    fn fetch(&mut self, addr: u16, cartridge: &ComponentCartridge) -> u8 {
+        if self.reg_mask.render_background() || self.reg_mask.render_sprites() {
+            self.address_bus = addr;
+        }
+
+        self.ppu_read(addr, false, cartridge)
+    }
+
     #[allow(clippy::cognitive_complexity)]
     pub fn tick(&mut self, screen: &mut ScreenData, cartridge: &ComponentCartridge) {
         if self.scanline >= -1 && self.scanline < 240 {
@@ -501,15 +514,15 @@ impl Component2C02 {
                 match (self.cycle - 1) % 8 {
                     0 => {
                         self.load_background_shifters();
-                        self.bg_next_tile_id = self.ppu_read(0x2000 | (self.vram_addr.into_bits() & 0x0FFF), false, cartridge);
+                        self.bg_next_tile_id = self.fetch(0x2000 | (self.vram_addr.into_bits() & 0x0FFF), cartridge);
                     },
                     2 => {
-                        self.bg_next_tile_attribute = self.ppu_read(0x23C0
+                        self.bg_next_tile_attribute = self.fetch(0x23C0
                             | ((self.vram_addr.nametable_y() as u16) << 11)
                             | ((self.vram_addr.nametable_x() as u16) << 10)
                             | (((self.vram_addr.coarse_y() as u16) >> 2) << 3)
                             | ((self.vram_addr.coarse_x() >> 2) as u16),
-                            false, cartridge);
+                            cartridge);
                         if self.vram_addr.coarse_y() & 0x02 != 0 {
                             self.bg_next_tile_attribute >>= 4;
                         }
@@ -519,18 +532,18 @@ impl Component2C02 {
                         self.bg_next_tile_attribute &= 0x03;
                     },
                     4 => {
-                        self.bg_next_tile_lsb = self.ppu_read(
+                        self.bg_next_tile_lsb = self.fetch(
                             ((self.reg_control.pattern_background() as u16) << 12)
                             + ((self.bg_next_tile_id as u16) << 4)
                             + (self.vram_addr.fine_y() as u16),
-                            false, cartridge);
+                            cartridge);
                     },
                     6 => {
-                        self.bg_next_tile_msb = self.ppu_read(
+                        self.bg_next_tile_msb = self.fetch(
                             ((self.reg_control.pattern_background() as u16) << 12)
                             + ((self.bg_next_tile_id as u16) << 4)
                             + (self.vram_addr.fine_y() as u16) + 8,
-                            false, cartridge);
+                            cartridge);
                     },
                     7 => self.increment_scroll_x(),
                     _ => {}
@@ -550,7 +563,7 @@ impl Component2C02 {
             }
 
             if self.cycle == 338 || self.cycle == 340 {
-                self.bg_next_tile_id = self.ppu_read(0x2000 | (self.vram_addr.into_bits() & 0x0FFF), false, cartridge);
+                self.bg_next_tile_id = self.fetch(0x2000 | (self.vram_addr.into_bits() & 0x0FFF), cartridge);
             }
 
             // Sprite evaluation phase
@@ -629,8 +642,8 @@ impl Component2C02 {
                     }
 
                     let sprite_pattern_addr_hi = sprite_pattern_addr_lo.wrapping_add(8);
-                    sprite_pattern_bits_lo = self.ppu_read(sprite_pattern_addr_lo, false, cartridge);
-                    sprite_pattern_bits_hi = self.ppu_read(sprite_pattern_addr_hi, false, cartridge);
+                    sprite_pattern_bits_lo = self.fetch(sprite_pattern_addr_lo, cartridge);
+                    sprite_pattern_bits_hi = self.fetch(sprite_pattern_addr_hi, cartridge);
 
                     if self.sprites_scanline[i].attributes & 0x40 != 0 {
                         sprite_pattern_bits_lo = sprite_pattern_bits_lo.reverse_bits();
@@ -640,6 +653,12 @@ impl Component2C02 {
                     self.sprite_shifter_pattern_lo[i] = sprite_pattern_bits_lo;
                     self.sprite_shifter_pattern_hi[i] = sprite_pattern_bits_hi;
                 }
+
+                // Unused sprite slots still fetch tile $FF
+                if self.sprite_count < 8 {
+                    let dummy_addr = if self.reg_control.sprite_size() { 0x1FF0 } else { ((self.reg_control.pattern_sprite() as u16) << 12) | 0x0FF0 };
+                    self.fetch(dummy_addr, cartridge);
+                }
             }
         }
 
@@ -737,7 +756,7 @@ impl Component2C02 {
         }
     }
 
-    fn get_palette_color(&self, palette: &ScreenData, palette_index: u8, pixel_index: u8, cartridge: &ComponentCartridge) -> Color {
+    fn get_palette_color(&self, palette: &ScreenData, palette_index: u8, pixel_index: u8, cartridge: &ComponentCartridge) -> Pixel {
         palette.screen_palette[(self.ppu_read(0x3F00 + (palette_index << 2) as u16 + pixel_index as u16, false, cartridge) & 0x3F) as usize]
     }
 
@@ -763,3 +782,91 @@ impl Component2C02 {
         };
     }
 }
+
+impl SaveState for Component2C02 {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.name_table);
+        state.write(&self.pattern_table);
+        state.write(&self.pallete_table);
+
+        state.write(&self.reg_status.into_bits());
+        state.write(&self.reg_mask.into_bits());
+        state.write(&self.reg_control.into_bits());
+
+        state.write(&self.nmi_occurred);
+        state.write(&self.address_latch);
+        state.write(&self.ppu_data_buffer);
+
+        state.write(&self.vram_addr.into_bits());
+        state.write(&self.tram_addr.into_bits());
+        state.write(&self.fine_x);
+        state.write(&self.address_bus);
+
+        state.write(&self.bg_next_tile_id);
+        state.write(&self.bg_next_tile_attribute);
+        state.write(&self.bg_next_tile_lsb);
+        state.write(&self.bg_next_tile_msb);
+        state.write(&self.bg_shifter_pattern_lo);
+        state.write(&self.bg_shifter_pattern_hi);
+        state.write(&self.bg_shifter_attribute_lo);
+        state.write(&self.bg_shifter_attribute_hi);
+
+        self.oam.save_state(state);
+        state.write(&self.sprites_scanline);
+        state.write(&self.sprite_count);
+        state.write(&self.sprite_shifter_pattern_lo);
+        state.write(&self.sprite_shifter_pattern_hi);
+
+        state.write(&self.is_sprite_zero_hit_possible);
+        state.write(&self.is_sprite_zero_being_rendered);
+
+        state.write(&self.scanline);
+        state.write(&self.cycle);
+
+        state.write(&self.is_frame_complete);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.name_table = state.read()?;
+        self.pattern_table = state.read()?;
+        self.pallete_table = state.read()?;
+
+        self.reg_status = RegisterStatus::from_bits(state.read()?);
+        self.reg_mask = RegisterMask::from_bits(state.read()?);
+        self.reg_control = RegisterControl::from_bits(state.read()?);
+
+        self.nmi_occurred = state.read()?;
+        self.address_latch = state.read()?;
+        self.ppu_data_buffer = state.read()?;
+
+        self.vram_addr = RegisterLoopy::from_bits(state.read()?);
+        self.tram_addr = RegisterLoopy::from_bits(state.read()?);
+        self.fine_x = state.read()?;
+        self.address_bus = state.read()?;
+
+        self.bg_next_tile_id = state.read()?;
+        self.bg_next_tile_attribute = state.read()?;
+        self.bg_next_tile_lsb = state.read()?;
+        self.bg_next_tile_msb = state.read()?;
+        self.bg_shifter_pattern_lo = state.read()?;
+        self.bg_shifter_pattern_hi = state.read()?;
+        self.bg_shifter_attribute_lo = state.read()?;
+        self.bg_shifter_attribute_hi = state.read()?;
+
+        self.oam.load_state(state)?;
+        self.sprites_scanline = state.read()?;
+        self.sprite_count = state.read()?;
+        self.sprite_shifter_pattern_lo = state.read()?;
+        self.sprite_shifter_pattern_hi = state.read()?;
+
+        self.is_sprite_zero_hit_possible = state.read()?;
+        self.is_sprite_zero_being_rendered = state.read()?;
+
+        self.scanline = state.read()?;
+        self.cycle = state.read()?;
+
+        self.is_frame_complete = state.read()?;
+
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+use crate::nes::state::{SaveState, StateError, StateReader, StateValue, StateWriter};
+
 #[derive(Debug, Clone, Copy)]
 pub struct EntryOA {
     pub y: u8,
@@ -24,6 +26,18 @@ impl EntryOA {
     }
 }
 
+impl StateValue for EntryOA {
+    fn write(&self, state: &mut StateWriter) {
+        state.write(&[self.y, self.tile_index, self.attributes, self.x]);
+    }
+
+    fn read(state: &mut StateReader<'_>) -> Result<Self, StateError> {
+        let [y, tile_index, attributes, x] = state.read()?;
+
+        Ok(Self { y, tile_index, attributes, x })
+    }
+}
+
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, Copy)]
 pub struct OAM {
@@ -70,4 +84,18 @@ impl OAM {
     pub fn set_address(&mut self, address: u8) {
         self.address = address;
     }
-}
\ No newline at end of file
+}
+
+impl SaveState for OAM {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write(&self.oam);
+        state.write(&self.address);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError> {
+        self.oam = state.read()?;
+        self.address = state.read()?;
+
+        Ok(())
+    }
+}
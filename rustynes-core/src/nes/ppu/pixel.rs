@@ -0,0 +1,22 @@
+/// RGBA8 color of a pixel, laid out like the bytes of an RGBA8 image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(C)]
+pub struct Pixel {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Pixel {
+    pub const BLANK: Self = Self::new(0, 0, 0, 0);
+
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Flattens `pixels` into an RGBA8 buffer
+    pub fn to_rgba(pixels: &[Self]) -> Vec<u8> {
+        pixels.iter().flat_map(|pixel| [pixel.r, pixel.g, pixel.b, pixel.a]).collect()
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+
+use super::Nes;
+
+/// Ring buffer of save states to step the emulation backwards.
+///
+/// A snapshot is taken every `interval` frames. Only the latest one is kept whole, each older one is stored
+/// as the compressed XOR with the one after it, which is mostly zeros from one snapshot to the next.
+/// Stepping back loads the last snapshot before the target frame and runs the frames in between again,
+/// with the controller inputs recorded for them.
+#[derive(Debug)]
+pub struct Rewind {
+    interval: u64,
+    capacity: usize,
+
+    latest: Vec<u8>,
+    latest_frame: u64,
+    /// `deltas[i]` turns snapshot `i + 1` back into snapshot `i`, the last one turns `latest` into the one before
+    deltas: VecDeque<Vec<u8>>,
+    /// Controller states of every frame since the oldest snapshot
+    inputs: VecDeque<[u8; 4]>,
+    /// Frames run since the buffer started
+    frame: u64,
+}
+
+impl Rewind {
+    /// Keeps up to `capacity` snapshots, taken every `interval` frames
+    pub fn new(capacity: usize, interval: u64) -> Self {
+        Self {
+            interval: interval.max(1),
+            capacity: capacity.max(1),
+
+            latest: Vec::new(),
+            latest_frame: 0,
+            deltas: VecDeque::new(),
+            inputs: VecDeque::new(),
+            frame: 0,
+        }
+    }
+
+    /// Forgets the history, to be called when the timeline is broken, like after a reset or loading a state
+    pub fn clear(&mut self) {
+        self.latest.clear();
+        self.latest_frame = 0;
+        self.deltas.clear();
+        self.inputs.clear();
+        self.frame = 0;
+    }
+
+    fn oldest_frame(&self) -> u64 {
+        self.latest_frame - self.deltas.len() as u64 * self.interval
+    }
+
+    /// Frames that can be stepped back
+    pub fn frames_count(&self) -> usize {
+        if self.latest.is_empty() { 0 } else { (self.frame - self.oldest_frame()) as usize }
+    }
+
+    /// To be called right before running each frame, once the controllers have their inputs
+    pub fn push_frame(&mut self, nes: &Nes) {
+        if self.latest.is_empty() || (self.frame.is_multiple_of(self.interval) && self.frame != self.latest_frame) {
+            self.push_snapshot(nes.save_state());
+        }
+
+        self.inputs.push_back(nes.get_pad_states());
+        self.frame += 1;
+    }
+
+    fn push_snapshot(&mut self, snapshot: Vec<u8>) {
+        // A different cartridge, nothing before can be loaded anymore
+        if snapshot.len() != self.latest.len() {
+            self.clear();
+        }
+
+        if !self.latest.is_empty() {
+            self.deltas.push_back(compress(&xor(&self.latest, &snapshot)));
+            if self.deltas.len() > self.capacity {
+                self.deltas.pop_front();
+                self.inputs.drain(..self.interval as usize);
+            }
+        }
+
+        self.latest = snapshot;
+        self.latest_frame = self.frame;
+    }
+
+    /// Puts `nes` back one frame earlier, returns false when the history is exhausted
+    pub fn step_back(&mut self, nes: &mut Nes) -> bool {
+        if self.frames_count() == 0 {
+            return false;
+        }
+        let target = self.frame - 1;
+
+        // The snapshot right before the target, so at least one frame is run and the screen is drawn
+        while self.latest_frame >= target {
+            let Some(delta) = self.deltas.pop_back() else {
+                break;
+            };
+            self.latest = xor(&self.latest, &decompress(&delta, self.latest.len()));
+            self.latest_frame -= self.interval;
+        }
+
+        if nes.load_state(&self.latest).is_err() {
+            self.clear();
+            return false;
+        }
+
+        let oldest_frame = self.oldest_frame();
+        for frame in self.latest_frame..target {
+            nes.set_pad_states(self.inputs[(frame - oldest_frame) as usize]);
+            nes.run_frame();
+        }
+
+        self.inputs.truncate((target - oldest_frame) as usize);
+        self.frame = target;
+        true
+    }
+}
+
+fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b).map(|(a, b)| a ^ b).collect()
+}
+
+/// Run-length encoding of the zeros: pairs of zero and literal counts, each followed by its literals
+fn compress(data: &[u8]) -> Vec<u8> {
+    let mut compressed = Vec::new();
+
+    let mut i = 0;
+    while i < data.len() {
+        let zeros = data[i..].iter().take(u16::MAX as usize).take_while(|&&byte| byte == 0).count();
+        i += zeros;
+        let literals = data[i..].iter().take(u16::MAX as usize).take_while(|&&byte| byte != 0).count();
+
+        compressed.extend_from_slice(&(zeros as u16).to_le_bytes());
+        compressed.extend_from_slice(&(literals as u16).to_le_bytes());
+        compressed.extend_from_slice(&data[i..i + literals]);
+        i += literals;
+    }
+
+    compressed
+}
+
+fn decompress(compressed: &[u8], len: usize) -> Vec<u8> {
+    let mut data = Vec::with_capacity(len);
+
+    let mut i = 0;
+    while i + 4 <= compressed.len() {
+        let zeros = u16::from_le_bytes([compressed[i], compressed[i + 1]]) as usize;
+        let literals = u16::from_le_bytes([compressed[i + 2], compressed[i + 3]]) as usize;
+        i += 4;
+
+        data.resize(data.len() + zeros, 0);
+        data.extend_from_slice(&compressed[i..i + literals]);
+        i += literals;
+    }
+
+    data.resize(len, 0);
+    data
+}
@@ -0,0 +1,234 @@
+use std::cell::Cell;
+use std::{fmt, io};
+
+use crate::nes::Mirror;
+
+pub const STATE_MAGIC: [u8; 4] = *b"RNST";
+/// Bumped whenever the layout changes, older states are refused
+pub const STATE_VERSION: u16 = 4;
+/// Magic, version and CRC32 of the cartridge
+pub const STATE_HEADER_SIZE: usize = 4 + 2 + 4;
+
+#[derive(Debug)]
+pub enum StateError {
+    Io(io::Error),
+    /// Not a save state
+    BadMagic,
+    UnsupportedVersion(u16),
+    /// The state was saved with another game, CRC32s of PRG+CHR
+    WrongCartridge { expected: u32, found: u32 },
+    /// The data ends early
+    Truncated,
+    /// There's more data after the last component, the layout doesn't match
+    TrailingData,
+    /// A buffer doesn't have the size the machine expects, like PRG-RAM from another board
+    SizeMismatch { expected: usize, found: usize },
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::BadMagic => write!(f, "not a save state"),
+            Self::UnsupportedVersion(version) => write!(f, "save state version {version} is not supported, expected {STATE_VERSION}"),
+            Self::WrongCartridge { expected, found } => write!(f, "save state is for another game, CRC32 {found:08X} instead of {expected:08X}"),
+            Self::Truncated => write!(f, "save state is truncated"),
+            Self::TrailingData => write!(f, "save state has data past its end"),
+            Self::SizeMismatch { expected, found } => write!(f, "save state doesn't fit the machine, expected {expected} bytes but found {found}"),
+        }
+    }
+}
+
+impl std::error::Error for StateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for StateError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// Components put their fields in the state in a fixed order, and read them back in the same order
+pub trait SaveState {
+    fn save_state(&self, state: &mut StateWriter);
+    fn load_state(&mut self, state: &mut StateReader<'_>) -> Result<(), StateError>;
+}
+
+/// Little-endian binary
+#[derive(Debug)]
+pub struct StateWriter {
+    data: Vec<u8>,
+}
+
+impl StateWriter {
+    pub const fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    pub fn write<T: StateValue>(&mut self, value: &T) {
+        value.write(self);
+    }
+
+    /// Length-prefixed, for buffers whose size depends on the cartridge
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write(&(bytes.len() as u64));
+        self.data.extend_from_slice(bytes);
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+#[derive(Debug)]
+pub struct StateReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> StateReader<'a> {
+    pub const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub fn read<T: StateValue>(&mut self) -> Result<T, StateError> {
+        T::read(self)
+    }
+
+    /// Reads what `write_bytes` wrote into `bytes`, which must have the same size
+    pub fn read_bytes_into(&mut self, bytes: &mut [u8]) -> Result<(), StateError> {
+        let len = self.read::<u64>()? as usize;
+        if len != bytes.len() {
+            return Err(StateError::SizeMismatch { expected: bytes.len(), found: len });
+        }
+
+        bytes.copy_from_slice(self.take(len)?);
+        Ok(())
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], StateError> {
+        if len > self.data.len() {
+            return Err(StateError::Truncated);
+        }
+
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+/// Values fields are made of
+pub trait StateValue: Sized {
+    fn write(&self, state: &mut StateWriter);
+    fn read(state: &mut StateReader<'_>) -> Result<Self, StateError>;
+}
+
+macro_rules! impl_state_value_for_numbers {
+    ($($number:ty),*) => {
+        $(
+            impl StateValue for $number {
+                fn write(&self, state: &mut StateWriter) {
+                    state.data.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn read(state: &mut StateReader<'_>) -> Result<Self, StateError> {
+                    let bytes = state.take(std::mem::size_of::<Self>())?;
+                    Ok(Self::from_le_bytes(bytes.try_into().unwrap_or_default()))
+                }
+            }
+        )*
+    };
+}
+
+impl_state_value_for_numbers!(u8, u16, u32, u64, u128, i8, i16, i32, f32);
+
+impl StateValue for bool {
+    fn write(&self, state: &mut StateWriter) {
+        state.write(&u8::from(*self));
+    }
+
+    fn read(state: &mut StateReader<'_>) -> Result<Self, StateError> {
+        Ok(state.read::<u8>()? != 0)
+    }
+}
+
+impl StateValue for usize {
+    fn write(&self, state: &mut StateWriter) {
+        state.write(&(*self as u64));
+    }
+
+    fn read(state: &mut StateReader<'_>) -> Result<Self, StateError> {
+        Ok(state.read::<u64>()? as Self)
+    }
+}
+
+impl<T: StateValue> StateValue for Option<T> {
+    fn write(&self, state: &mut StateWriter) {
+        state.write(&self.is_some());
+        if let Some(value) = self {
+            state.write(value);
+        }
+    }
+
+    fn read(state: &mut StateReader<'_>) -> Result<Self, StateError> {
+        Ok(if state.read::<bool>()? { Some(state.read()?) } else { None })
+    }
+}
+
+impl<T: StateValue, const N: usize> StateValue for [T; N] {
+    fn write(&self, state: &mut StateWriter) {
+        for value in self {
+            state.write(value);
+        }
+    }
+
+    fn read(state: &mut StateReader<'_>) -> Result<Self, StateError> {
+        let values = (0..N).map(|_| state.read()).collect::<Result<Vec<T>, _>>()?;
+
+        Ok(values.try_into().unwrap_or_else(|_| unreachable!("exactly N values were read")))
+    }
+}
+
+impl<T: StateValue + Copy> StateValue for Cell<T> {
+    fn write(&self, state: &mut StateWriter) {
+        state.write(&self.get());
+    }
+
+    fn read(state: &mut StateReader<'_>) -> Result<Self, StateError> {
+        Ok(Self::new(state.read()?))
+    }
+}
+
+impl StateValue for Mirror {
+    fn write(&self, state: &mut StateWriter) {
+        let value: u8 = match self {
+            Self::Hardware => 0,
+            Self::Horizontal => 1,
+            Self::Vertical => 2,
+            Self::OneScreenLo => 3,
+            Self::OneScreenHi => 4,
+            Self::FourScreen => 5,
+        };
+        state.write(&value);
+    }
+
+    fn read(state: &mut StateReader<'_>) -> Result<Self, StateError> {
+        Ok(match state.read::<u8>()? {
+            1 => Self::Horizontal,
+            2 => Self::Vertical,
+            3 => Self::OneScreenLo,
+            4 => Self::OneScreenHi,
+            5 => Self::FourScreen,
+            _ => Self::Hardware,
+        })
+    }
+}
@@ -0,0 +1,2504 @@
+#![allow(dead_code, unused_variables, non_snake_case)]
+
+#![cfg(test)]
+
+use serde::Deserialize;
+use super::*;
+
+#[derive(Debug, Deserialize)]
+pub struct TestState {
+    pub pc: u16,
+    pub s: u8,
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub p: u8,
+    pub ram: Vec<(u16, u8)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TestCycles {
+    address: u16,
+    value: u8,
+    operation: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TestEntry {
+    name: String,
+    initial: TestState,
+    r#final: TestState,
+    cycles: Vec<TestCycles>,
+}
+
+pub mod cycles_trace {
+    use lazy_static::lazy_static;
+    use std::sync::Mutex;
+
+    lazy_static! {
+        pub static ref CYCLES: Mutex<Vec<(u16, u8, String)>> = Mutex::new(Vec::new());
+    }
+}
+
+// --------------------------------- [ADC] --------------------------------- //
+
+#[test]
+fn ADC_immediate() {
+    run_json_test("tests/69.json");
+}
+
+#[test]
+fn ADC_zero_page() {
+    run_json_test("tests/65.json");
+}
+
+#[test]
+fn ADC_zero_page_x() {
+    run_json_test("tests/75.json");
+}
+
+#[test]
+fn ADC_absolute() {
+    run_json_test("tests/6d.json");
+}
+
+#[test]
+fn ADC_absolute_x() {
+    run_json_test("tests/7d.json");
+}
+
+#[test]
+fn ADC_absolute_y() {
+    run_json_test("tests/79.json");
+}
+
+#[test]
+fn ADC_indirect_x() {
+    run_json_test("tests/61.json");
+}
+
+#[test]
+fn ADC_indirect_y() {
+    run_json_test("tests/71.json");
+}
+
+// --------------------------------- [AND] --------------------------------- //
+
+#[test]
+fn AND_immediate() {
+    run_json_test("tests/29.json");
+}
+
+#[test]
+fn AND_zero_page() {
+    run_json_test("tests/25.json");
+}
+
+#[test]
+fn AND_zero_page_x() {
+    run_json_test("tests/35.json");
+}
+
+#[test]
+fn AND_absolute() {
+    run_json_test("tests/2d.json");
+}
+
+#[test]
+fn AND_absolute_x() {
+    run_json_test("tests/3d.json");
+}
+
+#[test]
+fn AND_absolute_y() {
+    run_json_test("tests/39.json");
+}
+
+#[test]
+fn AND_indirect_x() {
+    run_json_test("tests/21.json");
+}
+
+#[test]
+fn AND_indirect_y() {
+    run_json_test("tests/31.json");
+}
+
+// --------------------------------- [ASL] --------------------------------- //
+
+#[test]
+fn ASL_accumulator() {
+    run_json_test("tests/0a.json");
+}
+
+#[test]
+fn ASL_zero_page() {
+    run_json_test("tests/06.json");
+}
+
+#[test]
+fn ASL_zero_page_x() {
+    run_json_test("tests/16.json");
+}
+
+#[test]
+fn ASL_absolute() {
+    run_json_test("tests/0e.json");
+}
+
+#[test]
+fn ASL_absolute_x() {
+    run_json_test("tests/1e.json");
+}
+
+// --------------------------------- [BCC] --------------------------------- //
+
+#[test]
+fn BCC_relative() {
+    run_json_test("tests/90.json");
+}
+
+// --------------------------------- [BEQ] --------------------------------- //
+
+#[test]
+fn BEQ_relative() {
+    run_json_test("tests/f0.json");
+}
+
+// --------------------------------- [BIT] --------------------------------- //
+
+#[test]
+fn BIT_zero_page() {
+    run_json_test("tests/24.json");
+}
+
+#[test]
+fn BIT_absolute() {
+    run_json_test("tests/2c.json");
+}
+
+// --------------------------------- [BMI] --------------------------------- //
+
+#[test]
+fn BMI_relative() {
+    run_json_test("tests/30.json");
+}
+
+// --------------------------------- [BNE] --------------------------------- //
+
+#[test]
+fn BNE_relative() {
+    run_json_test("tests/d0.json");
+}
+
+// --------------------------------- [BPL] --------------------------------- //
+
+#[test]
+fn BPL_relative() {
+    run_json_test("tests/10.json");
+}
+
+// --------------------------------- [BRK] --------------------------------- //
+
+#[test]
+fn BRK_implied() {
+    run_json_test("tests/00.json");
+}
+
+// --------------------------------- [BVC] --------------------------------- //
+
+#[test]
+fn BVC_relative() {
+    run_json_test("tests/50.json");
+}
+
+// --------------------------------- [BVS] --------------------------------- //
+
+#[test]
+fn BVS_relative() {
+    run_json_test("tests/70.json");
+}
+
+// --------------------------------- [CLC] --------------------------------- //
+
+#[test]
+fn CLC_implied() {
+    run_json_test("tests/18.json");
+}
+
+// --------------------------------- [CLD] --------------------------------- //
+
+#[test]
+fn CLD_implied() {
+    run_json_test("tests/d8.json");
+}
+
+// --------------------------------- [CLI] --------------------------------- //
+
+#[test]
+fn CLI_implied() {
+    run_json_test("tests/58.json");
+}
+
+// --------------------------------- [CLV] --------------------------------- //
+
+#[test]
+fn CLV_implied() {
+    run_json_test("tests/b8.json");
+}
+
+// --------------------------------- [CMP] --------------------------------- //
+
+#[test]
+fn CMP_immediate() {
+    run_json_test("tests/c9.json");
+}
+
+#[test]
+fn CMP_zero_page() {
+    run_json_test("tests/c5.json");
+}
+
+#[test]
+fn CMP_zero_page_x() {
+    run_json_test("tests/d5.json");
+}
+
+#[test]
+fn CMP_absolute() {
+    run_json_test("tests/cd.json");
+}
+
+#[test]
+fn CMP_absolute_x() {
+    run_json_test("tests/dd.json");
+}
+
+#[test]
+fn CMP_absolute_y() {
+    run_json_test("tests/d9.json");
+}
+
+#[test]
+fn CMP_indirect_x() {
+    run_json_test("tests/c1.json");
+}
+
+#[test]
+fn CMP_indirect_y() {
+    run_json_test("tests/d1.json");
+}
+
+// --------------------------------- [CPX] --------------------------------- //
+
+#[test]
+fn CPX_immediate() {
+    run_json_test("tests/e0.json");
+}
+
+#[test]
+fn CPX_zero_page() {
+    run_json_test("tests/e4.json");
+}
+
+#[test]
+fn CPX_absolute() {
+    run_json_test("tests/ec.json");
+}
+
+// --------------------------------- [CPY] --------------------------------- //
+
+#[test]
+fn CPY_immediate() {
+    run_json_test("tests/c0.json");
+}
+
+#[test]
+fn CPY_zero_page() {
+    run_json_test("tests/c4.json");
+}
+
+#[test]
+fn CPY_absolute() {
+    run_json_test("tests/cc.json");
+}
+
+// --------------------------------- [DEC] --------------------------------- //
+
+#[test]
+fn DEC_zero_page() {
+    run_json_test("tests/c6.json");
+}
+
+#[test]
+fn DEC_zero_page_x() {
+    run_json_test("tests/d6.json");
+}
+
+#[test]
+fn DEC_absolute() {
+    run_json_test("tests/ce.json");
+}
+
+#[test]
+fn DEC_absolute_x() {
+    run_json_test("tests/de.json");
+}
+
+// --------------------------------- [DEX] --------------------------------- //
+
+#[test]
+fn DEX_implied() {
+    run_json_test("tests/ca.json");
+}
+
+// --------------------------------- [DEY] --------------------------------- //
+
+#[test]
+fn DEY_implied() {
+    run_json_test("tests/88.json");
+}
+
+// --------------------------------- [EOR] --------------------------------- //
+
+#[test]
+fn EOR_immediate() {
+    run_json_test("tests/49.json");
+}
+
+#[test]
+fn EOR_zero_page() {
+    run_json_test("tests/45.json");
+}
+
+#[test]
+fn EOR_zero_page_x() {
+    run_json_test("tests/55.json");
+}
+
+#[test]
+fn EOR_absolute() {
+    run_json_test("tests/4d.json");
+}
+
+#[test]
+fn EOR_absolute_x() {
+    run_json_test("tests/5d.json");
+}
+
+#[test]
+fn EOR_absolute_y() {
+    run_json_test("tests/59.json");
+}
+
+#[test]
+fn EOR_indirect_x() {
+    run_json_test("tests/41.json");
+}
+
+#[test]
+fn EOR_indirect_y() {
+    run_json_test("tests/51.json");
+}
+
+// --------------------------------- [INC] --------------------------------- //
+
+#[test]
+fn INC_zero_page() {
+    run_json_test("tests/e6.json");
+}
+
+#[test]
+fn INC_zero_page_x() {
+    run_json_test("tests/f6.json");
+}
+
+#[test]
+fn INC_absolute() {
+    run_json_test("tests/ee.json");
+}
+
+#[test]
+fn INC_absolute_x() {
+    run_json_test("tests/fe.json");
+}
+
+// --------------------------------- [INX] --------------------------------- //
+
+#[test]
+fn INX_implied() {
+    run_json_test("tests/e8.json");
+}
+
+// --------------------------------- [INY] --------------------------------- //
+
+#[test]
+fn INY_implied() {
+    run_json_test("tests/c8.json");
+}
+
+// --------------------------------- [JMP] --------------------------------- //
+
+#[test]
+fn JMP_absolute() {
+    run_json_test("tests/4c.json");
+}
+
+#[test]
+fn JMP_indirect() {
+    run_json_test("tests/6c.json");
+}
+
+// --------------------------------- [JSR] --------------------------------- //
+
+#[test]
+fn JSR_absolute() {
+    run_json_test("tests/20.json");
+}
+
+// --------------------------------- [LDA] --------------------------------- //
+
+#[test]
+fn LDA_immediate() {
+    run_json_test("tests/a9.json");
+}
+
+#[test]
+fn LDA_zero_page() {
+    run_json_test("tests/a5.json");
+}
+
+#[test]
+fn LDA_zero_page_x() {
+    run_json_test("tests/b5.json");
+}
+
+#[test]
+fn LDA_absolute() {
+    run_json_test("tests/ad.json");
+}
+
+#[test]
+fn LDA_absolute_x() {
+    run_json_test("tests/bd.json");
+}
+
+#[test]
+fn LDA_absolute_y() {
+    run_json_test("tests/b9.json");
+}
+
+#[test]
+fn LDA_indirect_x() {
+    run_json_test("tests/a1.json");
+}
+
+#[test]
+fn LDA_indirect_y() {
+    run_json_test("tests/b1.json");
+}
+
+// --------------------------------- [LDX] --------------------------------- //
+
+#[test]
+fn LDX_immediate() {
+    run_json_test("tests/a2.json");
+}
+
+#[test]
+fn LDX_zero_page() {
+    run_json_test("tests/a6.json");
+}
+
+#[test]
+fn LDX_zero_page_y() {
+    run_json_test("tests/b6.json");
+}
+
+#[test]
+fn LDX_absolute() {
+    run_json_test("tests/ae.json");
+}
+
+#[test]
+fn LDX_absolute_y() {
+    run_json_test("tests/be.json");
+}
+
+// --------------------------------- [LDY] --------------------------------- //
+
+#[test]
+fn LDY_immediate() {
+    run_json_test("tests/a0.json");
+}
+
+#[test]
+fn LDY_zero_page() {
+    run_json_test("tests/a4.json");
+}
+
+#[test]
+fn LDY_zero_page_x() {
+    run_json_test("tests/b4.json");
+}
+
+#[test]
+fn LDY_absolute() {
+    run_json_test("tests/ac.json");
+}
+
+#[test]
+fn LDY_absolute_x() {
+    run_json_test("tests/bc.json");
+}
+
+// --------------------------------- [LSR] --------------------------------- //
+
+#[test]
+fn LSR_accumulator() {
+    run_json_test("tests/4a.json");
+}
+
+#[test]
+fn LSR_zero_page() {
+    run_json_test("tests/46.json");
+}
+
+#[test]
+fn LSR_zero_page_x() {
+    run_json_test("tests/56.json");
+}
+
+#[test]
+fn LSR_absolutetmp() {
+    run_json_test("tests/4e.json");
+}
+
+#[test]
+fn LSR_absolute_x() {
+    run_json_test("tests/5e.json");
+}
+
+// --------------------------------- [NOP] --------------------------------- //
+
+#[test]
+fn NOP_implied() {
+    run_json_test("tests/ea.json");
+}
+
+// --------------------------------- [ORA] --------------------------------- //
+
+#[test]
+fn ORA_immediate() {
+    run_json_test("tests/09.json");
+}
+
+#[test]
+fn ORA_zero_page() {
+    run_json_test("tests/05.json");
+}
+
+#[test]
+fn ORA_zero_page_x() {
+    run_json_test("tests/15.json");
+}
+
+#[test]
+fn ORA_absolute() {
+    run_json_test("tests/0d.json");
+}
+
+#[test]
+fn ORA_absolute_x() {
+    run_json_test("tests/1d.json");
+}
+
+#[test]
+fn ORA_absolute_y() {
+    run_json_test("tests/19.json");
+}
+
+#[test]
+fn ORA_indirect_x() {
+    run_json_test("tests/01.json");
+}
+
+#[test]
+fn ORA_indirect_y() {
+    run_json_test("tests/11.json");
+}
+
+// --------------------------------- [PHA] --------------------------------- //
+
+#[test]
+fn PHA_implied() {
+    run_json_test("tests/48.json");
+}
+
+// --------------------------------- [PHP] --------------------------------- //
+
+#[test]
+fn PHP_implied() {
+    run_json_test("tests/08.json");
+}
+
+// --------------------------------- [PLA] --------------------------------- //
+
+#[test]
+fn PLA_implied() {
+    run_json_test("tests/68.json");
+}
+
+// --------------------------------- [PLP] --------------------------------- //
+
+#[test]
+fn PLP_implied() {
+    run_json_test("tests/28.json");
+}
+
+// --------------------------------- [ROL] --------------------------------- //
+
+#[test]
+fn ROL_accumulator() {
+    run_json_test("tests/2a.json");
+}
+
+#[test]
+fn ROL_zero_page() {
+    run_json_test("tests/26.json");
+}
+
+#[test]
+fn ROL_zero_page_x() {
+    run_json_test("tests/36.json");
+}
+
+#[test]
+fn ROL_absolute() {
+    run_json_test("tests/2e.json");
+}
+
+// --------------------------------- [ROR] --------------------------------- //
+
+#[test]
+fn ROR_accumulator() {
+    run_json_test("tests/6a.json");
+}
+
+#[test]
+fn ROR_zero_page() {
+    run_json_test("tests/66.json");
+}
+
+#[test]
+fn ROR_zero_page_x() {
+    run_json_test("tests/76.json");
+}
+
+#[test]
+fn ROR_absolute() {
+    run_json_test("tests/6e.json");
+}
+
+#[test]
+fn ROR_absolute_x() {
+    run_json_test("tests/7e.json");
+}
+
+// --------------------------------- [RTI] --------------------------------- //
+
+#[test]
+fn RTI_implied() {
+    run_json_test("tests/40.json");
+}
+
+// --------------------------------- [RTS] --------------------------------- //
+
+#[test]
+fn RTS_implied() {
+    run_json_test("tests/60.json");
+}
+
+// --------------------------------- [SBC] --------------------------------- //
+
+#[test]
+fn SBC_immediate() {
+    run_json_test("tests/e9.json");
+}
+
+#[test]
+fn SBC_zero_page() {
+    run_json_test("tests/e5.json");
+}
+
+#[test]
+fn SBC_zero_page_x() {
+    run_json_test("tests/f5.json");
+}
+
+#[test]
+fn SBC_absolute() {
+    run_json_test("tests/ed.json");
+}
+
+#[test]
+fn SBC_absolute_x() {
+    run_json_test("tests/fd.json");
+}
+
+#[test]
+fn SBC_absolute_y() {
+    run_json_test("tests/f9.json");
+}
+
+#[test]
+fn SBC_indirect_x() {
+    run_json_test("tests/e1.json");
+}
+
+#[test]
+fn SBC_indirect_y() {
+    run_json_test("tests/f1.json");
+}
+
+// --------------------------------- [SEC] --------------------------------- //
+
+#[test]
+fn SEC_implied() {
+    run_json_test("tests/38.json");
+}
+
+// --------------------------------- [SED] --------------------------------- //
+
+#[test]
+fn SED_implied() {
+    run_json_test("tests/f8.json");
+}
+
+// --------------------------------- [SEI] --------------------------------- //
+
+#[test]
+fn SEI_implied() {
+    run_json_test("tests/78.json");
+}
+
+// --------------------------------- [STA] --------------------------------- //
+
+#[test]
+fn STA_zero_page() {
+    run_json_test("tests/85.json");
+}
+
+#[test]
+fn STA_zero_page_x() {
+    run_json_test("tests/95.json");
+}
+
+#[test]
+fn STA_absolute() {
+    run_json_test("tests/8d.json");
+}
+
+#[test]
+fn STA_absolute_x() {
+    run_json_test("tests/9d.json");
+}
+
+#[test]
+fn STA_absolute_y() {
+    run_json_test("tests/99.json");
+}
+
+#[test]
+fn STA_indirect_x() {
+    run_json_test("tests/81.json");
+}
+
+#[test]
+fn STA_indirect_y() {
+    run_json_test("tests/91.json");
+}
+
+// --------------------------------- [STX] --------------------------------- //
+
+#[test]
+fn STX_zero_page() {
+    run_json_test("tests/86.json");
+}
+
+#[test]
+fn STX_zero_page_y() {
+    run_json_test("tests/96.json");
+}
+
+#[test]
+fn STX_absolute() {
+    run_json_test("tests/8e.json");
+}
+
+// --------------------------------- [STY] --------------------------------- //
+
+#[test]
+fn STY_zero_page() {
+    run_json_test("tests/84.json");
+}
+
+#[test]
+fn STY_zero_page_x() {
+    run_json_test("tests/94.json");
+}
+
+#[test]
+fn STY_absolute() {
+    run_json_test("tests/8c.json");
+}
+
+// --------------------------------- [TAX] --------------------------------- //
+
+#[test]
+fn TAX_implied() {
+    run_json_test("tests/aa.json");
+}
+
+// --------------------------------- [TAY] --------------------------------- //
+
+#[test]
+fn TAY_implied() {
+    run_json_test("tests/a8.json");
+}
+
+// --------------------------------- [TSX] --------------------------------- //
+
+#[test]
+fn TSX_implied() {
+    run_json_test("tests/ba.json");
+}
+
+// --------------------------------- [TXA] --------------------------------- //
+
+#[test]
+fn TXA_implied() {
+    run_json_test("tests/8a.json");
+}
+
+// --------------------------------- [TXS] --------------------------------- //
+
+#[test]
+fn TXS_implied() {
+    run_json_test("tests/9a.json");
+}
+
+// --------------------------------- [TYA] --------------------------------- //
+
+#[test]
+fn TYA_implied() {
+    run_json_test("tests/98.json");
+}
+
+// --------------------------------- [APU] --------------------------------- //
+
+#[test]
+fn APU_status_length_counters() {
+    let mut apu = nes::Component2A03::new();
+
+    apu.cpu_write(0x4015, 0x0F);
+    apu.cpu_write(0x4003, 0x08); // Pulse 1, length index 1
+    apu.cpu_write(0x400F, 0x08); // Noise, length index 1
+    assert_eq!(apu.cpu_read(0x4015, false) & 0x1F, 0x09);
+
+    // Disabling a channel clears its length counter
+    apu.cpu_write(0x4015, 0x08);
+    assert_eq!(apu.cpu_read(0x4015, false) & 0x1F, 0x08);
+}
+
+#[test]
+fn APU_frame_irq_4_step() {
+    let mut apu = nes::Component2A03::new();
+
+    for _ in 0..29_827 {
+        apu.tick();
+    }
+    assert_eq!(apu.cpu_read(0x4015, false) & 0x40, 0x00);
+
+    apu.tick();
+    assert_eq!(apu.cpu_read(0x4015, false) & 0x40, 0x40);
+    // Reading the status acknowledges the frame interrupt
+    assert_eq!(apu.cpu_read(0x4015, false) & 0x40, 0x00);
+}
+
+#[test]
+fn APU_frame_irq_inhibited() {
+    let mut apu = nes::Component2A03::new();
+
+    apu.cpu_write(0x4017, 0x40);
+    for _ in 0..40_000 {
+        apu.tick();
+    }
+    assert_eq!(apu.cpu_read(0x4015, false) & 0x40, 0x00);
+}
+
+#[test]
+fn APU_resampled_sample_count() {
+    let mut apu = nes::Component2A03::new();
+    apu.set_sample_rate(48_000);
+
+    // A tenth of a second of CPU cycles
+    for _ in 0..178_977 {
+        apu.tick();
+    }
+    let samples = apu.take_samples();
+    assert!((4795..=4800).contains(&samples.len()), "{} samples", samples.len());
+}
+
+#[test]
+fn APU_channel_volume() {
+    let mut apu = nes::Component2A03::new();
+
+    // An enabled triangle is heard in the mix, even before it is clocked, until its volume is set to 0
+    apu.cpu_write(0x4015, 0x04);
+    apu.cpu_write(0x4008, 0xFF);
+    apu.cpu_write(0x400B, 0x08);
+    assert!(apu.output() > 0.0);
+
+    apu.set_channel_volume(nes::Channel::Triangle, 0.0);
+    assert!(apu.output() == 0.0);
+}
+
+#[test]
+fn APU_wav_recording() {
+    let path = std::env::temp_dir().join("rustynes_apu_wav_recording.wav");
+    let mut apu = nes::Component2A03::new();
+
+    apu.start_recording(&path, true).unwrap();
+    for _ in 0..178_977 {
+        apu.tick();
+    }
+    apu.stop_recording().unwrap();
+
+    let wav = std::fs::read(&path).unwrap();
+    let data_size = u32::from_le_bytes(wav[40..44].try_into().unwrap());
+    assert_eq!(&wav[0..4], b"RIFF");
+    assert_eq!(u32::from_le_bytes(wav[4..8].try_into().unwrap()), 36 + data_size);
+    assert_eq!(wav.len(), 44 + data_size as usize);
+    // A tenth of a second at 44.1 kHz, minus the resampler latency
+    assert!((4400..=4410).contains(&(data_size / 2)), "{} samples", data_size / 2);
+
+    let stem = path.with_file_name("rustynes_apu_wav_recording_triangle.wav");
+    assert_eq!(std::fs::read(&stem).unwrap().len(), wav.len());
+
+    for channel in ["", "_pulse1", "_pulse2", "_triangle", "_noise", "_dmc", "_expansion"] {
+        let _ = std::fs::remove_file(path.with_file_name(format!("rustynes_apu_wav_recording{channel}.wav")));
+    }
+}
+
+// --------------------------------- [BUS] --------------------------------- //
+
+#[test]
+fn BUS_irq_line_shared() {
+    let mut bus = nes::Bus::new();
+
+    bus.set_irq(nes::IrqSource::ApuFrameCounter, true);
+    bus.set_irq(nes::IrqSource::Mapper, true);
+    assert!(bus.is_irq_asserted());
+
+    // The line stays asserted until every source releases it
+    bus.set_irq(nes::IrqSource::ApuFrameCounter, false);
+    assert!(bus.is_irq_asserted());
+    bus.set_irq(nes::IrqSource::Mapper, false);
+    assert!(!bus.is_irq_asserted());
+}
+
+/// Runs `program` at $8000 with interrupts disabled, IRQs jump to `handler` at $9000. The CPU sees the flat
+/// test memory, devices are reached through `Nes::test_bus_read` and `Nes::test_bus_write`
+fn irq_test_nes(cartridge: nes::ComponentCartridge, program: &[u8], handler: &[u8]) -> Nes {
+    let mut nes = Nes::new();
+    nes.insert_cartridge(cartridge);
+
+    let mut ram = vec![(0xFFFE, 0x00), (0xFFFF, 0x90)];
+    ram.extend(program.iter().enumerate().map(|(i, &data)| (0x8000 + i as u16, data)));
+    ram.extend(handler.iter().enumerate().map(|(i, &data)| (0x9000 + i as u16, data)));
+    nes.test_set_initial_state(&TestState { pc: 0x8000, s: 0xFD, a: 0, x: 0, y: 0, p: 0x24, ram });
+
+    nes
+}
+
+/// Ticks until the CPU enters the IRQ handler, `None` if it doesn't within `limit` ticks
+fn tick_until_irq(nes: &mut Nes, limit: usize) -> Option<usize> {
+    (0..limit).find(|_| {
+        nes.tick();
+        nes.get_cpu_info().program_counter >= 0x9000
+    })
+}
+
+#[test]
+fn BUS_irq_apu_frame_counter() {
+    // NOP, JMP $8000, the handler loops with interrupts disabled
+    let mut nes = irq_test_nes(synthetic_cartridge("bus_irq_apu_frame_counter", 0, 1, 1), &[0xEA, 0x4C, 0x00, 0x80], &[0x4C, 0x00, 0x90]);
+
+    // The frame IRQ is raised after a 4-step sequence, but I is set
+    assert_eq!(tick_until_irq(&mut nes, 100_000), None);
+
+    // The NOP becomes a CLI, the instruction after it still runs before the IRQ is taken
+    nes.test_write(0x8000, 0x58);
+    assert!(tick_until_irq(&mut nes, 100).is_some());
+    assert_eq!((nes.test_read(0x01FD), nes.test_read(0x01FC)), (0x80, 0x00));
+    assert_eq!(nes.get_cpu_info().stack_pointer, 0xFA);
+
+    // Reading $4015 acknowledges the frame IRQ
+    assert_eq!(nes.test_bus_read(0x4015) & 0x40, 0x40);
+    assert_eq!(nes.test_bus_read(0x4015) & 0x40, 0x00);
+}
+
+#[test]
+fn BUS_irq_mapper() {
+    // CLI, JMP $8001, the handler re-enables interrupts too: CLI, JMP $9001
+    let mut nes = irq_test_nes(synthetic_cartridge("bus_irq_mapper", 4, 2, 1), &[0x58, 0x4C, 0x01, 0x80], &[0x58, 0x4C, 0x01, 0x90]);
+
+    // MMC3 IRQ after 8 scanlines, with sprites at $1000 so A12 rises once per scanline while rendering
+    nes.test_bus_write(0xC000, 8);
+    nes.test_bus_write(0xC001, 0);
+    nes.test_bus_write(0xE001, 0);
+    nes.test_bus_write(0x2000, 0x08);
+    nes.test_bus_write(0x2001, 0x18);
+
+    assert!(tick_until_irq(&mut nes, 2 * 89_342).is_some());
+    assert_eq!(nes.get_cpu_info().stack_pointer, 0xFA);
+
+    // The line stays asserted until the mapper is acknowledged, so the IRQ is taken again once the handler clears I
+    for _ in 0..100 {
+        nes.tick();
+    }
+    assert!(nes.get_cpu_info().stack_pointer < 0xFA);
+
+    nes.test_bus_write(0xE000, 0);
+    let stack_pointer = nes.get_cpu_info().stack_pointer;
+    for _ in 0..10_000 {
+        nes.tick();
+    }
+    assert!(nes.get_cpu_info().stack_pointer >= stack_pointer - 3);
+}
+
+#[test]
+fn BUS_open_bus() {
+    let mut bus = nes::Bus::new();
+    let mut cartridge = nes::ComponentCartridge::new();
+    let mut ppu = nes::Component2C02::new();
+    let mut apu = nes::Component2A03::new();
+    let mut ports: nes::InputPorts = [Box::new(nes::Controller::new()), Box::new(nes::Controller::new())];
+
+    // Unmapped addresses and PPU write-only registers return the last value read
+    bus.cpu_write(0x0000, 0x7A, &mut ports, &mut cartridge, &mut ppu, &mut apu);
+    bus.cpu_write(0x0001, 0x00, &mut ports, &mut cartridge, &mut ppu, &mut apu);
+    assert_eq!(bus.cpu_read(0x0000, false, &mut ports, &cartridge, &mut ppu, &mut apu), 0x7A);
+    assert_eq!(bus.cpu_read(0x5000, false, &mut ports, &cartridge, &mut ppu, &mut apu), 0x7A);
+    assert_eq!(bus.cpu_read(0x2000, false, &mut ports, &cartridge, &mut ppu, &mut apu), 0x7A);
+    assert_eq!(bus.cpu_read(0x4018, false, &mut ports, &cartridge, &mut ppu, &mut apu), 0x7A);
+
+    // $4015 fills its bit 5 from the data bus without changing it
+    assert_eq!(bus.cpu_read(0x4015, false, &mut ports, &cartridge, &mut ppu, &mut apu), 0x20);
+    assert_eq!(bus.cpu_read(0x4018, false, &mut ports, &cartridge, &mut ppu, &mut apu), 0x7A);
+
+    // Writes drive the bus too, debugger reads leave it alone
+    bus.cpu_write(0x4000, 0x33, &mut ports, &mut cartridge, &mut ppu, &mut apu);
+    assert_eq!(bus.cpu_read(0x0001, true, &mut ports, &cartridge, &mut ppu, &mut apu), 0x00);
+    assert_eq!(bus.cpu_read(0x4018, false, &mut ports, &cartridge, &mut ppu, &mut apu), 0x33);
+}
+
+#[test]
+fn BUS_read_only_side_effects() {
+    let mut bus = nes::Bus::new();
+    let mut cartridge = nes::ComponentCartridge::new();
+    let mut ppu = nes::Component2C02::new();
+    let mut apu = nes::Component2A03::new();
+    let mut ports: nes::InputPorts = [Box::new(nes::Controller::new()), Box::new(nes::Controller::new())];
+    ports[0].set_buttons(0, nes::Controller::A | nes::Controller::B);
+    bus.cpu_write(0x4016, 0x01, &mut ports, &mut cartridge, &mut ppu, &mut apu);
+    bus.cpu_write(0x4016, 0x00, &mut ports, &mut cartridge, &mut ppu, &mut apu);
+
+    // Raises the frame IRQ flag
+    for _ in 0..29_828 {
+        apu.tick();
+    }
+
+    // Peeking doesn't shift the controller, acknowledge the frame IRQ or change the data bus
+    for _ in 0..3 {
+        assert_eq!(bus.cpu_read(0x4016, true, &mut ports, &cartridge, &mut ppu, &mut apu) & 0x01, 1);
+        assert_eq!(bus.cpu_read(0x4015, true, &mut ports, &cartridge, &mut ppu, &mut apu) & 0x40, 0x40);
+    }
+    assert_eq!(bus.open_bus, 0x00);
+
+    let port_1: Vec<u8> = (0..3).map(|_| bus.cpu_read(0x4016, false, &mut ports, &cartridge, &mut ppu, &mut apu) & 0x01).collect();
+    assert_eq!(port_1, [1, 1, 0]);
+    assert_eq!(bus.cpu_read(0x4015, false, &mut ports, &cartridge, &mut ppu, &mut apu) & 0x40, 0x40);
+    assert_eq!(bus.cpu_read(0x4015, false, &mut ports, &cartridge, &mut ppu, &mut apu) & 0x40, 0x00);
+}
+
+// -------------------------------- [INPUT] -------------------------------- //
+
+/// Bits 0-4 of `count` reads of `device`
+fn read_device(device: &mut dyn nes::InputDevice, count: usize) -> Vec<u8> {
+    (0..count).map(|_| device.read()).collect()
+}
+
+#[test]
+fn INPUT_ports_strobe() {
+    let mut bus = nes::Bus::new();
+    let mut cartridge = nes::ComponentCartridge::new();
+    let mut ppu = nes::Component2C02::new();
+    let mut apu = nes::Component2A03::new();
+    let mut ports: nes::InputPorts = [Box::new(nes::Controller::new()), Box::new(nes::Controller::new())];
+    ports[0].set_buttons(0, nes::Controller::A);
+    ports[1].set_buttons(0, nes::Controller::B);
+
+    // $4017 writes only reach the APU
+    bus.cpu_write(0x4017, 0x01, &mut ports, &mut cartridge, &mut ppu, &mut apu);
+    assert_eq!(bus.cpu_read(0x4017, false, &mut ports, &cartridge, &mut ppu, &mut apu) & 0x01, 0);
+
+    // While the strobe is high, reads keep returning the first button
+    bus.cpu_write(0x4016, 0x01, &mut ports, &mut cartridge, &mut ppu, &mut apu);
+    let port_1: Vec<u8> = (0..3).map(|_| bus.cpu_read(0x4016, false, &mut ports, &cartridge, &mut ppu, &mut apu) & 0x01).collect();
+    assert_eq!(port_1, [1, 1, 1]);
+
+    // Writing 0 ends the strobe on both ports, each is read at its own address, with 1s after the 8 buttons.
+    // The upper bits are open bus, $40 when the high byte of `LDA $4016` was the last value read
+    bus.cpu_write(0x4016, 0x00, &mut ports, &mut cartridge, &mut ppu, &mut apu);
+    ports[0].set_buttons(0, 0);
+    bus.open_bus = 0x40;
+    let port_1: Vec<u8> = (0..10).map(|_| bus.cpu_read(0x4016, false, &mut ports, &cartridge, &mut ppu, &mut apu)).collect();
+    let port_2: Vec<u8> = (0..10).map(|_| bus.cpu_read(0x4017, false, &mut ports, &cartridge, &mut ppu, &mut apu)).collect();
+    assert_eq!(port_1, [0x41, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x41, 0x41]);
+    assert_eq!(port_2, [0x40, 0x41, 0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x41, 0x41]);
+
+    // Writes with bit 0 clear don't reload the buttons
+    bus.cpu_write(0x4016, 0x02, &mut ports, &mut cartridge, &mut ppu, &mut apu);
+    bus.open_bus = 0x40;
+    assert_eq!(bus.cpu_read(0x4016, false, &mut ports, &cartridge, &mut ppu, &mut apu), 0x41);
+}
+
+#[test]
+fn INPUT_four_score() {
+    let mut nes = Nes::new();
+    nes.input_ports = [Box::new(nes::FourScore::new(0)), Box::new(nes::FourScore::new(1))];
+    nes.set_pad_states([nes::Controller::A, nes::Controller::START, nes::Controller::RIGHT, nes::Controller::B]);
+    assert_eq!(nes.get_pad_states(), [nes::Controller::A, nes::Controller::START, nes::Controller::RIGHT, nes::Controller::B]);
+
+    for device in &mut nes.input_ports {
+        device.write(0x01);
+        device.write(0x00);
+    }
+    // Controller 1, controller 3, the signature, then 1s
+    assert_eq!(read_device(nes.input_ports[0].as_mut(), 26), [
+        1, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 1,
+        0, 0, 0, 1, 0, 0, 0, 0,
+        1, 1,
+    ]);
+    assert_eq!(read_device(nes.input_ports[1].as_mut(), 24), [
+        0, 0, 0, 1, 0, 0, 0, 0,
+        0, 1, 0, 0, 0, 0, 0, 0,
+        0, 0, 1, 0, 0, 0, 0, 0,
+    ]);
+}
+
+#[test]
+fn INPUT_zapper() {
+    let mut zapper = nes::Zapper::new();
+    let mut screen = vec![nes::Pixel::new(0, 0, 0, 255); 256 * 240];
+    screen[100 * 256 + 50] = nes::Pixel::new(255, 255, 255, 255);
+    let draw_frame = |zapper: &mut nes::Zapper, screen: &[nes::Pixel]| {
+        (-1..=260).map(|scanline| {
+            zapper.ppu_scanline(scanline, screen);
+            zapper.read()
+        }).collect::<Vec<u8>>()
+    };
+
+    // Bit 3 clears for a while once the beam passed the white pixel it points at
+    zapper.set_pointer(Some((50, 100)), false);
+    let reads = draw_frame(&mut zapper, &screen);
+    assert_eq!(reads.iter().position(|&data| data & 0x08 == 0), Some(102));
+    assert!(reads[150..].iter().all(|&data| data == 0x08));
+
+    // Black, or off screen
+    zapper.set_pointer(Some((51, 100)), true);
+    assert!(draw_frame(&mut zapper, &screen).iter().all(|&data| data == 0x18));
+    zapper.set_pointer(None, false);
+    assert!(draw_frame(&mut zapper, &screen).iter().all(|&data| data == 0x08));
+}
+
+#[test]
+fn INPUT_arkanoid_paddle() {
+    let mut paddle = nes::ArkanoidPaddle::new();
+    paddle.set_position(0xA5);
+    paddle.set_pointer(None, true);
+    paddle.write(0x01);
+    paddle.write(0x00);
+
+    // Inverted knob value on bit 4, most significant bit first, the button on bit 3
+    let reads = read_device(&mut paddle, 8);
+    assert!(reads.iter().all(|&data| data & 0x08 != 0));
+    let value = reads.iter().fold(0_u8, |value, &data| (value << 1) | ((data >> 4) & 0x01));
+    assert_eq!(!value, 0xA5);
+
+    // The knob follows the pointer across the screen
+    paddle.set_pointer(Some((0, 10)), false);
+    let left = paddle.get_position();
+    paddle.set_pointer(Some((255, 10)), false);
+    assert!(paddle.get_position() > left);
+}
+
+// -------------------------------- [MAPPERS] -------------------------------- //
+
+/// iNES image whose PRG banks are filled with their 16K bank number and CHR banks with their 4K bank number
+fn synthetic_cartridge(name: &str, mapper_id: u8, prg_banks_count: u8, chr_banks_count: u8) -> nes::ComponentCartridge {
+    synthetic_cartridge_with_flags(name, mapper_id, prg_banks_count, chr_banks_count, 0x00)
+}
+
+fn synthetic_cartridge_with_flags(name: &str, mapper_id: u8, prg_banks_count: u8, chr_banks_count: u8, flags: u8) -> nes::ComponentCartridge {
+    load_rom_file(name, &synthetic_rom(mapper_id, prg_banks_count, chr_banks_count, flags)).unwrap()
+}
+
+/// Like `synthetic_cartridge`, with PRG filled with its 8K bank numbers and CHR with its 1K bank numbers, for
+/// mappers switching smaller banks
+fn synthetic_cartridge_small_banks(name: &str, mapper_id: u8, prg_banks_count: u8, chr_banks_count: u8) -> nes::ComponentCartridge {
+    let mut rom = synthetic_rom(mapper_id, prg_banks_count, chr_banks_count, 0x00);
+    let (prg_rom, chr_rom) = rom[16..].split_at_mut(prg_banks_count as usize * 16 * 1024);
+    fill_banks(prg_rom, 8 * 1024);
+    fill_banks(chr_rom, 1024);
+
+    load_rom_file(name, &rom).unwrap()
+}
+
+/// `flags` are the low bits of header byte 6 (mirroring, battery, trainer, four-screen)
+fn synthetic_rom(mapper_id: u8, prg_banks_count: u8, chr_banks_count: u8, flags: u8) -> Vec<u8> {
+    let mut rom = vec![b'N', b'E', b'S', 0x1A, prg_banks_count, chr_banks_count, (mapper_id << 4) | flags, mapper_id & 0xF0, 0, 0, 0, 0, 0, 0, 0, 0];
+    rom.resize(16 + (prg_banks_count as usize * 16 + chr_banks_count as usize * 8) * 1024, 0);
+    let (prg_rom, chr_rom) = rom[16..].split_at_mut(prg_banks_count as usize * 16 * 1024);
+    fill_banks(prg_rom, 16 * 1024);
+    fill_banks(chr_rom, 4 * 1024);
+
+    rom
+}
+
+fn fill_banks(data: &mut [u8], bank_size: usize) {
+    for (bank, data) in data.chunks_mut(bank_size).enumerate() {
+        data.fill(bank as u8);
+    }
+}
+
+/// Loads `rom` through a temporary file
+fn load_rom_file(name: &str, rom: &[u8]) -> Result<nes::ComponentCartridge, nes::CartridgeError> {
+    let path = std::env::temp_dir().join(format!("rustynes_{name}.nes"));
+    std::fs::write(&path, rom).unwrap();
+    let cartridge = nes::ComponentCartridge::from_path(path.to_str().unwrap());
+    std::fs::remove_file(&path).unwrap();
+
+    cartridge
+}
+
+fn cartridge_cpu_read(cartridge: &nes::ComponentCartridge, addr: u16) -> Option<u8> {
+    let mut data = 0;
+    cartridge.cpu_read(addr, false, &mut data).then_some(data)
+}
+
+fn cartridge_ppu_read(cartridge: &nes::ComponentCartridge, addr: u16) -> u8 {
+    let mut data = 0;
+    assert!(cartridge.ppu_read(addr, &mut data));
+    data
+}
+
+/// MMC1 registers are loaded serially, one bit per write
+fn mmc1_write(cartridge: &mut nes::ComponentCartridge, addr: u16, data: u8) {
+    for bit in 0..5 {
+        cartridge.cpu_write(addr, (data >> bit) & 0x01);
+    }
+}
+
+#[test]
+fn MAPPER_001_prg_modes() {
+    let mut cartridge = synthetic_cartridge("mapper_001_prg_modes", 1, 8, 1);
+
+    // Powers up with the last bank fixed at $C000
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xC000), Some(7));
+    mmc1_write(&mut cartridge, 0xE000, 2);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x8000), Some(2));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xFFFF), Some(7));
+
+    // First bank fixed at $8000
+    mmc1_write(&mut cartridge, 0x8000, 0x08);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x8000), Some(0));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xC000), Some(2));
+
+    // 32K mode ignores the low bit
+    mmc1_write(&mut cartridge, 0x8000, 0x00);
+    mmc1_write(&mut cartridge, 0xE000, 5);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x8000), Some(4));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xC000), Some(5));
+
+    // Writing with bit 7 set resets the shift register and goes back to the fixed last bank
+    cartridge.cpu_write(0x8000, 0x01);
+    cartridge.cpu_write(0x8000, 0x80);
+    mmc1_write(&mut cartridge, 0xE000, 3);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x8000), Some(3));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xC000), Some(7));
+}
+
+#[test]
+fn MAPPER_001_chr_modes_and_mirroring() {
+    let mut cartridge = synthetic_cartridge("mapper_001_chr_modes", 1, 2, 4);
+
+    // 4K mode, vertical mirroring
+    mmc1_write(&mut cartridge, 0x8000, 0x12);
+    mmc1_write(&mut cartridge, 0xA000, 3);
+    mmc1_write(&mut cartridge, 0xC000, 6);
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x0000), 3);
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x1000), 6);
+    assert_eq!(cartridge.mirror(), nes::Mirror::Vertical);
+
+    // 8K mode, one-screen mirroring
+    mmc1_write(&mut cartridge, 0x8000, 0x01);
+    mmc1_write(&mut cartridge, 0xA000, 5);
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x0000), 4);
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x1000), 5);
+    assert_eq!(cartridge.mirror(), nes::Mirror::OneScreenHi);
+}
+
+#[test]
+fn MAPPER_001_prg_ram() {
+    let mut cartridge = synthetic_cartridge("mapper_001_prg_ram", 1, 2, 1);
+
+    cartridge.cpu_write(0x6000, 0x42);
+    cartridge.cpu_write(0x7FFF, 0x24);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x6000), Some(0x42));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x7FFF), Some(0x24));
+
+    // Disabled through bit 4 of the PRG bank register
+    mmc1_write(&mut cartridge, 0xE000, 0x10);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x6000), None);
+}
+
+#[test]
+fn MAPPER_004_prg_banks() {
+    let mut cartridge = synthetic_cartridge_small_banks("mapper_004_prg_banks", 4, 8, 1);
+
+    cartridge.cpu_write(0x8000, 6);
+    cartridge.cpu_write(0x8001, 3);
+    cartridge.cpu_write(0x8000, 7);
+    cartridge.cpu_write(0x8001, 9);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x8000), Some(3));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xA000), Some(9));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xC000), Some(14));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xE000), Some(15));
+
+    // PRG inversion swaps $8000 and $C000
+    cartridge.cpu_write(0x8000, 0x46);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x8000), Some(14));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xC000), Some(3));
+}
+
+#[test]
+fn MAPPER_004_prg_ram_protect() {
+    let mut cartridge = synthetic_cartridge("mapper_004_prg_ram", 4, 2, 1);
+
+    cartridge.cpu_write(0x6000, 0x11);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x6000), Some(0x11));
+
+    // Write protected
+    cartridge.cpu_write(0xA001, 0xC0);
+    cartridge.cpu_write(0x6000, 0x22);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x6000), Some(0x11));
+
+    // Disabled
+    cartridge.cpu_write(0xA001, 0x00);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x6000), None);
+
+    cartridge.cpu_write(0xA001, 0x80);
+    cartridge.cpu_write(0x6000, 0x33);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x6000), Some(0x33));
+
+    // A reset enables it again, without write protection
+    cartridge.cpu_write(0xA001, 0x40);
+    cartridge.reset();
+    cartridge.cpu_write(0x6000, 0x44);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x6000), Some(0x44));
+}
+
+#[test]
+fn MAPPER_004_chr_banks_and_mirroring() {
+    let mut cartridge = synthetic_cartridge_small_banks("mapper_004_chr_banks", 4, 2, 4);
+
+    for (register, bank) in [(0, 9), (1, 20), (2, 3), (3, 4), (4, 5), (5, 31)] {
+        cartridge.cpu_write(0x8000, register);
+        cartridge.cpu_write(0x8001, bank);
+    }
+    // 2K banks ignore the low bit
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x0000), 8);
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x0400), 9);
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x0C00), 21);
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x1C00), 31);
+
+    // CHR inversion swaps the pattern table halves
+    cartridge.cpu_write(0x8000, 0x80);
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x0000), 3);
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x1000), 8);
+
+    cartridge.cpu_write(0xA000, 0x01);
+    assert_eq!(cartridge.mirror(), nes::Mirror::Horizontal);
+
+    // The header mirroring holds until the game writes $A000
+    let mut cartridge = synthetic_cartridge_with_flags("mapper_004_header_mirroring", 4, 2, 1, 0x01);
+    assert_eq!(cartridge.mirror(), nes::Mirror::Vertical);
+    cartridge.cpu_write(0xA000, 0x01);
+    cartridge.reset();
+    assert_eq!(cartridge.mirror(), nes::Mirror::Vertical);
+    let cartridge = synthetic_cartridge_with_flags("mapper_004_header_mirroring", 4, 2, 1, 0x00);
+    assert_eq!(cartridge.mirror(), nes::Mirror::Horizontal);
+}
+
+#[test]
+fn MAPPER_004_scanline_irq() {
+    let mut cartridge = synthetic_cartridge("mapper_004_scanline_irq", 4, 2, 1);
+    // A12 rises once per scanline, like with the background at $0000 and sprites at $1000
+    let scanline = |cartridge: &mut nes::ComponentCartridge| {
+        for _ in 0..300 {
+            cartridge.ppu_tick(0x0000);
+        }
+        for _ in 0..41 {
+            cartridge.ppu_tick(0x1000);
+        }
+    };
+
+    cartridge.cpu_write(0xC000, 2);
+    cartridge.cpu_write(0xC001, 0);
+    cartridge.cpu_write(0xE001, 0);
+
+    scanline(&mut cartridge);
+    scanline(&mut cartridge);
+    assert!(!cartridge.is_irq_asserted());
+    scanline(&mut cartridge);
+    assert!(cartridge.is_irq_asserted());
+
+    // Acknowledged by writing to $E000
+    cartridge.cpu_write(0xE000, 0);
+    assert!(!cartridge.is_irq_asserted());
+
+    // Short low periods, like between background fetches, are filtered out
+    cartridge.cpu_write(0xE001, 0);
+    for _ in 0..100 {
+        cartridge.ppu_tick(0x0000);
+        cartridge.ppu_tick(0x1000);
+    }
+    assert!(!cartridge.is_irq_asserted());
+}
+
+#[test]
+fn MAPPER_003_chr_banks() {
+    let mut cartridge = synthetic_cartridge_small_banks("mapper_003_chr_banks", 3, 1, 4);
+
+    // 16K PRG is mirrored
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x8000), Some(0));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xC000), Some(0));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xE000), Some(1));
+
+    cartridge.cpu_write(0x8000, 2);
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x0000), 16);
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x1C00), 23);
+    cartridge.cpu_write(0xFFFF, 3);
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x0400), 25);
+
+    // Writes only select the bank, PRG ROM is unchanged
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xFFFF), Some(1));
+}
+
+#[test]
+fn MAPPER_007_prg_banks_and_mirroring() {
+    let mut cartridge = synthetic_cartridge_small_banks("mapper_007_prg_banks", 7, 8, 0);
+    let mut ppu = nes::Component2C02::new();
+
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x8000), Some(0));
+    cartridge.cpu_write(0x8000, 0x02);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x8000), Some(8));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xE000), Some(11));
+    assert_eq!(cartridge.mirror(), nes::Mirror::OneScreenLo);
+
+    // All four nametables show the same page
+    ppu.ppu_write(0x2000, 0xAA, &mut cartridge);
+    assert_eq!(ppu.ppu_read(0x2C00, false, &cartridge), 0xAA);
+
+    cartridge.cpu_write(0x8000, 0x13);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x8000), Some(12));
+    assert_eq!(cartridge.mirror(), nes::Mirror::OneScreenHi);
+    assert_eq!(ppu.ppu_read(0x2000, false, &cartridge), 0x00);
+    ppu.ppu_write(0x2400, 0x55, &mut cartridge);
+    assert_eq!(ppu.ppu_read(0x2800, false, &cartridge), 0x55);
+
+    cartridge.cpu_write(0x8000, 0x00);
+    assert_eq!(ppu.ppu_read(0x2400, false, &cartridge), 0xAA);
+
+    // A 16K image is mirrored in both halves
+    let mut cartridge = synthetic_cartridge_small_banks("mapper_007_prg_16k", 7, 1, 0);
+    cartridge.cpu_write(0x8000, 0x07);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x8000), Some(0));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xA000), Some(1));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xC000), Some(0));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xFFFF), Some(1));
+}
+
+#[test]
+fn MAPPER_066_prg_and_chr_banks() {
+    let mut cartridge = synthetic_cartridge_small_banks("mapper_066_banks", 66, 8, 4);
+
+    cartridge.cpu_write(0x8000, 0x21);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x8000), Some(8));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xFFFF), Some(11));
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x0000), 8);
+
+    cartridge.cpu_write(0x8000, 0x13);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x8000), Some(4));
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x1C00), 31);
+
+    // A 16K image is mirrored in both halves
+    let mut cartridge = synthetic_cartridge_small_banks("mapper_066_prg_16k", 66, 1, 1);
+    cartridge.cpu_write(0x8000, 0x30);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x8000), Some(0));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xC000), Some(0));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xFFFF), Some(1));
+}
+
+// ------------------------------- [CARTRIDGE] ------------------------------- //
+
+/// Writes `header` followed by zeroed PRG and CHR of the given sizes, then loads it
+fn cartridge_from_header(name: &str, header: [u8; 16], prg_size: usize, chr_size: usize) -> nes::ComponentCartridge {
+    let mut rom = header.to_vec();
+    rom.resize(16 + prg_size + chr_size, 0);
+
+    load_rom_file(name, &rom).unwrap()
+}
+
+#[test]
+fn CARTRIDGE_ines_header() {
+    let cartridge = cartridge_from_header("ines_header", [b'N', b'E', b'S', 0x1A, 2, 0, 0x13, 0x00, 0, 0x01, 0, 0, 0, 0, 0, 0], 32 * 1024, 0);
+    let info = cartridge.info();
+
+    assert_eq!(info.format, nes::HeaderFormat::INes);
+    assert_eq!(info.mapper_id, 1);
+    assert_eq!(info.prg_rom_size, 32 * 1024);
+    assert_eq!(info.chr_rom_size, 0);
+    assert_eq!(info.chr_ram_size, 8 * 1024);
+    assert_eq!((info.prg_ram_size, info.prg_nvram_size), (0, 8 * 1024));
+    assert_eq!(info.mirror, nes::Mirror::Vertical);
+    assert!(info.has_battery);
+    assert!(!info.has_trainer);
+    assert_eq!(info.timing, nes::Timing::Pal);
+    assert_eq!(info.console_type, nes::ConsoleType::Nes);
+
+    // Garbage in bytes 7-15 from old dumping tools, the upper mapper nibble is ignored
+    let cartridge = cartridge_from_header("ines_dirty_header", *b"NES\x1a\x01\x01\x10DiskDude!", 16 * 1024, 8 * 1024);
+    assert_eq!(cartridge.info().mapper_id, 1);
+    assert_eq!(cartridge.info().prg_ram_size, 8 * 1024);
+    assert_eq!(cartridge.info().timing, nes::Timing::Ntsc);
+}
+
+#[test]
+fn CARTRIDGE_nes2_header() {
+    let header = [b'N', b'E', b'S', 0x1A, 8, 0, 0x48, 0x09, 0x10, 0x00, 0x70, 0x07, 0x03, 0x00, 0x01, 0x01];
+    let cartridge = cartridge_from_header("nes2_header", header, 128 * 1024, 0);
+    let info = cartridge.info();
+
+    assert_eq!(info.format, nes::HeaderFormat::Nes2);
+    assert_eq!((info.mapper_id, info.submapper_id), (4, 1));
+    assert_eq!(info.prg_rom_size, 128 * 1024);
+    assert_eq!((info.prg_ram_size, info.prg_nvram_size), (0, 8 * 1024));
+    assert_eq!((info.chr_ram_size, info.chr_nvram_size), (8 * 1024, 0));
+    assert_eq!(info.mirror, nes::Mirror::FourScreen);
+    assert_eq!(info.timing, nes::Timing::Dendy);
+    assert_eq!(info.console_type, nes::ConsoleType::VsSystem);
+    assert_eq!(info.expansion_device, 1);
+    assert_eq!(info.misc_roms_count, 1);
+
+    // Mapper numbers above 255 and extended console types
+    let header = [b'N', b'E', b'S', 0x1A, 1, 1, 0x20, 0x1B, 0x21, 0x00, 0x00, 0x00, 0x02, 0x05, 0x00, 0x00];
+    let info = nes::CartridgeInfo::from_header(&nes::HeaderCartridge::from_bytes(&header));
+    assert_eq!((info.mapper_id, info.submapper_id), (0x112, 2));
+    assert_eq!(info.timing, nes::Timing::MultiRegion);
+    assert_eq!(info.console_type, nes::ConsoleType::Extended(5));
+}
+
+#[test]
+fn CARTRIDGE_nes2_exponent_rom_size() {
+    // 2^15 * 1 bytes of PRG, 2^13 * 3 bytes of CHR
+    let header = [b'N', b'E', b'S', 0x1A, 15 << 2, (13 << 2) | 0x01, 0x00, 0x08, 0x00, 0xFF, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let cartridge = cartridge_from_header("nes2_exponent_rom_size", header, 32 * 1024, 24 * 1024);
+    let info = cartridge.info();
+
+    assert_eq!(info.prg_rom_size, 32 * 1024);
+    assert_eq!(info.chr_rom_size, 24 * 1024);
+    assert_eq!(info.prg_banks_count(), 2);
+    assert_eq!(info.chr_banks_count(), 3);
+}
+
+#[test]
+fn CARTRIDGE_nes2_exponent_rom_size_too_large() {
+    // 2^63 bytes of PRG, more than a Vec can hold
+    let header = [b'N', b'E', b'S', 0x1A, 0xFC, 0x0F, 0x00, 0x08, 0x00, 0x0F, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut rom = header.to_vec();
+    rom.resize(16 + 16 * 1024, 0);
+    assert!(matches!(load_rom_file("nes2_exponent_overflow", &rom), Err(nes::CartridgeError::TruncatedPrg { found: 0x4000, .. })));
+
+    // 2^40 bytes of PRG, 1 TiB
+    rom[4] = 40 << 2;
+    assert!(matches!(
+        load_rom_file("nes2_exponent_huge", &rom),
+        Err(nes::CartridgeError::TruncatedPrg { expected: 0x100_0000_0000, found: 0x4000 })
+    ));
+}
+
+#[test]
+fn CARTRIDGE_errors() {
+    let header = [b'N', b'E', b'S', 0x1A, 2, 1, 0x00, 0x00, 0, 0, 0, 0, 0, 0, 0, 0];
+    let mut rom = header.to_vec();
+    rom.resize(16 + 32 * 1024 + 8 * 1024, 0);
+
+    let missing = std::env::temp_dir().join("rustynes_missing_rom.nes");
+    assert!(matches!(nes::ComponentCartridge::from_path(missing.to_str().unwrap()), Err(nes::CartridgeError::Io(_))));
+
+    assert!(matches!(load_rom_file("bad_magic", b"NES\x00"), Err(nes::CartridgeError::BadMagic)));
+    assert!(matches!(load_rom_file("bad_magic_short", b"NE"), Err(nes::CartridgeError::BadMagic)));
+    assert!(matches!(
+        load_rom_file("truncated_prg", &rom[..16 + 1000]),
+        Err(nes::CartridgeError::TruncatedPrg { expected: 0x8000, found: 1000 })
+    ));
+    assert!(matches!(
+        load_rom_file("truncated_chr", &rom[..rom.len() - 1]),
+        Err(nes::CartridgeError::TruncatedChr { expected: 0x2000, found: 0x1FFF })
+    ));
+
+    let mut no_prg = rom.clone();
+    no_prg[4] = 0;
+    assert!(matches!(load_rom_file("no_prg", &no_prg), Err(nes::CartridgeError::Malformed(_))));
+
+    rom[6] = 0x50;
+    rom[7] = 0x00;
+    assert!(matches!(load_rom_file("unsupported_mapper", &rom), Err(nes::CartridgeError::UnsupportedMapper(5))));
+}
+
+#[test]
+fn CARTRIDGE_prg_ram_size() {
+    // NES 2.0 with no PRG-RAM
+    let header = [b'N', b'E', b'S', 0x1A, 1, 1, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut cartridge = cartridge_from_header("no_prg_ram", header, 16 * 1024, 8 * 1024);
+    assert!(!cartridge.cpu_write(0x6000, 0x42));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x6000), None);
+
+    // 2K of PRG-RAM, mirrored through the window
+    let header = [b'N', b'E', b'S', 0x1A, 1, 1, 0x00, 0x08, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut cartridge = cartridge_from_header("small_prg_ram", header, 16 * 1024, 8 * 1024);
+    assert!(cartridge.cpu_write(0x6001, 0x42));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x6801), Some(0x42));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x7801), Some(0x42));
+
+    // iNES always gets 8K
+    let mut cartridge = synthetic_cartridge("ines_prg_ram", 0, 1, 1);
+    cartridge.cpu_write(0x7FFF, 0x24);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x7FFF), Some(0x24));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x67FF), Some(0x00));
+}
+
+#[test]
+fn CARTRIDGE_battery_save() {
+    let save_path = std::env::temp_dir().join("rustynes_battery_save.sav");
+    let _ = std::fs::remove_file(&save_path);
+
+    let mut cartridge = synthetic_cartridge_with_flags("battery_save", 1, 2, 1, 0x02);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x6123), Some(0x00));
+    cartridge.cpu_write(0x6123, 0x99);
+    cartridge.save_battery().unwrap();
+
+    let save = std::fs::read(&save_path).unwrap();
+    assert_eq!(save.len(), 8 * 1024);
+    assert_eq!(save[0x0123], 0x99);
+
+    // Reloaded along with the ROM
+    let cartridge = synthetic_cartridge_with_flags("battery_save", 1, 2, 1, 0x02);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x6123), Some(0x99));
+    std::fs::remove_file(&save_path).unwrap();
+
+    // Nothing is written without a battery
+    let cartridge = synthetic_cartridge("battery_save", 1, 2, 1);
+    cartridge.save_battery().unwrap();
+    assert!(!save_path.exists());
+}
+
+#[test]
+fn CARTRIDGE_trainer() {
+    let mut rom = vec![b'N', b'E', b'S', 0x1A, 1, 1, 0x04, 0x00, 0, 0, 0, 0, 0, 0, 0, 0];
+    rom.extend((0..512).map(|i| (i % 251) as u8 + 1));
+    rom.extend(std::iter::repeat_n(0xEA, 16 * 1024));
+    rom.extend(std::iter::repeat_n(0x00, 8 * 1024));
+
+    let cartridge = load_rom_file("trainer", &rom).unwrap();
+    assert!(cartridge.info().has_trainer);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x6FFF), Some(0x00));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x7000), Some(1));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x71FF), Some((511 % 251) as u8 + 1));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x7200), Some(0x00));
+    // PRG ROM starts after the trainer
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x8000), Some(0xEA));
+
+    // NES 2.0 without PRG-RAM still gets room for the trainer
+    rom[7] = 0x08;
+    let cartridge = load_rom_file("trainer_nes2", &rom).unwrap();
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x7000), Some(1));
+
+    assert!(matches!(
+        load_rom_file("truncated_trainer", &rom[..16 + 100]),
+        Err(nes::CartridgeError::TruncatedTrainer { expected: 512, found: 100 })
+    ));
+}
+
+/// Zip archive holding `files`, deflated
+fn zip_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+    use std::io::Write;
+
+    let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (name, data) in files {
+        archive.start_file(*name, zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated)).unwrap();
+        archive.write_all(data).unwrap();
+    }
+
+    archive.finish().unwrap().into_inner()
+}
+
+#[test]
+fn CARTRIDGE_from_bytes_and_reader() {
+    let rom = synthetic_rom(4, 8, 2, 0x00);
+
+    let cartridge = nes::ComponentCartridge::from_bytes(&rom).unwrap();
+    assert_eq!(cartridge.info().mapper_id, 4);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xE000), Some(7));
+
+    // Reading starts from the current position
+    let mut buffer = vec![0xFF; 100];
+    buffer.extend(&rom);
+    let mut reader = std::io::Cursor::new(buffer);
+    reader.set_position(100);
+    let cartridge = nes::ComponentCartridge::from_reader(reader).unwrap();
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x1C00), 1);
+
+    assert!(matches!(nes::ComponentCartridge::from_bytes(&rom[..20]), Err(nes::CartridgeError::TruncatedPrg { .. })));
+}
+
+#[test]
+fn CARTRIDGE_zip_archive() {
+    let rom = synthetic_rom(1, 2, 1, 0x02);
+    let archive = zip_archive(&[("readme.txt", b"Not a ROM"), ("roms/Game.NES", &rom), ("other.nes", b"Not this one")]);
+
+    let cartridge = nes::ComponentCartridge::from_bytes(&archive).unwrap();
+    assert_eq!(cartridge.info().mapper_id, 1);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xC000), Some(1));
+
+    // Battery saves go next to the archive
+    let path = std::env::temp_dir().join("rustynes_zip_archive.zip");
+    let save_path = path.with_extension("sav");
+    std::fs::write(&path, &archive).unwrap();
+    let mut cartridge = nes::ComponentCartridge::from_path(path.to_str().unwrap()).unwrap();
+    cartridge.cpu_write(0x6000, 0x5A);
+    cartridge.save_battery().unwrap();
+    assert_eq!(std::fs::read(&save_path).unwrap()[0], 0x5A);
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&save_path).unwrap();
+
+    let archive = zip_archive(&[("readme.txt", b"Not a ROM")]);
+    assert!(matches!(nes::ComponentCartridge::from_bytes(&archive), Err(nes::CartridgeError::NoRomInArchive)));
+    assert!(matches!(nes::ComponentCartridge::from_bytes(&archive[..30]), Err(nes::CartridgeError::Zip(_))));
+}
+
+#[test]
+fn CARTRIDGE_game_database() {
+    // Every line of the built-in database parses
+    let lines = include_str!("nes/cartridge/database.txt").lines().filter(|line| !line.trim().is_empty() && !line.starts_with('#')).count();
+    assert_eq!(nes::GameDatabase::built_in().entries().len(), lines);
+
+    let rom = synthetic_rom(0, 2, 1, 0x00);
+    let (prg_rom, chr_rom) = rom[16..].split_at(32 * 1024);
+    let crc32 = nes::GameDatabase::crc32(prg_rom, chr_rom);
+
+    // Unknown games keep their header
+    let cartridge = nes::ComponentCartridge::from_bytes(&rom).unwrap();
+    assert_eq!(cartridge.info().crc32, crc32);
+    assert!(!cartridge.info().is_database_match);
+
+    let sha1 = "0000000000000000000000000000000000000000";
+    let database = nes::GameDatabase::parse(&format!("
+        # Comment
+        {crc32:08X} - 4.1 - 0 8192 0 0 PAL Synthetic
+        {crc32:08X} {sha1} 7.0 H 0 0 0 0 NTSC Same CRC32, other SHA-1
+        {:08X} - 1.0 V 8192 0 0 0 NTSC Other game
+        {crc32:08X} - bad line
+    ", crc32 ^ 1));
+    assert_eq!(database.entries().len(), 3);
+    assert!(database.find(crc32 ^ 2, prg_rom, chr_rom).is_none());
+
+    let entry = database.find(crc32, prg_rom, chr_rom).unwrap();
+    assert_eq!(entry.name, "Synthetic");
+    let mut info = *cartridge.info();
+    entry.apply(&mut info);
+    assert_eq!((info.mapper_id, info.submapper_id), (4, 1));
+    // The mapper controls mirroring, the header's is kept
+    assert_eq!(info.mirror, nes::Mirror::Horizontal);
+    assert_eq!((info.prg_ram_size, info.prg_nvram_size), (0, 8 * 1024));
+    assert!(info.has_battery);
+    assert_eq!(info.timing, nes::Timing::Pal);
+    assert!(info.is_database_match);
+    assert_eq!((info.prg_rom_size, info.chr_rom_size), (32 * 1024, 8 * 1024));
+
+    // The SHA-1 has to match when there is one
+    let database = nes::GameDatabase::parse(&format!("{crc32:08X} {sha1} 7.0 H 0 0 0 0 NTSC Collision"));
+    assert!(database.find(crc32, prg_rom, chr_rom).is_none());
+    let sha1 = sha1_smol::Sha1::from([prg_rom, chr_rom].concat()).digest().to_string();
+    let database = nes::GameDatabase::parse(&format!("{crc32:08X} {sha1} 7.0 H 0 0 0 0 NTSC Match"));
+    assert_eq!(database.find(crc32, prg_rom, chr_rom).map(|entry| entry.mapper_id), Some(7));
+
+    // A database file given when loading
+    let database_path = std::env::temp_dir().join("rustynes_game_database.txt");
+    std::fs::write(&database_path, format!("{crc32:08X} - 2.0 V 0 0 0 0 NTSC From a file")).unwrap();
+    let options = nes::LoadOptions { database_path: Some(database_path.clone()), ..Default::default() };
+    let cartridge = nes::ComponentCartridge::from_reader_with_options(std::io::Cursor::new(&rom), &options).unwrap();
+    std::fs::remove_file(&database_path).unwrap();
+    assert!(cartridge.info().is_database_match);
+    assert_eq!((cartridge.info().mapper_id, cartridge.info().mirror), (2, nes::Mirror::Vertical));
+    assert!(matches!(nes::ComponentCartridge::from_reader_with_options(std::io::Cursor::new(&rom), &options), Err(nes::CartridgeError::Database(..))));
+}
+
+/// `chunks` are IDs with their data, after the 32-byte header
+fn unif_image(chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+    let mut image = b"UNIF".to_vec();
+    image.extend(7u32.to_le_bytes());
+    image.resize(32, 0);
+    for (id, data) in chunks {
+        image.extend(*id);
+        image.extend((data.len() as u32).to_le_bytes());
+        image.extend(*data);
+    }
+
+    image
+}
+
+#[test]
+fn CARTRIDGE_unif() {
+    let prg0 = vec![0xA0; 16 * 1024];
+    let prg1 = vec![0xA1; 16 * 1024];
+    let chr0 = vec![0xC0; 8 * 1024];
+    let image = unif_image(&[
+        (b"NAME", b"Synthetic\0"),
+        (b"MAPR", b"NES-SLROM\0"),
+        // Chunks are ordered by their number, not by where they are in the file
+        (b"PRG1", &prg1),
+        (b"PRG0", &prg0),
+        (b"CHR0", &chr0),
+        (b"MIRR", &[1]),
+        (b"BATR", &[1]),
+        (b"TVCI", &[1]),
+    ]);
+
+    let cartridge = nes::ComponentCartridge::from_bytes(&image).unwrap();
+    let info = cartridge.info();
+    assert_eq!(info.format, nes::HeaderFormat::Unif);
+    assert_eq!(info.mapper_id, 1);
+    assert_eq!((info.prg_rom_size, info.chr_rom_size, info.prg_nvram_size), (32 * 1024, 8 * 1024, 8 * 1024));
+    assert_eq!(info.mirror, nes::Mirror::Vertical);
+    assert!(info.has_battery);
+    assert_eq!(info.timing, nes::Timing::Pal);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x8000), Some(0xA0));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xC000), Some(0xA1));
+    assert_eq!(cartridge_ppu_read(&cartridge, 0x0000), 0xC0);
+
+    // Board names resolve to mappers, with or without their prefix
+    for (board, mapper_id) in [("NES-NROM-256", 0), ("UNROM", 2), ("HVC-CNROM", 3), ("NES-TLROM", 4), ("NES-AOROM", 7), ("NES-GNROM", 66)] {
+        let image = unif_image(&[(b"MAPR", board.as_bytes()), (b"PRG0", &prg0), (b"PRG1", &prg1)]);
+        let cartridge = nes::ComponentCartridge::from_bytes(&image).unwrap();
+        assert_eq!(cartridge.info().mapper_id, mapper_id, "{board}");
+        // No CHR chunk means CHR RAM
+        assert_eq!(cartridge.info().chr_ram_size, 8 * 1024, "{board}");
+    }
+
+    let image = unif_image(&[(b"MAPR", b"UNL-SOMETHING\0"), (b"PRG0", &prg0)]);
+    assert!(matches!(nes::ComponentCartridge::from_bytes(&image), Err(nes::CartridgeError::UnsupportedBoard(board)) if board == "UNL-SOMETHING"));
+    let image = unif_image(&[(b"PRG0", &prg0)]);
+    assert!(matches!(nes::ComponentCartridge::from_bytes(&image), Err(nes::CartridgeError::Malformed(_))));
+    let image = unif_image(&[(b"MAPR", b"NES-NROM-128\0"), (b"PRG0", &prg0)]);
+    assert!(matches!(nes::ComponentCartridge::from_bytes(&image[..image.len() - 1]), Err(nes::CartridgeError::Malformed(_))));
+}
+
+/// One side of a `.fds` image, with a single file whose data is `file`
+fn fds_side(file: &[u8]) -> Vec<u8> {
+    let mut side = b"\x01*NINTENDO-HVC*".to_vec();
+    side.resize(56, 0);
+    side.extend([0x02, 1]);
+
+    let mut header = vec![0x03; 16];
+    header[13..15].copy_from_slice(&(file.len() as u16).to_le_bytes());
+    side.extend(header);
+    side.push(0x04);
+    side.extend(file);
+
+    side.resize(65500, 0);
+    side
+}
+
+fn fds_image(sides: &[Vec<u8>]) -> Vec<u8> {
+    let mut image = vec![b'F', b'D', b'S', 0x1A, sides.len() as u8];
+    image.resize(16, 0);
+    for side in sides {
+        image.extend(side);
+    }
+
+    image
+}
+
+fn fds_cartridge(name: &str, image: &[u8]) -> nes::ComponentCartridge {
+    let bios_path = std::env::temp_dir().join(format!("rustynes_{name}_disksys.rom"));
+    std::fs::write(&bios_path, vec![0xB1; 8 * 1024]).unwrap();
+    let options = nes::LoadOptions { fds_bios_path: Some(bios_path.clone()), ..Default::default() };
+    let cartridge = nes::ComponentCartridge::from_reader_with_options(std::io::Cursor::new(image), &options);
+    std::fs::remove_file(&bios_path).unwrap();
+
+    cartridge.unwrap()
+}
+
+#[test]
+fn CARTRIDGE_fds_image() {
+    let image = fds_image(&[fds_side(b"A"), fds_side(b"B")]);
+
+    let cartridge = fds_cartridge("fds_image", &image);
+    let info = cartridge.info();
+    assert_eq!(info.format, nes::HeaderFormat::Fds);
+    assert_eq!((info.prg_rom_size, info.prg_ram_size, info.chr_ram_size), (8 * 1024, 32 * 1024, 8 * 1024));
+    assert_eq!(cartridge.disk_sides_count(), 2);
+    assert_eq!(cartridge.disk_side(), Some(0));
+
+    // BIOS at $E000, RAM from $6000 to $DFFF
+    let mut cartridge = cartridge;
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xE000), Some(0xB1));
+    cartridge.cpu_write(0x6000, 0x12);
+    cartridge.cpu_write(0xDFFF, 0x34);
+    cartridge.cpu_write(0xE000, 0x56);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x6000), Some(0x12));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xDFFF), Some(0x34));
+    assert_eq!(cartridge_cpu_read(&cartridge, 0xE000), Some(0xB1));
+
+    // Headerless images work too
+    let cartridge = fds_cartridge("fds_headerless", &image[16..]);
+    assert_eq!(cartridge.disk_sides_count(), 2);
+
+    // The BIOS is looked for next to the image by default
+    assert!(matches!(nes::ComponentCartridge::from_bytes(&image), Err(nes::CartridgeError::MissingFdsBios(None))));
+    let path = std::env::temp_dir().join("rustynes_fds_no_bios").join("game.fds");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, &image).unwrap();
+    let result = nes::ComponentCartridge::from_path(path.to_str().unwrap());
+    assert!(matches!(result, Err(nes::CartridgeError::MissingFdsBios(Some(bios_path))) if bios_path == path.with_file_name("disksys.rom")));
+    std::fs::write(path.with_file_name("disksys.rom"), vec![0xB1; 8 * 1024]).unwrap();
+    assert!(nes::ComponentCartridge::from_path(path.to_str().unwrap()).is_ok());
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    assert!(matches!(nes::ComponentCartridge::from_bytes(&image[..1000]), Err(nes::CartridgeError::Malformed(_))));
+}
+
+#[test]
+fn MAPPER_fds_timer_irq() {
+    let mut cartridge = fds_cartridge("fds_timer_irq", &fds_image(&[fds_side(b"A")]));
+
+    // Ignored while disk I/O is disabled
+    cartridge.cpu_write(0x4020, 10);
+    cartridge.cpu_write(0x4021, 0);
+    cartridge.cpu_write(0x4022, 0x02);
+    for _ in 0..20 {
+        cartridge.cpu_tick();
+    }
+    assert!(!cartridge.is_irq_asserted());
+
+    cartridge.cpu_write(0x4023, 0x01);
+    cartridge.cpu_write(0x4022, 0x02);
+    for _ in 0..10 {
+        cartridge.cpu_tick();
+    }
+    assert!(!cartridge.is_irq_asserted());
+    cartridge.cpu_tick();
+    assert!(cartridge.is_irq_asserted());
+
+    // Peeking the status and data doesn't acknowledge it
+    let mut data = 0x00;
+    assert!(cartridge.cpu_read(0x4030, true, &mut data));
+    assert_eq!(data & 0x01, 0x01);
+    assert!(cartridge.cpu_read(0x4031, true, &mut data));
+    assert!(cartridge.is_irq_asserted());
+
+    // Acknowledged by reading the status
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x4030).map(|status| status & 0x01), Some(0x01));
+    assert!(!cartridge.is_irq_asserted());
+
+    // Without repeat it only fires once
+    for _ in 0..100 {
+        cartridge.cpu_tick();
+    }
+    assert!(!cartridge.is_irq_asserted());
+
+    cartridge.cpu_write(0x4022, 0x03);
+    let mut irqs = 0;
+    for _ in 0..55 {
+        cartridge.cpu_tick();
+        if cartridge.is_irq_asserted() {
+            irqs += 1;
+            cartridge_cpu_read(&cartridge, 0x4030);
+        }
+    }
+    assert_eq!(irqs, 5);
+}
+
+#[test]
+fn MAPPER_fds_disk_read() {
+    let mut cartridge = fds_cartridge("fds_disk_read", &fds_image(&[fds_side(b"DATA")]));
+
+    cartridge.cpu_write(0x4023, 0x01);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x4032).map(|status| status & 0x01), Some(0x00));
+
+    // Motor on, read mode, transfer once the gap ends, IRQ on each byte
+    cartridge.cpu_write(0x4025, 0xC5);
+
+    let mut data = Vec::new();
+    for _ in 0..2_000_000 {
+        cartridge.cpu_tick();
+        if cartridge.is_irq_asserted() {
+            data.push(cartridge_cpu_read(&cartridge, 0x4031).unwrap());
+            if data.len() == 15 {
+                break;
+            }
+        }
+    }
+    // The start mark is skipped
+    assert_eq!(data, b"\x01*NINTENDO-HVC*");
+
+    // Mirroring is set by $4025
+    assert_eq!(cartridge.mirror(), nes::Mirror::Vertical);
+    cartridge.cpu_write(0x4025, 0xCD);
+    assert_eq!(cartridge.mirror(), nes::Mirror::Horizontal);
+}
+
+#[test]
+fn MAPPER_fds_disk_side_switch() {
+    let cartridge = fds_cartridge("fds_disk_side_switch", &fds_image(&[fds_side(b"A"), fds_side(b"B")]));
+    let mut nes = nes::Nes::new();
+    nes.insert_cartridge(cartridge);
+    assert_eq!(nes.get_disk_sides_count(), 2);
+
+    nes.switch_disk_side();
+    assert_eq!(nes.get_disk_side(), Some(1));
+    nes.switch_disk_side();
+    assert_eq!(nes.get_disk_side(), Some(0));
+
+    // The disk is out of the drive for a while before the new side goes in
+    let mut cartridge = fds_cartridge("fds_disk_side_switch", &fds_image(&[fds_side(b"A"), fds_side(b"B")]));
+    cartridge.cpu_write(0x4023, 0x01);
+    cartridge.insert_disk_side(Some(1));
+    cartridge.cpu_tick();
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x4032).map(|status| status & 0x01), Some(0x01));
+    for _ in 0..2_000_000 {
+        cartridge.cpu_tick();
+    }
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x4032).map(|status| status & 0x01), Some(0x00));
+    assert_eq!(cartridge.disk_side(), Some(1));
+
+    // Cartridges have no disk
+    let mut nes = nes::Nes::new();
+    nes.insert_cartridge(synthetic_cartridge("fds_no_disk", 0, 1, 1));
+    nes.switch_disk_side();
+    assert_eq!(nes.get_disk_side(), None);
+}
+
+#[test]
+fn APU_fds_audio() {
+    let mut cartridge = fds_cartridge("fds_audio", &fds_image(&[fds_side(b"A")]));
+    cartridge.cpu_write(0x4023, 0x03);
+
+    // Full-scale square wave
+    cartridge.cpu_write(0x4089, 0x80);
+    for i in 0..64 {
+        cartridge.cpu_write(0x4040 + i, if i < 32 { 0x3F } else { 0x00 });
+    }
+    cartridge.cpu_write(0x4089, 0x00);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x4041), Some(0x3F));
+    // The table can't be written while it's playing
+    cartridge.cpu_write(0x4041, 0x00);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x4041), Some(0x3F));
+
+    // Envelope off, the gain is the speed bits
+    cartridge.cpu_write(0x4080, 0x80 | 0x20);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x4090), Some(0x60));
+
+    // Slow enough that the first half of the table lasts a while
+    cartridge.cpu_write(0x4082, 0x10);
+    cartridge.cpu_write(0x4083, 0x00);
+    for _ in 0..2000 {
+        cartridge.cpu_tick();
+    }
+    let full = cartridge.audio_output();
+    assert!(full > 0.3 && full < 0.4, "{full}");
+
+    // Master volume 2/5
+    cartridge.cpu_write(0x4089, 0x03);
+    for _ in 0..2000 {
+        cartridge.cpu_tick();
+    }
+    let quiet = cartridge.audio_output();
+    assert!((quiet / full - 14.0 / 36.0).abs() < 0.02, "{quiet}");
+
+    // Halting the wave goes back to the start of the table, nothing plays with sound I/O off
+    cartridge.cpu_write(0x4083, 0x80);
+    cartridge.cpu_write(0x4023, 0x01);
+    cartridge.cpu_write(0x4089, 0x00);
+    assert_eq!(cartridge_cpu_read(&cartridge, 0x4090), None);
+}
+
+// ---------------------------------- [PPU] ---------------------------------- //
+
+/// Tags each of the four logical nametables, then reads back what each one shows
+fn name_table_tags(ppu: &mut nes::Component2C02, cartridge: &mut nes::ComponentCartridge) -> [u8; 4] {
+    for (i, addr) in [0x2000, 0x2400, 0x2800, 0x2C00].into_iter().enumerate() {
+        ppu.ppu_write(addr + 0x0123, i as u8 + 1, cartridge);
+    }
+
+    [0x2000, 0x2400, 0x2800, 0x2C00].map(|addr| ppu.ppu_read(addr + 0x0123, false, cartridge))
+}
+
+#[test]
+fn PPU_name_table_mirroring() {
+    let mut ppu = nes::Component2C02::new();
+
+    let mut cartridge = synthetic_cartridge_with_flags("ppu_horizontal", 0, 1, 1, 0x00);
+    assert_eq!(name_table_tags(&mut ppu, &mut cartridge), [2, 2, 4, 4]);
+
+    let mut cartridge = synthetic_cartridge_with_flags("ppu_vertical", 0, 1, 1, 0x01);
+    assert_eq!(name_table_tags(&mut ppu, &mut cartridge), [3, 4, 3, 4]);
+
+    // $3000-$3EFF mirrors $2000-$2EFF
+    assert_eq!(ppu.ppu_read(0x3523, false, &cartridge), 4);
+
+    // MMC1 one-screen modes
+    let mut cartridge = synthetic_cartridge("ppu_one_screen", 1, 2, 1);
+    mmc1_write(&mut cartridge, 0x8000, 0x00);
+    assert_eq!(name_table_tags(&mut ppu, &mut cartridge), [4, 4, 4, 4]);
+    mmc1_write(&mut cartridge, 0x8000, 0x01);
+    assert_eq!(name_table_tags(&mut ppu, &mut cartridge), [4, 4, 4, 4]);
+    mmc1_write(&mut cartridge, 0x8000, 0x00);
+    ppu.ppu_write(0x2000, 0x10, &mut cartridge);
+    mmc1_write(&mut cartridge, 0x8000, 0x01);
+    assert_eq!(ppu.ppu_read(0x2C00, false, &cartridge), 0x00);
+}
+
+#[test]
+fn PPU_four_screen_mirroring() {
+    let mut ppu = nes::Component2C02::new();
+    let mut cartridge = synthetic_cartridge_with_flags("ppu_four_screen", 4, 2, 1, 0x08);
+
+    assert_eq!(cartridge.mirror(), nes::Mirror::FourScreen);
+    assert_eq!(name_table_tags(&mut ppu, &mut cartridge), [1, 2, 3, 4]);
+
+    // The MMC3 mirroring register has no effect
+    cartridge.cpu_write(0xA000, 0x01);
+    assert_eq!(cartridge.mirror(), nes::Mirror::FourScreen);
+    assert_eq!(ppu.ppu_read(0x2D23, false, &cartridge), 4);
+}
+
+#[test]
+fn PPU_mirroring_change_mid_frame() {
+    let mut ppu = nes::Component2C02::new();
+    let mut cartridge = synthetic_cartridge_with_flags("ppu_mid_frame", 4, 2, 1, 0x01);
+
+    ppu.ppu_write(0x2000, 0x11, &mut cartridge);
+    ppu.ppu_write(0x2400, 0x22, &mut cartridge);
+    assert_eq!(ppu.ppu_read(0x2800, false, &cartridge), 0x11);
+
+    // Takes effect on the very next fetch
+    cartridge.cpu_write(0xA000, 0x01);
+    assert_eq!(ppu.ppu_read(0x2800, false, &cartridge), 0x22);
+    assert_eq!(ppu.ppu_read(0x2400, false, &cartridge), 0x11);
+}
+
+// -------------------------------- [STATE] -------------------------------- //
+
+/// MMC3 console running a loop that keeps incrementing RAM, from the flat test memory
+fn state_test_nes(name: &str) -> Nes {
+    let mut nes = Nes::new();
+    let mut cartridge = synthetic_cartridge(name, 4, 8, 8);
+    // R0, 2K CHR bank at $0000
+    cartridge.cpu_write(0x8000, 0);
+    cartridge.cpu_write(0x8001, 12);
+    nes.insert_cartridge(cartridge);
+
+    // INC $10, INX, STX $11, JMP $8000
+    for (i, data) in [0xE6, 0x10, 0xE8, 0x86, 0x11, 0x4C, 0x00, 0x80].into_iter().enumerate() {
+        nes.cpu_write(0x8000 + i as u16, data);
+    }
+    nes.cpu_write(0xFFFC, 0x00);
+    nes.cpu_write(0xFFFD, 0x80);
+    nes.reset();
+
+    nes
+}
+
+fn run_ticks(nes: &mut Nes, ticks: usize) {
+    for _ in 0..ticks {
+        nes.tick();
+    }
+}
+
+#[test]
+fn STATE_round_trip() {
+    let mut nes = state_test_nes("state_round_trip");
+    run_ticks(&mut nes, 10_000);
+
+    let state = nes.save_state();
+    run_ticks(&mut nes, 5_000);
+    let later = nes.save_state();
+    let ram = nes.get_ram(0x0010, 0x0011).2.to_vec();
+
+    // Going back and running again ends up in the exact same place
+    nes.load_state(&state).unwrap();
+    assert_ne!(nes.get_ram(0x0010, 0x0011).2, ram.as_slice());
+    run_ticks(&mut nes, 5_000);
+    assert!(nes.save_state() == later);
+    assert_eq!(nes.get_ram(0x0010, 0x0011).2, ram.as_slice());
+}
+
+#[test]
+fn STATE_mapper_registers() {
+    let mut nes = state_test_nes("state_mapper_registers");
+    run_ticks(&mut nes, 1_000);
+    let state = nes.save_state();
+
+    // Same game, with its mapper still at power-up
+    let mut other = Nes::new();
+    other.insert_cartridge(synthetic_cartridge("state_mapper_registers_other", 4, 8, 8));
+    other.load_state(&state).unwrap();
+    assert!(other.save_state() == state);
+}
+
+#[test]
+fn STATE_errors() {
+    let mut nes = state_test_nes("state_errors");
+    run_ticks(&mut nes, 1_000);
+    let state = nes.save_state();
+
+    let mut bad_magic = state.clone();
+    bad_magic[0] = b'X';
+    assert!(matches!(nes.load_state(&bad_magic), Err(nes::StateError::BadMagic)));
+
+    let mut bad_version = state.clone();
+    bad_version[4] = 0xFF;
+    assert!(matches!(nes.load_state(&bad_version), Err(nes::StateError::UnsupportedVersion(0x00FF))));
+
+    let mut other = Nes::new();
+    other.insert_cartridge(synthetic_cartridge("state_errors_other", 4, 8, 4));
+    assert!(matches!(other.load_state(&state), Err(nes::StateError::WrongCartridge { .. })));
+
+    // A truncated state leaves the machine as it was
+    run_ticks(&mut nes, 1_000);
+    let current = nes.save_state();
+    assert!(matches!(nes.load_state(&state[..state.len() - 100]), Err(nes::StateError::Truncated)));
+    assert!(nes.save_state() == current);
+
+    let mut too_long = state.clone();
+    too_long.push(0);
+    assert!(matches!(nes.load_state(&too_long), Err(nes::StateError::TrailingData)));
+    assert!(nes.save_state() == current);
+}
+
+#[test]
+fn STATE_rewind() {
+    let mut nes = state_test_nes("state_rewind");
+    let mut rewind = nes::Rewind::new(3, 3);
+
+    let mut states = Vec::new();
+    for _ in 0..14 {
+        states.push(nes.save_state());
+        rewind.push_frame(&nes);
+        nes.run_frame();
+    }
+
+    // Snapshots are taken every 3 frames and 3 are kept before the latest one, the history starts at frame 3
+    assert_eq!(rewind.frames_count(), 11);
+    for frame in (3..14).rev() {
+        assert!(rewind.step_back(&mut nes));
+        assert!(nes.save_state() == states[frame], "frame {frame}");
+    }
+    assert!(!rewind.step_back(&mut nes));
+
+    // Running again picks up from there
+    rewind.push_frame(&nes);
+    nes.run_frame();
+    assert!(rewind.step_back(&mut nes));
+    assert!(nes.save_state() == states[3]);
+}
+
+// -------------------------------- [MOVIE] -------------------------------- //
+
+#[test]
+fn MOVIE_fm2_format() {
+    let fm2 = "version 3\nemuVersion 22020\nrerecordCount 7\npalFlag 0\nromFilename Super Mario Bros.\n\
+        guid 452DE2C3-EF43-2FA9-77AC-0677FC51543B\nfourscore 0\nport0 1\nport1 1\nport2 0\ncomment author someone\n\
+        |0|........|........||\n|1|R......A|........||\n|0|....T...|.L.U....||\n|0|........|||\n";
+    let movie = nes::Movie::from_fm2(fm2).unwrap();
+
+    assert_eq!(movie.rom_filename, "Super Mario Bros.");
+    assert_eq!(movie.rerecord_count, 7);
+    assert_eq!(movie.comments, ["author someone"]);
+    assert!(movie.start_state.is_none());
+    assert_eq!(movie.frames, [
+        nes::MovieFrame { commands: 0, controllers: [0x00, 0x00, 0x00, 0x00] },
+        nes::MovieFrame { commands: nes::MovieFrame::SOFT_RESET, controllers: [0x81, 0x00, 0x00, 0x00] },
+        nes::MovieFrame { commands: 0, controllers: [0x10, 0x0A, 0x00, 0x00] },
+        // Nothing plugged in the second port
+        nes::MovieFrame { commands: 0, controllers: [0x00, 0x00, 0x00, 0x00] },
+    ]);
+
+    // Headers this emulator doesn't use survive an export
+    let exported = movie.to_fm2();
+    assert!(exported.contains("guid 452DE2C3-EF43-2FA9-77AC-0677FC51543B\n"));
+    assert!(exported.contains("|1|R......A|........||\n"));
+    let reimported = nes::Movie::from_fm2(&exported).unwrap();
+    assert_eq!(reimported.frames, movie.frames);
+    assert_eq!(reimported.comments, movie.comments);
+
+    assert!(matches!(nes::Movie::from_fm2("version 2\n"), Err(nes::MovieError::UnsupportedVersion(_))));
+    assert!(matches!(nes::Movie::from_fm2("version 3\n|0|...|\n"), Err(nes::MovieError::Malformed(2, _))));
+}
+
+#[test]
+fn MOVIE_playback_is_deterministic() {
+    let mut nes = state_test_nes("movie_playback");
+    run_ticks(&mut nes, 1_000);
+
+    // Recorded from a save state, the flat test memory holding the program doesn't survive a power cycle
+    let mut movie = nes::Movie::from_state("movie_playback", &nes);
+    for frame in 0..6_u8 {
+        nes.set_pad_states([frame.wrapping_mul(37), 0, 0, 0]);
+        movie.push_frame(&nes, if frame == 3 { nes::MovieFrame::SOFT_RESET } else { 0 });
+        if frame == 3 {
+            nes.reset();
+        }
+        nes.run_frame();
+    }
+    let recorded_end = nes.save_state();
+
+    // Through the FM2 text, with the start state in base64
+    let movie = nes::Movie::from_fm2(&movie.to_fm2()).unwrap();
+    let mut checkpoints = Vec::new();
+    movie.play(&mut nes, |_, nes| checkpoints.push(nes.get_screen_crc32())).unwrap();
+    assert!(nes.save_state() == recorded_end);
+
+    let mut replayed_checkpoints = Vec::new();
+    movie.play(&mut nes, |_, nes| replayed_checkpoints.push(nes.get_screen_crc32())).unwrap();
+    assert_eq!(checkpoints.len(), 6);
+    assert_eq!(checkpoints, replayed_checkpoints);
+}
+
+#[test]
+fn MOVIE_recorded_from_power_on() {
+    let mut nes = state_test_nes("movie_power_on");
+    run_ticks(&mut nes, 1_000);
+    // APU registers a reset leaves alone: 5-step sequence, pulse duty and envelope, triangle linear counter, noise period
+    for (addr, data) in [(0x4017, 0xC0), (0x4000, 0xBF), (0x4008, 0xFF), (0x400E, 0x85), (0x4015, 0x0F), (0x400B, 0xF8)] {
+        nes.test_bus_write(addr, data);
+    }
+    run_ticks(&mut nes, 1_000);
+    nes.set_audio_sample_rate(22_050);
+    nes.set_channel_volume(nes::Channel::Noise, 0.5);
+
+    let mut movie = nes::Movie::new("movie_power_on");
+    movie.rewind_to_start(&mut nes).unwrap();
+    for frame in 0..4_u8 {
+        nes.set_pad_states([frame.wrapping_mul(37), 0, 0, 0]);
+        movie.push_frame(&nes, 0);
+        nes.run_frame();
+    }
+    // The host settings survive the power cycle
+    assert_eq!(nes.get_audio_sample_rate(), 22_050);
+    assert_eq!(nes.get_channel_volume(nes::Channel::Noise), 0.5);
+
+    // A new console playing it ends up in the same state
+    let mut replay_nes = state_test_nes("movie_power_on");
+    movie.play(&mut replay_nes, |_, _| {}).unwrap();
+    assert!(replay_nes.save_state() == nes.save_state());
+}
+
+fn run_json_test(path: &str) {
+    const CYCLE_LIMIT : usize = 10000;
+    // const CYCLE_LIMIT : usize = 5;
+    // Relative to the crate, cargo runs tests from there but the workspace may be built from elsewhere
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(path);
+    let file_contents = std::fs::read_to_string(&path).unwrap_or_else(|error| panic!("Failed to read {}: {error}", path.display()));
+    let deserialized: Vec<TestEntry> = serde_json::from_str(&file_contents).unwrap();
+
+    let mut test_passed = Vec::new();
+    let mut nes = Nes::new();
+    
+    for entry in deserialized.iter() {
+        let mut local_cycle = 0_u64;
+        nes.test_reset();
+        cycles_trace::CYCLES.lock().unwrap().clear();
+        
+        println!("\n[Executing test: {}]", entry.name);
+        
+        nes.test_set_initial_state(&entry.initial);
+
+        while cycles_trace::CYCLES.lock().unwrap().len() != entry.cycles.len() {
+            nes.test_tick();
+            local_cycle += 1;
+            if local_cycle > CYCLE_LIMIT as u64 {
+                println!("\n[ERROR] End state should have been:\n");
+                println!("pc [{:4X}] | sp [{:2X}] | a [{:2X}] | x [{:2X}] | y [{:2X}] | status [{:2X}]", entry.r#final.pc, entry.r#final.s, entry.r#final.a, entry.r#final.x, entry.r#final.y, entry.r#final.p);
+                for cycle in entry.cycles.iter() {
+                    println!("[{}, {}, \"{}\"]", cycle.address, cycle.value, cycle.operation);
+                }
+                println!("\n[ERROR] But it was:\n");
+                println!("pc [{:4X}] | sp [{:2X}] | a [{:2X}] | x [{:2X}] | y [{:2X}] | status [{:2X}]", nes.get_cpu_info().program_counter, nes.get_cpu_info().stack_pointer, nes.get_cpu_info().reg_a, nes.get_cpu_info().reg_x, nes.get_cpu_info().reg_y, nes.get_cpu_flags());
+                for cycle in cycles_trace::CYCLES.lock().unwrap().iter() {
+                    println!("[{}, {}, \"{}\"]", cycle.0, cycle.1, cycle.2);
+                }
+                panic!("Failed to execute test: {} - cycle limit reached\n", entry.name);
+            }
+        }
+
+        println!("\nExpected cycles");
+        for cycle in entry.cycles.iter() {
+            println!("[{}, {}, \"{}\"]", cycle.address, cycle.value, cycle.operation);
+        }
+        println!("\nMy cycles");
+        for cycle in cycles_trace::CYCLES.lock().unwrap().iter() {
+            println!("[{}, {}, \"{}\"]", cycle.0, cycle.1, cycle.2);
+        }
+
+        if !nes.test_end_state(&entry.r#final) {
+            println!("\n[ERROR] End state should have been:\n");
+            println!("pc [{:4X}] | sp [{:2X}] | a [{:2X}] | x [{:2X}] | y [{:2X}] | status [{:2X}]", entry.r#final.pc, entry.r#final.s, entry.r#final.a, entry.r#final.x, entry.r#final.y, entry.r#final.p);
+            for cycle in entry.cycles.iter() {
+                println!("[{}, {}, \"{}\"]", cycle.address, cycle.value, cycle.operation);
+            }
+            println!("\n[ERROR] But it was:\n");
+            println!("pc [{:4X}] | sp [{:2X}] | a [{:2X}] | x [{:2X}] | y [{:2X}] | status [{:2X}]", nes.get_cpu_info().program_counter, nes.get_cpu_info().stack_pointer, nes.get_cpu_info().reg_a, nes.get_cpu_info().reg_x, nes.get_cpu_info().reg_y, nes.get_cpu_flags());
+            for cycle in cycles_trace::CYCLES.lock().unwrap().iter() {
+                println!("[{}, {}, \"{}\"]", cycle.0, cycle.1, cycle.2);
+            }
+            panic!("Failed to execute test: {}\n", entry.name);
+        }
+
+        test_passed.push(&entry.name);
+    }
+
+    println!("\n\nTests passed:");
+    for test in test_passed.iter() {
+        println!("{}", test);
+    }
+
+    assert_eq!(test_passed.len(), deserialized.len());
+}
@@ -0,0 +1,234 @@
+#![allow(clippy::cast_possible_truncation)]
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+
+//! Windowless runs for scripts and CI, only built on `rustynes-core`
+
+mod tests;
+
+use std::io;
+use std::path::Path;
+
+use rustynes_core::constants::{NES_SCREEN_HEIGHT, NES_SCREEN_WIDTH};
+use rustynes_core::{LoadOptions, Movie, Nes};
+
+const FPS: u32 = 60;
+
+const USAGE: &str = "\
+usage: rustynes-headless <rom> [options]
+  --frames <n>             frames to run at most, defaults to the movie length or a minute
+  --movie <path.fm2>       controller input, and start state, from a movie
+  --until-pc <addr>        stop when the CPU is about to run the instruction at addr (hex)
+  --until-ram <addr=value> stop when the RAM byte at addr ($0000-$1FFF) holds value, checked after each frame (hex)
+  --screenshot <path.png>  writes the last frame
+  --dump-ram <path>        writes the 2K of CPU RAM
+  --fds-bios <path>        FDS BIOS, disksys.rom next to the image by default
+  --database <path>        game database checked before the built-in one
+Exits with 2 when a stop condition is given and isn't met";
+
+#[derive(Debug, Default)]
+struct HeadlessOptions {
+    rom_path: String,
+    load_options: LoadOptions,
+    frames: Option<u64>,
+    movie_path: Option<String>,
+    until_pc: Option<u16>,
+    until_ram: Option<(u16, u8)>,
+    screenshot_path: Option<String>,
+    ram_path: Option<String>,
+}
+
+impl HeadlessOptions {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut rom_path = None;
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{arg} needs a value"));
+            match arg.as_str() {
+                "--frames" => options.frames = Some(value()?.parse().map_err(|_| "--frames needs a number".to_string())?),
+                "--movie" => options.movie_path = Some(value()?),
+                "--until-pc" => options.until_pc = Some(parse_hex(&value()?)?),
+                "--until-ram" => {
+                    let value = value()?;
+                    let (addr, data) = value.split_once('=').ok_or_else(|| format!("--until-ram expects addr=value, got {value}"))?;
+                    let addr: u16 = parse_hex(addr)?;
+                    if addr > 0x1FFF {
+                        return Err(format!("--until-ram expects a CPU RAM address ($0000-$1FFF), got {addr:04X}"));
+                    }
+                    // $0800-$1FFF mirror the 2K of RAM
+                    options.until_ram = Some((addr & 0x07FF, parse_hex(data)?));
+                }
+                "--screenshot" => options.screenshot_path = Some(value()?),
+                "--dump-ram" => options.ram_path = Some(value()?),
+                "--fds-bios" => options.load_options.fds_bios_path = Some(value()?.into()),
+                "--database" => options.load_options.database_path = Some(value()?.into()),
+                _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+                _ => rom_path = Some(arg),
+            }
+        }
+
+        options.rom_path = rom_path.ok_or_else(|| "no ROM given".to_string())?;
+        Ok(options)
+    }
+}
+
+fn parse_hex<T: TryFrom<u32>>(text: &str) -> Result<T, String> {
+    let digits = text.trim_start_matches('$').trim_start_matches("0x");
+    u32::from_str_radix(digits, 16).ok()
+        .and_then(|value| T::try_from(value).ok())
+        .ok_or_else(|| format!("{text} is not a valid hex value"))
+}
+
+fn main() {
+    std::process::exit(run(std::env::args().skip(1)));
+}
+
+/// Runs the emulator as `args` say, returns the process exit code
+fn run(args: impl Iterator<Item = String>) -> i32 {
+    let options = match HeadlessOptions::parse(args) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{error}\n\n{USAGE}");
+            return 1;
+        }
+    };
+
+    let mut nes = Nes::new();
+    if let Err(error) = nes.load_cartridge(&options.rom_path, &options.load_options) {
+        eprintln!("Could not load {}: {error}", options.rom_path);
+        return 1;
+    }
+    nes.reset();
+    nes.pause = false;
+
+    let movie = match &options.movie_path {
+        Some(path) => match Movie::from_path(path).and_then(|movie| movie.rewind_to_start(&mut nes).map(|()| movie)) {
+            Ok(movie) => Some(movie),
+            Err(error) => {
+                eprintln!("Could not play movie {path}: {error}");
+                return 1;
+            }
+        },
+        None => None,
+    };
+    let frames = options.frames.or_else(|| movie.as_ref().map(|movie| movie.frames.len() as u64)).unwrap_or(60 * FPS as u64);
+
+    let mut frames_run = 0;
+    let mut is_condition_met = false;
+    while frames_run < frames && !is_condition_met {
+        if let Some(movie) = &movie {
+            movie.apply_frame(frames_run as usize, &mut nes);
+        }
+
+        is_condition_met = match options.until_pc {
+            Some(pc) => run_frame_until_pc(&mut nes, pc),
+            None => {
+                nes.run_frame();
+                false
+            }
+        };
+        frames_run += 1;
+        nes.take_audio_samples();
+
+        if let Some((addr, data)) = options.until_ram {
+            is_condition_met |= nes.get_ram(addr, addr.saturating_add(1)).2.first() == Some(&data);
+        }
+    }
+
+    let cpu = nes.get_cpu_info();
+    println!("frames {frames_run}");
+    println!("pc {:04X} a {:02X} x {:02X} y {:02X} sp {:02X} p {:02X}", cpu.program_counter, cpu.reg_a, cpu.reg_x, cpu.reg_y, cpu.stack_pointer, nes.get_cpu_flags());
+    println!("screen crc32 {:08X}", nes.get_screen_crc32());
+
+    if let Some(path) = &options.screenshot_path {
+        if let Err(error) = write_png(path, &screen_rgb(&nes)) {
+            eprintln!("Could not write {path}: {error}");
+            return 1;
+        }
+    }
+    if let Some(path) = &options.ram_path {
+        if let Err(error) = std::fs::write(path, nes.get_ram(0x0000, 0x0800).2) {
+            eprintln!("Could not write {path}: {error}");
+            return 1;
+        }
+    }
+
+    let has_condition = options.until_pc.is_some() || options.until_ram.is_some();
+    if has_condition && !is_condition_met {
+        eprintln!("Stop condition not met after {frames_run} frames");
+        return 2;
+    }
+
+    0
+}
+
+/// Like `Nes::run_frame`, but stops as soon as the CPU reaches `pc`, returns whether it did
+fn run_frame_until_pc(nes: &mut Nes, pc: u16) -> bool {
+    loop {
+        nes.tick();
+        if nes.is_cpu_instruction_complete() && nes.get_cpu_info().program_counter == pc {
+            return true;
+        }
+        if nes.is_ppu_frame_complete() {
+            nes.set_ppu_frame_complete(false);
+            return false;
+        }
+    }
+}
+
+fn screen_rgb(nes: &Nes) -> Vec<u8> {
+    nes.get_screen().iter().flat_map(|color| [color.r, color.g, color.b]).collect()
+}
+
+/// 8-bit RGB PNG, the image data is stored without compression so it only needs the checksums
+fn write_png<P: AsRef<Path>>(path: P, rgb: &[u8]) -> io::Result<()> {
+    let width = NES_SCREEN_WIDTH as u32;
+    let height = NES_SCREEN_HEIGHT as u32;
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // Bit depth, RGB, deflate, standard filters, no interlacing
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    // Each row starts with its filter type, none
+    let mut raw = Vec::new();
+    for row in rgb.chunks_exact(width as usize * 3) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    // zlib stream made of stored deflate blocks
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(u16::MAX as usize).peekable();
+    while let Some(block) = blocks.next() {
+        zlib.push(u8::from(blocks.peek().is_none()));
+        zlib.extend_from_slice(&(block.len() as u16).to_le_bytes());
+        zlib.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    for (kind, data) in [(b"IHDR", header.as_slice()), (b"IDAT", zlib.as_slice()), (b"IEND", &[])] {
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        png.extend_from_slice(kind);
+        png.extend_from_slice(data);
+
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(kind);
+        hasher.update(data);
+        png.extend_from_slice(&hasher.finalize().to_be_bytes());
+    }
+
+    std::fs::write(path, png)
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1_u32, 0_u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+
+    (b << 16) | a
+}
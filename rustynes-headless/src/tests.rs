@@ -0,0 +1,53 @@
+#![allow(non_snake_case)]
+
+#![cfg(test)]
+
+use super::*;
+
+/// NROM image with every PRG bank filled with its index
+fn synthetic_rom() -> Vec<u8> {
+    let mut rom = vec![b'N', b'E', b'S', 0x1A, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    rom.extend(std::iter::repeat_n(0, 8 * 1024));
+    rom.extend(std::iter::repeat_n(1, 8 * 1024));
+    rom.extend(std::iter::repeat_n(0, 8 * 1024));
+
+    rom
+}
+
+// ------------------------------- [HEADLESS] ------------------------------- //
+
+#[test]
+fn HEADLESS_run() {
+    let dir = std::env::temp_dir();
+    let rom_path = dir.join("rustynes_headless.nes");
+    let screenshot_path = dir.join("rustynes_headless.png");
+    let ram_path = dir.join("rustynes_headless.ram");
+    std::fs::write(&rom_path, synthetic_rom()).unwrap();
+    let args = |extra: &[&str]| {
+        let mut args = vec![rom_path.to_str().unwrap().to_string()];
+        args.extend(extra.iter().map(ToString::to_string));
+        args.into_iter()
+    };
+
+    let screenshot = screenshot_path.to_str().unwrap();
+    let ram = ram_path.to_str().unwrap();
+    assert_eq!(run(args(&["--frames", "2", "--screenshot", screenshot, "--dump-ram", ram])), 0);
+    let png = std::fs::read(&screenshot_path).unwrap();
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(&png[12..16], b"IHDR");
+    assert_eq!(std::fs::read(&ram_path).unwrap().len(), 0x0800);
+
+    // Stop conditions that are never met
+    assert_eq!(run(args(&["--frames", "2", "--until-pc", "$ABCD"])), 2);
+    assert_eq!(run(args(&["--frames", "2", "--until-ram", "0x0010=FF"])), 2);
+    // RAM mirrors are checked through the 2K of RAM, anything past them is refused
+    assert_eq!(run(args(&["--frames", "2", "--until-ram", "0x0800=00"])), 0);
+    assert_eq!(run(args(&["--frames", "2", "--until-ram", "0x2000=00"])), 1);
+
+    assert_eq!(run(args(&["--frames", "many"])), 1);
+    assert_eq!(run(std::iter::empty()), 1);
+
+    for path in [rom_path, screenshot_path, ram_path] {
+        let _ = std::fs::remove_file(path);
+    }
+}
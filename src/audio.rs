@@ -0,0 +1,80 @@
+#![allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+
+use std::collections::VecDeque;
+
+use raylib::core::audio::{AudioStream, RaylibAudio};
+use raylib::ffi;
+
+/// Size of each of the two buffers raylib plays from, in samples
+const BUFFER_SIZE: usize = 1024;
+/// Samples kept queued ahead of raylib, emulation runs whenever the queue drops below this
+const TARGET_QUEUE_SIZE: usize = BUFFER_SIZE * 2;
+/// Maximum stretch applied to the audio to keep the queue around its target
+const MAX_RATE_ADJUSTMENT: f64 = 0.005;
+
+/// Streams the emulator audio through raylib, and drives the emulation pace from its consumption
+#[derive(Debug)]
+pub struct AudioOutput<'aud> {
+    stream: AudioStream<'aud>,
+    queue: VecDeque<f32>,
+    buffer: Vec<i16>,
+    muted: bool,
+}
+
+impl<'aud> AudioOutput<'aud> {
+    pub fn new(audio: &'aud RaylibAudio, sample_rate: u32) -> Self {
+        // raylib sizes its buffers from the device by default, which is too large to react to the queue fill
+        unsafe {
+            ffi::SetAudioStreamBufferSizeDefault(BUFFER_SIZE as i32);
+        }
+        let mut stream = audio.new_audio_stream(sample_rate, 16, 1);
+        stream.play();
+
+        Self {
+            stream,
+            queue: VecDeque::with_capacity(TARGET_QUEUE_SIZE * 2),
+            buffer: vec![0; BUFFER_SIZE],
+            muted: false,
+        }
+    }
+
+    pub fn push(&mut self, samples: &[f32]) {
+        self.queue.extend(samples);
+    }
+
+    /// Hands queued samples to raylib for every buffer it finished playing
+    pub fn update(&mut self) {
+        while self.stream.is_processed() {
+            // Missing samples are played as silence rather than stalling the stream
+            for sample in &mut self.buffer {
+                *sample = (self.queue.pop_front().unwrap_or(0.0).clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16;
+            }
+
+            // `AudioStream::update` passes a size in bytes where raylib expects a number of samples
+            unsafe {
+                ffi::UpdateAudioStream(*self.stream, self.buffer.as_ptr().cast(), BUFFER_SIZE as i32);
+            }
+        }
+    }
+
+    /// Whether another frame needs to be emulated to keep the stream fed
+    pub fn needs_samples(&self) -> bool {
+        self.queue.len() < TARGET_QUEUE_SIZE
+    }
+
+    /// Ratio to produce samples at, slightly above 1.0 when the queue runs low and below when it fills up
+    pub fn rate_adjustment(&self) -> f64 {
+        let fill = self.queue.len() as f64 / TARGET_QUEUE_SIZE as f64;
+
+        1.0 + ((1.0 - fill) * MAX_RATE_ADJUSTMENT).clamp(-MAX_RATE_ADJUSTMENT, MAX_RATE_ADJUSTMENT)
+    }
+
+    pub const fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+        self.stream.set_volume(if self.muted { 0.0 } else { 1.0 });
+    }
+}
@@ -1,4 +1,7 @@
-pub const FPS: f32 = 60.0;
-pub const NES_SCREEN_WIDTH: u16 = 256;
-pub const NES_SCREEN_HEIGHT: u16 = 240;
-pub const STACK_ADDRESS: u16 = 0x0100;
+pub const FPS: u32 = 60;
+/// Frames between two rewind snapshots
+pub const REWIND_INTERVAL: u64 = 4;
+/// A minute of rewind at 60 FPS
+pub const REWIND_CAPACITY: usize = 60 * 60 / REWIND_INTERVAL as usize;
+/// Read at startup when it exists, see `input::DEFAULT_CONFIG` for its format
+pub const INPUT_CONFIG_PATH: &str = "input.cfg";
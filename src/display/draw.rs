@@ -2,7 +2,7 @@
 
 use raylib::prelude::*;
 
-use crate::nes::{Nes, CpuInfo};
+use rustynes_core::{Nes, CpuInfo, Channel, Pixel};
 
 const BYTES_PER_LINE: u8 = 40;
 
@@ -175,6 +175,20 @@ impl NesDisplay {
             y = cpu_info.reg_y
         )
     }
+
+    pub fn mixer_to_string(nes: &Nes, muted: bool) -> String {
+        let mut text = format!(
+            "Audio {}{}\n",
+            if muted { "(muted)" } else { "" },
+            if nes.is_recording_audio() { "[REC]" } else { "" },
+        );
+
+        for (index, channel) in Channel::ALL.into_iter().enumerate() {
+            text.push_str(&format!("{}:\t{:?}\t{:.0}%\n", index + 1, channel, nes.get_channel_volume(channel) * 100.0));
+        }
+
+        text
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -367,17 +381,9 @@ impl ScreenDisplay {
         }
     }
 
-    pub fn update(&mut self, rl_handle: &mut RaylibHandle, rl_thread: &RaylibThread, pixels: &[Color]) {
+    pub fn update(&mut self, rl_handle: &mut RaylibHandle, rl_thread: &RaylibThread, pixels: &[Pixel]) {
         let (width, height) = (self.dimensions.x as usize, self.dimensions.y as usize);
-        let mut pixel_data = vec![0; width * height * 4];
-
-        for (i, pixel) in pixels.iter().enumerate() {
-            let offset = i * 4;
-            pixel_data[offset] = pixel.r;
-            pixel_data[offset + 1] = pixel.g;
-            pixel_data[offset + 2] = pixel.b;
-            pixel_data[offset + 3] = 255;
-        }
+        let pixel_data = Pixel::to_rgba(pixels);
 
         if let Some(texture) = &mut self.texture {
             texture.update_texture(&pixel_data);
@@ -0,0 +1,394 @@
+//! Devices plugged into the controller ports and their keyboard, gamepad and mouse bindings, read from a small text config
+//!
+//! Each line is `name = value`, `#` starts a comment. Buttons are named `<controller>.<button>`, with the controller
+//! 1 to 4, 3 and 4 being on the Four Score, and the button one of `a`, `b`, `select`, `start`, `up`, `down`, `left`,
+//! `right`, `turbo_a` and `turbo_b`.
+//! Their value is a comma separated list of bindings, each one being:
+//! - a raylib key name without its `KEY_` prefix, `Z`, `LEFT_SHIFT` or `KP_5`
+//! - a gamepad button, `pad0:RIGHT_FACE_DOWN`, named like raylib's without the `GAMEPAD_BUTTON_` prefix
+//! - a stick direction, `pad0:LEFT_X-` or `pad0:LEFT_Y+`
+//!
+//! Names are case insensitive. Buttons left out of the file keep their `DEFAULT_CONFIG` bindings, an empty
+//! value unbinds them.
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use raylib::core::input::key_from_i32;
+use raylib::prelude::*;
+use rustynes_core::constants::{NES_SCREEN_HEIGHT, NES_SCREEN_WIDTH};
+use rustynes_core::{ArkanoidPaddle, Controller, FourScore, Nes, Zapper};
+
+pub const DEFAULT_CONFIG: &str = "\
+# What each port has, joypad, zapper or paddle, the zapper and the paddle follow the mouse over the screen
+port1 = joypad
+port2 = joypad
+# Controllers 1 to 4 on a Four Score, in place of what the ports have
+four_score = false
+# Frames a turbo button stays pressed, then released
+turbo_period = 2
+# Lets Up+Down and Left+Right through, some games glitch or crash on them
+allow_opposite_directions = false
+
+1.a = X, pad0:RIGHT_FACE_RIGHT
+1.b = Z, pad0:RIGHT_FACE_DOWN
+1.select = A, pad0:MIDDLE_LEFT
+1.start = S, pad0:MIDDLE_RIGHT
+1.up = UP, pad0:LEFT_FACE_UP, pad0:LEFT_Y-
+1.down = DOWN, pad0:LEFT_FACE_DOWN, pad0:LEFT_Y+
+1.left = LEFT, pad0:LEFT_FACE_LEFT, pad0:LEFT_X-
+1.right = RIGHT, pad0:LEFT_FACE_RIGHT, pad0:LEFT_X+
+1.turbo_a = pad0:RIGHT_FACE_UP
+1.turbo_b = pad0:RIGHT_FACE_LEFT
+
+2.a = O, pad1:RIGHT_FACE_RIGHT
+2.b = U, pad1:RIGHT_FACE_DOWN
+2.select = Y, pad1:MIDDLE_LEFT
+2.start = H, pad1:MIDDLE_RIGHT
+2.up = I, pad1:LEFT_FACE_UP, pad1:LEFT_Y-
+2.down = K, pad1:LEFT_FACE_DOWN, pad1:LEFT_Y+
+2.left = J, pad1:LEFT_FACE_LEFT, pad1:LEFT_X-
+2.right = L, pad1:LEFT_FACE_RIGHT, pad1:LEFT_X+
+2.turbo_a = pad1:RIGHT_FACE_UP
+2.turbo_b = pad1:RIGHT_FACE_LEFT
+
+3.a = pad2:RIGHT_FACE_RIGHT
+3.b = pad2:RIGHT_FACE_DOWN
+3.select = pad2:MIDDLE_LEFT
+3.start = pad2:MIDDLE_RIGHT
+3.up = pad2:LEFT_FACE_UP, pad2:LEFT_Y-
+3.down = pad2:LEFT_FACE_DOWN, pad2:LEFT_Y+
+3.left = pad2:LEFT_FACE_LEFT, pad2:LEFT_X-
+3.right = pad2:LEFT_FACE_RIGHT, pad2:LEFT_X+
+3.turbo_a = pad2:RIGHT_FACE_UP
+3.turbo_b = pad2:RIGHT_FACE_LEFT
+
+4.a = pad3:RIGHT_FACE_RIGHT
+4.b = pad3:RIGHT_FACE_DOWN
+4.select = pad3:MIDDLE_LEFT
+4.start = pad3:MIDDLE_RIGHT
+4.up = pad3:LEFT_FACE_UP, pad3:LEFT_Y-
+4.down = pad3:LEFT_FACE_DOWN, pad3:LEFT_Y+
+4.left = pad3:LEFT_FACE_LEFT, pad3:LEFT_X-
+4.right = pad3:LEFT_FACE_RIGHT, pad3:LEFT_X+
+4.turbo_a = pad3:RIGHT_FACE_UP
+4.turbo_b = pad3:RIGHT_FACE_LEFT
+";
+
+const PADS_COUNT: usize = 4;
+
+/// Config names of the buttons and their `Controller` bits, the turbo ones come last
+const BUTTONS: [(&str, u8); 10] = [
+    ("a", Controller::A),
+    ("b", Controller::B),
+    ("select", Controller::SELECT),
+    ("start", Controller::START),
+    ("up", Controller::UP),
+    ("down", Controller::DOWN),
+    ("left", Controller::LEFT),
+    ("right", Controller::RIGHT),
+    ("turbo_a", Controller::A),
+    ("turbo_b", Controller::B),
+];
+const TURBO_BUTTONS_START: usize = 8;
+
+/// How far a stick goes before it counts as a D-pad press
+const AXIS_THRESHOLD: f32 = 0.5;
+
+/// Keys with a name longer than their character, single characters are looked up by their code
+const KEY_NAMES: [(&str, KeyboardKey); 36] = [
+    ("SPACE", KeyboardKey::KEY_SPACE),
+    ("ENTER", KeyboardKey::KEY_ENTER),
+    ("TAB", KeyboardKey::KEY_TAB),
+    ("BACKSPACE", KeyboardKey::KEY_BACKSPACE),
+    ("UP", KeyboardKey::KEY_UP),
+    ("DOWN", KeyboardKey::KEY_DOWN),
+    ("LEFT", KeyboardKey::KEY_LEFT),
+    ("RIGHT", KeyboardKey::KEY_RIGHT),
+    ("LEFT_SHIFT", KeyboardKey::KEY_LEFT_SHIFT),
+    ("RIGHT_SHIFT", KeyboardKey::KEY_RIGHT_SHIFT),
+    ("LEFT_CONTROL", KeyboardKey::KEY_LEFT_CONTROL),
+    ("RIGHT_CONTROL", KeyboardKey::KEY_RIGHT_CONTROL),
+    ("LEFT_ALT", KeyboardKey::KEY_LEFT_ALT),
+    ("RIGHT_ALT", KeyboardKey::KEY_RIGHT_ALT),
+    ("HOME", KeyboardKey::KEY_HOME),
+    ("END", KeyboardKey::KEY_END),
+    ("PAGE_UP", KeyboardKey::KEY_PAGE_UP),
+    ("PAGE_DOWN", KeyboardKey::KEY_PAGE_DOWN),
+    ("INSERT", KeyboardKey::KEY_INSERT),
+    ("DELETE", KeyboardKey::KEY_DELETE),
+    ("COMMA", KeyboardKey::KEY_COMMA),
+    ("PERIOD", KeyboardKey::KEY_PERIOD),
+    ("SLASH", KeyboardKey::KEY_SLASH),
+    ("SEMICOLON", KeyboardKey::KEY_SEMICOLON),
+    ("APOSTROPHE", KeyboardKey::KEY_APOSTROPHE),
+    ("MINUS", KeyboardKey::KEY_MINUS),
+    ("KP_0", KeyboardKey::KEY_KP_0),
+    ("KP_1", KeyboardKey::KEY_KP_1),
+    ("KP_2", KeyboardKey::KEY_KP_2),
+    ("KP_3", KeyboardKey::KEY_KP_3),
+    ("KP_4", KeyboardKey::KEY_KP_4),
+    ("KP_5", KeyboardKey::KEY_KP_5),
+    ("KP_6", KeyboardKey::KEY_KP_6),
+    ("KP_7", KeyboardKey::KEY_KP_7),
+    ("KP_8", KeyboardKey::KEY_KP_8),
+    ("KP_9", KeyboardKey::KEY_KP_9),
+];
+
+const GAMEPAD_BUTTON_NAMES: [(&str, GamepadButton); 17] = [
+    ("LEFT_FACE_UP", GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP),
+    ("LEFT_FACE_RIGHT", GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT),
+    ("LEFT_FACE_DOWN", GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN),
+    ("LEFT_FACE_LEFT", GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT),
+    ("RIGHT_FACE_UP", GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP),
+    ("RIGHT_FACE_RIGHT", GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT),
+    ("RIGHT_FACE_DOWN", GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN),
+    ("RIGHT_FACE_LEFT", GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT),
+    ("LEFT_TRIGGER_1", GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1),
+    ("LEFT_TRIGGER_2", GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_2),
+    ("RIGHT_TRIGGER_1", GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1),
+    ("RIGHT_TRIGGER_2", GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2),
+    ("MIDDLE_LEFT", GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT),
+    ("MIDDLE", GamepadButton::GAMEPAD_BUTTON_MIDDLE),
+    ("MIDDLE_RIGHT", GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT),
+    ("LEFT_THUMB", GamepadButton::GAMEPAD_BUTTON_LEFT_THUMB),
+    ("RIGHT_THUMB", GamepadButton::GAMEPAD_BUTTON_RIGHT_THUMB),
+];
+
+const GAMEPAD_AXIS_NAMES: [(&str, GamepadAxis); 4] = [
+    ("LEFT_X", GamepadAxis::GAMEPAD_AXIS_LEFT_X),
+    ("LEFT_Y", GamepadAxis::GAMEPAD_AXIS_LEFT_Y),
+    ("RIGHT_X", GamepadAxis::GAMEPAD_AXIS_RIGHT_X),
+    ("RIGHT_Y", GamepadAxis::GAMEPAD_AXIS_RIGHT_Y),
+];
+
+#[derive(Debug)]
+pub enum InputConfigError {
+    Io(io::Error),
+    /// Line number, starting at 1, and what's wrong with it
+    Malformed(usize, String),
+}
+
+impl fmt::Display for InputConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Malformed(line, reason) => write!(f, "malformed input config at line {line}, {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for InputConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Malformed(..) => None,
+        }
+    }
+}
+
+impl From<io::Error> for InputConfigError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PortDevice {
+    Joypad,
+    Zapper,
+    Paddle,
+}
+
+impl PortDevice {
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "joypad" => Some(Self::Joypad),
+            "zapper" => Some(Self::Zapper),
+            "paddle" => Some(Self::Paddle),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Binding {
+    Key(KeyboardKey),
+    /// Gamepad number and button
+    GamepadButton(i32, GamepadButton),
+    /// Gamepad number, axis, and whether it's pushed towards positive values
+    GamepadAxis(i32, GamepadAxis, bool),
+}
+
+impl Binding {
+    fn parse(text: &str) -> Option<Self> {
+        let Some((pad, name)) = text.split_once(':') else {
+            let key = match text.as_bytes() {
+                &[character] => key_from_i32(i32::from(character.to_ascii_uppercase())),
+                _ => KEY_NAMES.iter().find(|(name, _)| name.eq_ignore_ascii_case(text)).map(|&(_, key)| key),
+            };
+            return key.map(Self::Key);
+        };
+
+        let pad = pad.strip_prefix("pad")?.parse().ok()?;
+        if let Some(&(_, button)) = GAMEPAD_BUTTON_NAMES.iter().find(|(button, _)| button.eq_ignore_ascii_case(name)) {
+            return Some(Self::GamepadButton(pad, button));
+        }
+
+        let (axis, is_positive) = match (name.strip_suffix('+'), name.strip_suffix('-')) {
+            (Some(axis), _) => (axis, true),
+            (_, Some(axis)) => (axis, false),
+            _ => return None,
+        };
+        GAMEPAD_AXIS_NAMES.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(axis))
+            .map(|&(_, axis)| Self::GamepadAxis(pad, axis, is_positive))
+    }
+
+    fn is_down(self, rl_handle: &RaylibHandle) -> bool {
+        match self {
+            Self::Key(key) => rl_handle.is_key_down(key),
+            Self::GamepadButton(pad, button) => rl_handle.is_gamepad_available(pad) && rl_handle.is_gamepad_button_down(pad, button),
+            Self::GamepadAxis(pad, axis, is_positive) => {
+                let movement = if rl_handle.is_gamepad_available(pad) { rl_handle.get_gamepad_axis_movement(pad, axis) } else { 0.0 };
+                if is_positive { movement > AXIS_THRESHOLD } else { movement < -AXIS_THRESHOLD }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InputConfig {
+    pub ports: [PortDevice; 2],
+    pub four_score: bool,
+    /// Bindings of each controller, in the order of `BUTTONS`
+    bindings: [[Vec<Binding>; BUTTONS.len()]; PADS_COUNT],
+    pub turbo_period: u64,
+    pub allow_opposite_directions: bool,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        let mut config = Self {
+            ports: [PortDevice::Joypad; 2],
+            four_score: false,
+            bindings: Default::default(),
+            turbo_period: 1,
+            allow_opposite_directions: false,
+        };
+        config.apply(DEFAULT_CONFIG).expect("The default input config is valid");
+
+        config
+    }
+}
+
+impl InputConfig {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, InputConfigError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// The defaults, with the settings of `text` on top
+    pub fn parse(text: &str) -> Result<Self, InputConfigError> {
+        let mut config = Self::default();
+        config.apply(text)?;
+
+        Ok(config)
+    }
+
+    fn apply(&mut self, text: &str) -> Result<(), InputConfigError> {
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.split_once('#').map_or(line, |(line, _)| line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let malformed = |reason: String| InputConfigError::Malformed(line_number, reason);
+            let (name, value) = line.split_once('=').ok_or_else(|| malformed(format!("expected `name = value`, got `{line}`")))?;
+            let (name, value) = (name.trim(), value.trim());
+
+            match name {
+                "port1" | "port2" => {
+                    let port = if name == "port1" { 0 } else { 1 };
+                    self.ports[port] = PortDevice::parse(value).ok_or_else(|| malformed(format!("unknown device `{value}`")))?;
+                }
+                "four_score" => {
+                    self.four_score = value.parse().map_err(|_| malformed(format!("expected true or false, got `{value}`")))?;
+                }
+                "turbo_period" => {
+                    self.turbo_period = value.parse().ok().filter(|&period| period > 0).ok_or_else(|| malformed(format!("bad turbo period `{value}`")))?;
+                }
+                "allow_opposite_directions" => {
+                    self.allow_opposite_directions = value.parse().map_err(|_| malformed(format!("expected true or false, got `{value}`")))?;
+                }
+                _ => {
+                    let (pad, button) = name.split_once('.')
+                        .and_then(|(pad, button)| {
+                            let pad = pad.parse::<usize>().ok().filter(|pad| (1..=PADS_COUNT).contains(pad))?;
+                            Some((pad - 1, BUTTONS.iter().position(|&(name, _)| name == button)?))
+                        })
+                        .ok_or_else(|| malformed(format!("unknown setting `{name}`")))?;
+
+                    self.bindings[pad][button] = value.split(',')
+                        .map(str::trim)
+                        .filter(|binding| !binding.is_empty())
+                        .map(|binding| Binding::parse(binding).ok_or_else(|| malformed(format!("unknown binding `{binding}`"))))
+                        .collect::<Result<_, _>>()?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `Controller` state of `pad` from the bindings that are down, turbo buttons alternate every `turbo_period` frames
+    pub fn controller_state(&self, pad: usize, frame: u64, is_down: impl Fn(Binding) -> bool) -> u8 {
+        let is_turbo_pressed = (frame / self.turbo_period).is_multiple_of(2);
+
+        let mut state = 0;
+        for (i, bindings) in self.bindings[pad].iter().enumerate() {
+            if (i < TURBO_BUTTONS_START || is_turbo_pressed) && bindings.iter().any(|&binding| is_down(binding)) {
+                state |= BUTTONS[i].1;
+            }
+        }
+
+        if !self.allow_opposite_directions {
+            for directions in [Controller::UP | Controller::DOWN, Controller::LEFT | Controller::RIGHT] {
+                if state & directions == directions {
+                    state &= !directions;
+                }
+            }
+        }
+
+        state
+    }
+
+    /// Plugs the configured devices into the ports of `nes`
+    pub fn plug_devices(&self, nes: &mut Nes) {
+        for (port, device) in self.ports.into_iter().enumerate() {
+            nes.input_ports[port] = match device {
+                _ if self.four_score => Box::new(FourScore::new(port)),
+                PortDevice::Joypad => Box::new(Controller::new()),
+                PortDevice::Zapper => Box::new(Zapper::new()),
+                PortDevice::Paddle => Box::new(ArkanoidPaddle::new()),
+            };
+        }
+    }
+
+    /// Feeds the devices of `nes`, the mouse is placed on the NES screen drawn in `screen_area`
+    pub fn update(&self, rl_handle: &RaylibHandle, frame: u64, screen_area: Rectangle, nes: &mut Nes) {
+        nes.set_pad_states(std::array::from_fn(|pad| self.controller_state(pad, frame, |binding| binding.is_down(rl_handle))));
+
+        let mouse = rl_handle.get_mouse_position();
+        let position = screen_area.check_collision_point_rec(mouse).then(|| {
+            let x = (mouse.x - screen_area.x) / screen_area.width * f32::from(NES_SCREEN_WIDTH);
+            let y = (mouse.y - screen_area.y) / screen_area.height * f32::from(NES_SCREEN_HEIGHT);
+            (x as u16, y as u16)
+        });
+        let is_pressed = rl_handle.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT);
+        for device in &mut nes.input_ports {
+            device.set_pointer(position, is_pressed);
+        }
+    }
+}
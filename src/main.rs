@@ -4,31 +4,92 @@
 
 mod tests;
 mod constants;
-mod nes;
 mod display;
+mod audio;
+mod input;
 
 use raylib::prelude::*;
-use nes::Nes;
+use rustynes_core::{Nes, Channel, LoadOptions, Movie, MovieFrame, Rewind, StateError};
+use audio::AudioOutput;
+use input::InputConfig;
 use display::draw::{FlagsDisplay, InstructionHistoryDisplay, NesDisplay, ScreenDisplay, TextBox};
 
 #[allow(clippy::too_many_lines)]
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
+    let mut args = std::env::args().skip(1);
+    let mut rom_path = None;
+    let mut options = LoadOptions::default();
+    let mut play_movie_path = None;
+    let mut record_movie_path = None;
+    let mut input_config_path = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--fds-bios" => options.fds_bios_path = args.next().map(Into::into),
+            "--database" => options.database_path = args.next().map(Into::into),
+            "--play-movie" => play_movie_path = args.next(),
+            "--record-movie" => record_movie_path = args.next(),
+            "--input-config" => input_config_path = args.next(),
+            _ => rom_path = Some(arg),
+        }
+    }
+    let rom_path = rom_path.as_deref().unwrap_or("./ROMS/nestest.nes");
     
     let mut nes = Nes::new();
-    if args.len() == 1 {
-        nes.load_cartridge("./ROMS/nestest.nes");
-    } else {
-        nes.load_cartridge(&args[1]);
-    }
-    nes.reset();
+    let load_result = nes.load_cartridge(rom_path, &options);
 
     let (mut rl_handle, rl_thread) = raylib::init()
         .size(800, 600)
         .title("Rustyness")
         .build();
 
-    NesDisplay::set_options(&mut rl_handle, None, 20, true);
+    if let Err(error) = load_result {
+        eprintln!("Could not load {rom_path}: {error}");
+        show_error(&mut rl_handle, &rl_thread, &format!("Could not load {rom_path}\n\n{error}"));
+        return;
+    }
+    println!("{}", nes.get_cartridge_info());
+    nes.reset();
+    let mut rewind = Rewind::new(constants::REWIND_CAPACITY, constants::REWIND_INTERVAL);
+
+    // Bindings from the given config, or from the default one when it exists
+    let input_config_path = input_config_path.unwrap_or_else(|| constants::INPUT_CONFIG_PATH.to_string());
+    let input_config = if std::path::Path::new(&input_config_path).exists() {
+        InputConfig::from_path(&input_config_path).unwrap_or_else(|error| {
+            eprintln!("Could not load input config {input_config_path}: {error}, using the default bindings");
+            InputConfig::default()
+        })
+    } else {
+        InputConfig::default()
+    };
+    input_config.plug_devices(&mut nes);
+    // Drives turbo buttons
+    let mut input_frame = 0_u64;
+
+    let rom_name = std::path::Path::new(rom_path).file_stem().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut movie_mode = None;
+    if let Some(path) = play_movie_path {
+        match Movie::from_path(&path).and_then(|movie| movie.rewind_to_start(&mut nes).map(|()| movie)) {
+            Ok(movie) => {
+                println!("Playing movie {path}, {} frames", movie.frames.len());
+                movie_mode = Some(MovieMode::Playing { movie, frame: 0 });
+            }
+            Err(error) => eprintln!("Could not play movie {path}: {error}"),
+        }
+    } else if let Some(path) = record_movie_path {
+        nes.power_on();
+        println!("Recording movie to {path}");
+        let mut movie = Movie::new(&rom_name);
+        movie.four_score = input_config.four_score;
+        movie_mode = Some(MovieMode::Recording { movie, path });
+    }
+    // Commands to record with the next frame
+    let mut movie_commands = 0;
+
+    let rl_audio = RaylibAudio::init_audio_device().ok();
+    let mut audio_output = rl_audio.as_ref().map(|audio| AudioOutput::new(audio, nes.get_audio_sample_rate()));
+
+    // Emulation is paced by the audio stream, or by the frame rate when there is no audio device
+    NesDisplay::set_options(&mut rl_handle, audio_output.is_none().then_some(constants::FPS), 20, true);
     let font = NesDisplay::set_font(&mut rl_handle, &rl_thread, "assets/font/Monocraft.ttf", 25);
 
     let mut zero_page = TextBox::new(
@@ -72,6 +133,14 @@ fn main() {
         &font,
     );
 
+    let mut mixer_display = TextBox::new(
+        NesDisplay::mixer_to_string(&nes, false),
+        Vector2::new(cycles_left_display.get_position().x, cycles_left_display.get_position().y + cycles_left_display.get_dimensions().y + 9.0),
+        Color::WHITE,
+        Color::WHITE,
+        &font,
+    );
+
     let mut history_instruction_display = InstructionHistoryDisplay::new(
         Vector2::new(cycles_left_display.get_position().x - 250.0, 10.0 + zero_page.get_position().y + zero_page.get_dimensions().y + 5.0 + program_location.get_dimensions().y + 5.0),
         28,
@@ -97,19 +166,10 @@ fn main() {
     );
 
     while !rl_handle.window_should_close() {
-        let frame_time = rl_handle.get_frame_time();
-
         // Controls
-        nes.controllers[0].check_inputs(
-            rl_handle.is_key_down(KeyboardKey::KEY_Z),
-            rl_handle.is_key_down(KeyboardKey::KEY_X),
-            rl_handle.is_key_down(KeyboardKey::KEY_A),
-            rl_handle.is_key_down(KeyboardKey::KEY_S),
-            rl_handle.is_key_down(KeyboardKey::KEY_UP),
-            rl_handle.is_key_down(KeyboardKey::KEY_DOWN),
-            rl_handle.is_key_down(KeyboardKey::KEY_LEFT),
-            rl_handle.is_key_down(KeyboardKey::KEY_RIGHT),
-        );
+        let screen_area = Rectangle::new(screen_display.get_position().x, screen_display.get_position().y, screen_display.get_dimensions().x, screen_display.get_dimensions().y);
+        input_config.update(&rl_handle, input_frame, screen_area, &mut nes);
+        input_frame += 1;
         
         // Resume / Pause
         if rl_handle.is_key_pressed(KeyboardKey::KEY_SPACE) {
@@ -118,6 +178,54 @@ fn main() {
         // Reset
         if rl_handle.is_key_pressed(KeyboardKey::KEY_R) {
             nes.reset();
+            rewind.clear();
+            movie_commands |= MovieFrame::SOFT_RESET;
+        }
+
+        // Famicom Disk System side switching
+        if rl_handle.is_key_pressed(KeyboardKey::KEY_D) && nes.get_disk_sides_count() > 0 {
+            nes.switch_disk_side();
+            movie_commands |= MovieFrame::FDS_SELECT_SIDE;
+            if let Some(side) = nes.get_disk_side() {
+                println!("Inserting disk {} side {}", side / 2 + 1, if side % 2 == 0 { 'A' } else { 'B' });
+            }
+        }
+
+        // Quick-save slots, F1-F4 loads and holding shift saves
+        for (slot, key) in [KeyboardKey::KEY_F1, KeyboardKey::KEY_F2, KeyboardKey::KEY_F3, KeyboardKey::KEY_F4].into_iter().enumerate() {
+            if rl_handle.is_key_pressed(key) {
+                let path = std::path::Path::new(rom_path).with_extension(format!("ss{}", slot + 1));
+                if rl_handle.is_key_down(KeyboardKey::KEY_LEFT_SHIFT) || rl_handle.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT) {
+                    match std::fs::write(&path, nes.save_state()) {
+                        Ok(()) => println!("Saved state to slot {}", slot + 1),
+                        Err(error) => eprintln!("Could not write {}: {error}", path.display()),
+                    }
+                } else {
+                    match std::fs::read(&path).map_err(StateError::from).and_then(|data| nes.load_state(&data)) {
+                        Ok(()) => {
+                            println!("Loaded state from slot {}", slot + 1);
+                            rewind.clear();
+                            // The movie can't follow the jump
+                            stop_movie(&mut movie_mode);
+                        }
+                        Err(error) => eprintln!("Could not load {}: {error}", path.display()),
+                    }
+                }
+            }
+        }
+
+        // Movie recording from the current state, the movie is saved when stopping
+        if rl_handle.is_key_pressed(KeyboardKey::KEY_F5) {
+            if movie_mode.is_some() {
+                stop_movie(&mut movie_mode);
+            } else {
+                let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
+                let path = format!("movie_{timestamp}.fm2");
+                println!("Recording movie to {path}");
+                let mut movie = Movie::from_state(&rom_name, &nes);
+                movie.four_score = input_config.four_score;
+                movie_mode = Some(MovieMode::Recording { movie, path });
+            }
         }
 
         // Palette cycling
@@ -125,19 +233,77 @@ fn main() {
             nes.cycle_palette();
         }
 
-        if !nes.pause {
-            if nes.timer > 0.0 {
-                nes.timer -= frame_time;
+        // Mute
+        if rl_handle.is_key_pressed(KeyboardKey::KEY_M) {
+            if let Some(audio_output) = &mut audio_output {
+                audio_output.toggle_mute();
+            }
+        }
+
+        // Audio recording, holding shift also records each channel separately
+        if rl_handle.is_key_pressed(KeyboardKey::KEY_W) {
+            if nes.is_recording_audio() {
+                match nes.stop_audio_recording() {
+                    Ok(()) => println!("Audio recording stopped"),
+                    Err(error) => eprintln!("Audio recording failed: {error}"),
+                }
             } else {
-                nes.timer += (1.0 / constants::FPS) - frame_time;
+                let with_stems = rl_handle.is_key_down(KeyboardKey::KEY_LEFT_SHIFT) || rl_handle.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+                let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
+                let path = format!("recording_{timestamp}.wav");
+
+                match nes.start_audio_recording(&path, with_stems) {
+                    Ok(()) => println!("Recording audio to {path}"),
+                    Err(error) => eprintln!("Could not record audio to {path}: {error}"),
+                }
+            }
+        }
+
+        // Mixer, each channel cycles between full, half and no volume
+        for (key, channel) in [KeyboardKey::KEY_ONE, KeyboardKey::KEY_TWO, KeyboardKey::KEY_THREE, KeyboardKey::KEY_FOUR, KeyboardKey::KEY_FIVE, KeyboardKey::KEY_SIX].into_iter().zip(Channel::ALL) {
+            if rl_handle.is_key_pressed(key) {
+                let volume = match nes.get_channel_volume(channel) {
+                    v if v > 0.5 => 0.5,
+                    v if v > 0.0 => 0.0,
+                    _ => 1.0,
+                };
+                nes.set_channel_volume(channel, volume);
+            }
+        }
+
+        if let Some(audio_output) = &mut audio_output {
+            audio_output.update();
+        }
 
-                loop {
-                    nes.tick();
-                    if nes.is_ppu_frame_complete() {
-                        break;
+        // Rewind, one frame per displayed frame while held, paused or not
+        if rl_handle.is_key_down(KeyboardKey::KEY_BACKSPACE) {
+            if rewind.step_back(&mut nes) {
+                // Recording goes on from the earlier frame
+                match &mut movie_mode {
+                    Some(MovieMode::Recording { movie, .. }) => {
+                        movie.frames.pop();
+                        if rl_handle.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+                            movie.rerecord_count += 1;
+                        }
                     }
+                    Some(MovieMode::Playing { frame, .. }) => *frame = frame.saturating_sub(1),
+                    None => {}
+                }
+            }
+            nes.take_audio_samples();
+        } else if !nes.pause {
+            match &mut audio_output {
+                Some(audio_output) => {
+                    while audio_output.needs_samples() {
+                        nes.set_audio_rate_adjustment(audio_output.rate_adjustment());
+                        run_frame(&mut nes, &mut rewind, &mut movie_mode, &mut movie_commands);
+                        audio_output.push(&nes.take_audio_samples());
+                    }
+                }
+                None => {
+                    run_frame(&mut nes, &mut rewind, &mut movie_mode, &mut movie_commands);
+                    nes.take_audio_samples();
                 }
-                nes.set_ppu_frame_complete(false);
             }
         } else if let Some(key) = rl_handle.get_key_pressed() {
             match key {
@@ -158,6 +324,7 @@ fn main() {
                 }
                 // Step into next PPU frame
                 KeyboardKey::KEY_F => {
+                    rewind.push_frame(&nes);
                     loop {
                         nes.tick();
                         if nes.is_ppu_frame_complete() {
@@ -171,10 +338,12 @@ fn main() {
                         }
                     }
                     nes.set_ppu_frame_complete(false);
-                    
+
                 }
                 _ => {}
             }
+            // Audio isn't played while stepping
+            nes.take_audio_samples();
         }
 
         let cycle = nes.get_cpu_info().cycles;
@@ -190,6 +359,7 @@ fn main() {
         flags_display.set_flags(nes.get_cpu_flags());
         history_instruction_display.update(&mut nes);
         cycles_left_display.set_text(format!("Next in\n[{cycle}] cycles"), cycle_text_color);
+        mixer_display.set_text(NesDisplay::mixer_to_string(&nes, audio_output.as_ref().is_some_and(AudioOutput::is_muted)), None);
         screen_display.update(&mut rl_handle, &rl_thread, nes.get_screen());
         pattern_table_display_1.update(&mut rl_handle, &rl_thread, nes.get_pattern_table(0));
         pattern_table_display_2.update(&mut rl_handle, &rl_thread, nes.get_pattern_table(1));
@@ -204,8 +374,67 @@ fn main() {
         flags_display.draw(&mut rl_draw_handle);
         history_instruction_display.draw(&mut rl_draw_handle);
         cycles_left_display.draw(&mut rl_draw_handle);
+        mixer_display.draw(&mut rl_draw_handle);
         screen_display.draw(&mut rl_draw_handle);
         pattern_table_display_1.draw(&mut rl_draw_handle);
         pattern_table_display_2.draw(&mut rl_draw_handle);
     }
+
+    if let Err(error) = nes.stop_audio_recording() {
+        eprintln!("Audio recording failed: {error}");
+    }
+    stop_movie(&mut movie_mode);
+    if let Err(error) = nes.save_battery() {
+        eprintln!("Could not write save file: {error}");
+    }
+}
+
+enum MovieMode {
+    Recording { movie: Movie, path: String },
+    /// `frame` is the next one to play
+    Playing { movie: Movie, frame: usize },
+}
+
+/// Runs a frame with the movie inputs when playing one, and records it for rewinding and in the movie
+fn run_frame(nes: &mut Nes, rewind: &mut Rewind, movie_mode: &mut Option<MovieMode>, movie_commands: &mut u8) {
+    match movie_mode {
+        Some(MovieMode::Recording { movie, .. }) => movie.push_frame(nes, *movie_commands),
+        Some(MovieMode::Playing { movie, frame }) => {
+            if movie.apply_frame(*frame, nes) {
+                *frame += 1;
+            } else {
+                println!("Movie playback finished");
+                *movie_mode = None;
+            }
+        }
+        None => {}
+    }
+    *movie_commands = 0;
+
+    rewind.push_frame(nes);
+    nes.run_frame();
+}
+
+/// Saves the movie being recorded
+fn stop_movie(movie_mode: &mut Option<MovieMode>) {
+    match movie_mode.take() {
+        Some(MovieMode::Recording { movie, path }) => match movie.save(&path) {
+            Ok(()) => println!("Saved movie to {path}, {} frames", movie.frames.len()),
+            Err(error) => eprintln!("Could not write movie {path}: {error}"),
+        },
+        Some(MovieMode::Playing { .. }) => println!("Movie playback stopped"),
+        None => {}
+    }
+}
+
+/// Shows `message` until the window is closed
+fn show_error(rl_handle: &mut RaylibHandle, rl_thread: &RaylibThread, message: &str) {
+    rl_handle.set_target_fps(constants::FPS);
+
+    while !rl_handle.window_should_close() {
+        let mut rl_draw_handle = rl_handle.begin_drawing(rl_thread);
+
+        rl_draw_handle.clear_background(Color::new(50, 50, 50, 255));
+        rl_draw_handle.draw_text(message, 10, 10, 20, Color::RED);
+    }
 }